@@ -0,0 +1,314 @@
+use crate::{Bdd, BddPartialValuation, BddPointer, BddValuation, BddVariable, BddVariableSet};
+use std::collections::{BTreeSet, HashSet};
+
+/// Conversion of `Bdd`s into the algebraic normal form (a XOR of monomials, also known as
+/// the Zhegalkin polynomial) and back.
+impl Bdd {
+    /// Compute the algebraic normal form of this `Bdd`: the function is the XOR of the returned
+    /// monomials, where each monomial is a conjunction of (positive) variables and the empty
+    /// monomial represents the constant `true`.
+    ///
+    /// The monomials are listed in the order of their `BddValuation` encoding (see
+    /// `Bdd::anf_monomials`). Be aware that the number of monomials can be exponential even for
+    /// small `Bdd`s (e.g. for $\neg x_1 \land \ldots \land \neg x_n$).
+    pub fn to_anf(&self) -> Vec<BTreeSet<BddVariable>> {
+        self.anf_monomials()
+            .sat_valuations()
+            .map(|valuation| monomial_of(&valuation))
+            .collect()
+    }
+
+    /// Compute the set of monomials of the algebraic normal form of this `Bdd` as another
+    /// `Bdd`: a valuation belongs to the result if the conjunction of variables which are
+    /// `true` in the valuation is a monomial of the algebraic normal form.
+    ///
+    /// The result is obtained using the binary Möbius transform, applied to every node once
+    /// (bottom-up): for a node $f = x ? f_1 : f_0$, the monomials are
+    /// $M(f) = (\neg x \land M(f_0)) \lor (x \land (M(f_0) \oplus M(f_1)))$. Since the
+    /// transform is its own inverse, applying it to the result gives the original function.
+    pub fn anf_monomials(&self) -> Bdd {
+        mobius_transform(self)
+    }
+
+    /// Compute the algebraic degree of this `Bdd`, i.e. the number of variables in the largest
+    /// monomial of its algebraic normal form. The degree of `false` is undefined (`None`).
+    ///
+    /// The degree is computed as the longest path in the `Bdd` of monomials, where only
+    /// the edges which set a variable to `true` (including skipped variables) are counted.
+    pub fn algebraic_degree(&self) -> Option<usize> {
+        let monomials = self.anf_monomials();
+        let num_vars = usize::from(monomials.num_vars());
+        let level = |node: BddPointer| {
+            if node.is_terminal() {
+                num_vars
+            } else {
+                usize::from(monomials.var_of(node).0)
+            }
+        };
+        // Degree of the largest monomial in the sub-graph of each node (restricted to
+        // the variables starting at the node's level).
+        let mut degrees: Vec<Option<usize>> = vec![None, Some(0)];
+        for node in monomials.pointers().skip(2) {
+            let var = level(node);
+            let branch = |child: BddPointer, value: usize| {
+                degrees[child.to_index()].map(|d| d + value + (level(child) - var - 1))
+            };
+            let degree =
+                branch(monomials.low_link_of(node), 0).max(branch(monomials.high_link_of(node), 1));
+            degrees.push(degree);
+        }
+        let root = monomials.root_pointer();
+        degrees[root.to_index()].map(|d| d + level(root))
+    }
+
+    /// Compute the algebraic immunity of this `Bdd`: the smallest degree of a non-zero function
+    /// $g$ such that $f \land g$ or $\neg f \land g$ is `false` (an *annihilator* of $f$ or
+    /// $\neg f$). The result is `None` if the algebraic immunity is larger than `limit`.
+    ///
+    /// For every degree `d`, the existence of an annihilator is decided by solving a system
+    /// of linear equations over $GF(2)$ with one unknown per monomial of degree at most `d`
+    /// and one equation per satisfying valuation. The computation is therefore only feasible
+    /// for functions with a moderate number of variables (the algebraic immunity is always
+    /// at most $\lceil n / 2 \rceil$, so `limit` does not need to be larger).
+    pub fn algebraic_immunity(&self, limit: usize) -> Option<usize> {
+        let negation = self.not();
+        let num_vars = usize::from(self.num_vars());
+        let limit = limit.min(num_vars / 2 + num_vars % 2);
+        (0..=limit).find(|d| has_annihilator(self, *d) || has_annihilator(&negation, *d))
+    }
+}
+
+impl BddVariableSet {
+    /// Create a `Bdd` from its algebraic normal form: the XOR of the given monomials, where
+    /// each monomial is a conjunction of variables and the empty monomial represents `true`.
+    ///
+    /// Monomials which appear an even number of times cancel out.
+    ///
+    /// *Panics:* All variables must be valid variables in this set.
+    pub fn mk_from_anf(&self, monomials: &[BTreeSet<BddVariable>]) -> Bdd {
+        let mut odd: HashSet<&BTreeSet<BddVariable>> = HashSet::new();
+        for monomial in monomials {
+            if !odd.remove(monomial) {
+                odd.insert(monomial);
+            }
+        }
+        let cubes: Vec<Bdd> = odd
+            .into_iter()
+            .map(|monomial| {
+                let values: Vec<(BddVariable, bool)> = self
+                    .variables()
+                    .into_iter()
+                    .map(|v| (v, monomial.contains(&v)))
+                    .collect();
+                for var in monomial {
+                    assert!(var.0 < self.num_vars(), "Invalid variable id.");
+                }
+                self.mk_conjunctive_clause(&BddPartialValuation::from_values(&values))
+            })
+            .collect();
+        if cubes.is_empty() {
+            return self.mk_false();
+        }
+        mobius_transform(&Bdd::union_all(&cubes))
+    }
+}
+
+/// **(internal)** The monomial encoded by the given valuation.
+fn monomial_of(valuation: &BddValuation) -> BTreeSet<BddVariable> {
+    (0..valuation.num_vars())
+        .map(BddVariable)
+        .filter(|v| valuation[*v])
+        .collect()
+}
+
+/// **(internal)** Check if there is a non-zero function of degree at most `degree` which is
+/// `false` in every satisfying valuation of `bdd`.
+///
+/// Every satisfying valuation gives one linear equation: the sum of the coefficients of all
+/// monomials contained in the valuation must be zero. The equations are added to a row echelon
+/// basis until it has full rank (only the zero function satisfies them) or until all
+/// valuations are processed (a non-zero annihilator exists).
+fn has_annihilator(bdd: &Bdd, degree: usize) -> bool {
+    let mut monomials: Vec<Vec<BddVariable>> = vec![Vec::new()];
+    let mut last_degree = 0..1;
+    for _ in 0..degree {
+        let mut next = Vec::new();
+        for monomial in &monomials[last_degree.clone()] {
+            let first = monomial.last().map(|v| v.0 + 1).unwrap_or(0);
+            for var in first..bdd.num_vars() {
+                let mut extended = monomial.clone();
+                extended.push(BddVariable(var));
+                next.push(extended);
+            }
+        }
+        last_degree = monomials.len()..(monomials.len() + next.len());
+        monomials.append(&mut next);
+    }
+
+    let words = monomials.len() / 64 + 1;
+    // `basis[i]` is a row whose lowest set bit is `i`.
+    let mut basis: Vec<Option<Vec<u64>>> = vec![None; monomials.len()];
+    let mut rank = 0;
+    for valuation in bdd.sat_valuations() {
+        let mut row = vec![0u64; words];
+        for (i, monomial) in monomials.iter().enumerate() {
+            if monomial.iter().all(|v| valuation[*v]) {
+                row[i / 64] |= 1 << (i % 64);
+            }
+        }
+        while let Some(pivot) = lowest_bit(&row) {
+            match &basis[pivot] {
+                Some(base) => row.iter_mut().zip(base).for_each(|(r, b)| *r ^= b),
+                None => {
+                    basis[pivot] = Some(row);
+                    rank += 1;
+                    break;
+                }
+            }
+        }
+        if rank == monomials.len() {
+            return false;
+        }
+    }
+    true
+}
+
+/// **(internal)** Index of the lowest set bit in the given bit vector.
+fn lowest_bit(row: &[u64]) -> Option<usize> {
+    row.iter()
+        .enumerate()
+        .find(|(_, word)| **word != 0)
+        .map(|(i, word)| i * 64 + word.trailing_zeros() as usize)
+}
+
+/// **(internal)** Compute the binary Möbius transform of the given `Bdd`.
+///
+/// The transform of a node only constrains the variables below the node's variable: variables
+/// skipped by an edge of the source `Bdd` do not influence the function, hence they cannot
+/// appear in any monomial and are fixed to `false` when the result of the child is used.
+fn mobius_transform(bdd: &Bdd) -> Bdd {
+    let num_vars = bdd.num_vars();
+    // Conjunction of negative literals of all variables in the range `from..to`.
+    let absent = |from: u16, to: u16| {
+        let values: Vec<(BddVariable, bool)> =
+            (from..to).map(|v| (BddVariable(v), false)).collect();
+        Bdd::mk_cube(num_vars, &BddPartialValuation::from_values(&values))
+    };
+    let level = |node: usize| {
+        if node < 2 {
+            num_vars
+        } else {
+            bdd.0[node].var.0
+        }
+    };
+
+    let mut transformed: Vec<Bdd> = Vec::with_capacity(bdd.size());
+    transformed.push(Bdd::mk_false(num_vars));
+    transformed.push(Bdd::mk_true(num_vars));
+    for node in bdd.pointers().skip(2) {
+        let var = bdd.var_of(node);
+        let (low, high) = (bdd.low_link_of(node), bdd.high_link_of(node));
+        let low_monomials =
+            transformed[low.to_index()].and(&absent(var.0 + 1, level(low.to_index())));
+        let high_monomials =
+            transformed[high.to_index()].and(&absent(var.0 + 1, level(high.to_index())));
+        let difference = low_monomials.xor(&high_monomials);
+        let result = Bdd::mk_not_var(num_vars, var)
+            .and(&low_monomials)
+            .or(&Bdd::mk_var(num_vars, var).and(&difference));
+        transformed.push(result);
+    }
+    let root = bdd.size() - 1;
+    transformed[root].and(&absent(0, level(root)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{BddVariable, BddVariableSet};
+    use std::collections::BTreeSet;
+
+    fn monomial(vars: &[u16]) -> BTreeSet<BddVariable> {
+        vars.iter().map(|v| BddVariable(*v)).collect()
+    }
+
+    #[test]
+    fn anf_basic() {
+        let variables = mk_5_variable_set();
+        assert!(variables.mk_false().to_anf().is_empty());
+        assert_eq!(vec![monomial(&[])], variables.mk_true().to_anf());
+        let mut anf = variables.eval_expression_string("v1 | v3").to_anf();
+        anf.sort();
+        // x | y = x ^ y ^ xy
+        assert_eq!(vec![monomial(&[0]), monomial(&[0, 2]), monomial(&[2])], anf);
+        let mut anf = variables.eval_expression_string("!v2 ^ (v4 & v5)").to_anf();
+        anf.sort();
+        assert_eq!(vec![monomial(&[]), monomial(&[1]), monomial(&[3, 4])], anf);
+    }
+
+    #[test]
+    fn anf_round_trip() {
+        let variables = mk_5_variable_set();
+        for expression in &[
+            "(v1 => v2) & (v3 | v4) & !(v4 & v5)",
+            "v1 ^ v2 ^ v3 ^ v4 ^ v5",
+            "!v1 & !v2 & !v3 & !v4 & !v5",
+            "(v2 & v4) | !v5",
+            "true",
+            "false",
+        ] {
+            let bdd = variables.eval_expression_string(expression);
+            assert_eq!(bdd, variables.mk_from_anf(&bdd.to_anf()));
+            assert_eq!(bdd, bdd.anf_monomials().anf_monomials());
+        }
+        // The negation of a conjunction of five negative literals has all 31 non-empty
+        // monomials.
+        let bdd = variables.eval_expression_string("v1 | v2 | v3 | v4 | v5");
+        assert_eq!(31, bdd.to_anf().len());
+    }
+
+    #[test]
+    fn mk_from_anf_cancellation() {
+        let variables = BddVariableSet::new_anonymous(3);
+        let monomials = vec![monomial(&[0, 1]), monomial(&[2]), monomial(&[0, 1])];
+        assert_eq!(
+            variables.eval_expression_string("x_2"),
+            variables.mk_from_anf(&monomials)
+        );
+        assert!(variables.mk_from_anf(&[]).is_false());
+    }
+
+    #[test]
+    fn anf_algebraic_degree() {
+        let variables = mk_5_variable_set();
+        let degree = |e: &str| variables.eval_expression_string(e).algebraic_degree();
+        assert_eq!(None, degree("false"));
+        assert_eq!(Some(0), degree("true"));
+        assert_eq!(Some(1), degree("!v2 ^ v4 ^ v5"));
+        assert_eq!(Some(3), degree("v1 & v3 & v5"));
+        assert_eq!(Some(2), degree("(v1 & v3) ^ (v2 & v5) ^ v4"));
+        assert_eq!(Some(5), degree("v1 | v2 | v3 | v4 | v5"));
+        assert_eq!(Some(4), degree("v2 | v3 | v4 | v5"));
+    }
+
+    #[test]
+    fn anf_algebraic_immunity() {
+        let variables = mk_5_variable_set();
+        let immunity = |e: &str, limit: usize| {
+            variables
+                .eval_expression_string(e)
+                .algebraic_immunity(limit)
+        };
+        assert_eq!(Some(0), immunity("false", 5));
+        assert_eq!(Some(0), immunity("true", 5));
+        assert_eq!(Some(1), immunity("v2", 5));
+        assert_eq!(Some(1), immunity("v1 & v2 & v3 & v4", 5));
+        assert_eq!(Some(1), immunity("v1 ^ v2 ^ v3 ^ v4 ^ v5", 5));
+        // The majority function has the maximal algebraic immunity.
+        let majority = "(v1 & v2 & v3) | (v1 & v2 & v4) | (v1 & v2 & v5) | (v1 & v3 & v4) \
+            | (v1 & v3 & v5) | (v1 & v4 & v5) | (v2 & v3 & v4) | (v2 & v3 & v5) \
+            | (v2 & v4 & v5) | (v3 & v4 & v5)";
+        assert_eq!(Some(3), immunity(majority, 5));
+        assert_eq!(None, immunity(majority, 2));
+    }
+}
@@ -0,0 +1,337 @@
+use crate::_impl_bdd_expr::SizeOrdered;
+use crate::op_function::BooleanOp;
+use crate::{Bdd, BddExpr};
+use fxhash::FxBuildHasher;
+use std::borrow::Cow;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// Operations which process whole slices of `Bdd`s at once.
+impl Bdd {
+    /// Compute the conjunction of every pair of `Bdd`s with the same index in `left`
+    /// and `right`, i.e. `result[i] = left[i] & right[i]`.
+    ///
+    /// Each distinct pair runs its own `apply`, so there is no operation cache shared across
+    /// the batch. The only savings come from the pairs themselves: pairs which repeat (in
+    /// either order) are only computed once, and pairs with a constant or identical operand
+    /// are resolved without running `apply` at all. (A cache shared by the whole batch would
+    /// mostly hold unrelated sub-problems and make the common case slower.) For a parallel
+    /// version, see `Bdd::par_pairwise_and`.
+    ///
+    /// *Panics:* `left` and `right` must have the same length. As with all binary operations,
+    /// the operands must have the same number of variables.
+    pub fn pairwise_and(left: &[Bdd], right: &[Bdd]) -> Vec<Bdd> {
+        if left.len() != right.len() {
+            panic!("Batch length mismatch: {} != {}.", left.len(), right.len());
+        }
+        let mut computed: HashMap<(&Bdd, &Bdd), usize, FxBuildHasher> =
+            HashMap::with_capacity_and_hasher(left.len(), FxBuildHasher::default());
+        let mut results: Vec<Bdd> = Vec::with_capacity(left.len());
+        for (l, r) in left.iter().zip(right.iter()) {
+            let key = and_key(l, r);
+            let result = match computed.get(&key) {
+                Some(i) => results[*i].clone(),
+                None => {
+                    computed.insert(key, results.len());
+                    and_shortcut(l, r).unwrap_or_else(|| l.and(r))
+                }
+            };
+            results.push(result);
+        }
+        results
+    }
+
+    /// Compute the disjunction of all `sets`.
+    ///
+    /// Duplicate operands are only considered once, and the operands are combined from
+    /// the smallest to the largest (the same way `BddExpr::build` evaluates an n-ary
+    /// disjunction), stopping early once the result is `true`. To perform the union using
+    /// multiple threads, use `Bdd::par_fold_op` with `Bdd::or`.
+    ///
+    /// *Panics:* `sets` must not be empty. As with all binary operations, the operands must
+    /// have the same number of variables.
+    pub fn union_all(sets: &[Bdd]) -> Bdd {
+        if sets.is_empty() {
+            panic!("Cannot compute a union of an empty list of Bdds.");
+        }
+        for set in sets {
+            sets[0].check_compatible(set);
+        }
+        let mut seen: HashSet<&Bdd, FxBuildHasher> =
+            HashSet::with_capacity_and_hasher(sets.len(), FxBuildHasher::default());
+        let mut expression: Option<BddExpr> = None;
+        for set in sets {
+            if set.is_false() || !seen.insert(set) {
+                continue;
+            }
+            expression = Some(match expression {
+                None => BddExpr::from(set),
+                Some(expression) => expression.or(set),
+            });
+        }
+        match expression {
+            Some(expression) => expression.build(),
+            None => sets[0].clone(), // all operands are `false`
+        }
+    }
+
+    /// **(internal)** Combine all `operands` using `op`, which must be `And`, `Or` or `Xor`.
+    ///
+    /// The operands are kept in a heap ordered by size and the two smallest are always
+    /// combined first. Operands which are neutral for `op` are skipped, and the evaluation
+    /// stops once the result is absorbing (`false` for conjunction, `true` for disjunction).
+    /// Duplicate operands are resolved before any `apply` runs: conjunction and disjunction
+    /// ignore them, while for `xor`, they cancel out in pairs (and `true` operands only
+    /// negate the result). If no operands remain, the result is the neutral constant.
+    ///
+    /// *Panics:* All operands must have `num_vars` variables.
+    pub(crate) fn fold_smallest_first<I>(num_vars: u16, operands: I, op: BooleanOp) -> Bdd
+    where
+        I: IntoIterator<Item = Bdd>,
+    {
+        let (neutral, absorbing) = match op {
+            BooleanOp::And => (true, Some(false)),
+            BooleanOp::Or => (false, Some(true)),
+            BooleanOp::Xor => (false, None),
+            _ => panic!("Operator {:?} cannot be used in an n-ary fold.", op),
+        };
+        let apply = |left: &Bdd, right: &Bdd| match op {
+            BooleanOp::And => left.and(right),
+            BooleanOp::Or => left.or(right),
+            _ => left.xor(right),
+        };
+        let identity = if neutral {
+            Bdd::mk_true(num_vars)
+        } else {
+            Bdd::mk_false(num_vars)
+        };
+        let is_absorbing = |bdd: &Bdd| match absorbing {
+            Some(true) => bdd.is_true(),
+            Some(false) => bdd.is_false(),
+            None => false,
+        };
+        let mut negate = false;
+        let mut remaining: Vec<Bdd> = Vec::new();
+        for bdd in operands {
+            identity.check_compatible(&bdd);
+            if is_absorbing(&bdd) {
+                return bdd;
+            }
+            if bdd == identity {
+                continue;
+            }
+            if op == BooleanOp::Xor && bdd.is_true() {
+                negate = !negate;
+            } else {
+                remaining.push(bdd);
+            }
+        }
+        let mut counts: HashMap<&Bdd, usize, FxBuildHasher> =
+            HashMap::with_capacity_and_hasher(remaining.len(), FxBuildHasher::default());
+        for bdd in &remaining {
+            *counts.entry(bdd).or_insert(0) += 1;
+        }
+        let mut queue = BinaryHeap::with_capacity(counts.len());
+        for (i, bdd) in remaining.iter().enumerate() {
+            if let Some(count) = counts.remove(bdd) {
+                if op != BooleanOp::Xor || count % 2 == 1 {
+                    queue.push(SizeOrdered(Reverse(bdd.size()), i, Cow::Borrowed(bdd)));
+                }
+            }
+        }
+        let mut next_id = remaining.len();
+        while queue.len() > 1 {
+            let SizeOrdered(_, _, left) = queue.pop().unwrap();
+            let SizeOrdered(_, _, right) = queue.pop().unwrap();
+            let result = apply(&left, &right);
+            if is_absorbing(&result) {
+                return result;
+            }
+            queue.push(SizeOrdered(
+                Reverse(result.size()),
+                next_id,
+                Cow::Owned(result),
+            ));
+            next_id += 1;
+        }
+        let result = match queue.pop() {
+            Some(SizeOrdered(_, _, result)) => result.into_owned(),
+            None => identity,
+        };
+        if negate {
+            result.not()
+        } else {
+            result
+        }
+    }
+}
+
+/// **(internal)** The cache key of a conjunction in `Bdd::pairwise_and`. Conjunction is
+/// commutative, so the operands are always ordered: by size first, and operands of the same
+/// size by their nodes.
+fn and_key<'a>(left: &'a Bdd, right: &'a Bdd) -> (&'a Bdd, &'a Bdd) {
+    let node_key = |bdd: &'a Bdd| {
+        bdd.nodes()
+            .map(|node| (node.var, node.low_link, node.high_link))
+    };
+    let ordering = left
+        .size()
+        .cmp(&right.size())
+        .then_with(|| node_key(left).cmp(node_key(right)));
+    if ordering == Ordering::Greater {
+        (right, left)
+    } else {
+        (left, right)
+    }
+}
+
+/// **(internal)** Resolve a conjunction which does not need a full `apply`, i.e. when one
+/// of the operands is constant or both operands are equal.
+fn and_shortcut(left: &Bdd, right: &Bdd) -> Option<Bdd> {
+    if left.num_vars() != right.num_vars() {
+        // Let `and` report the error.
+        None
+    } else if left.is_false() || right.is_true() || left == right {
+        Some(left.clone())
+    } else if right.is_false() || left.is_true() {
+        Some(right.clone())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{Bdd, BddVariableSet};
+
+    #[test]
+    fn pairwise_and_basic() {
+        let variables = mk_5_variable_set();
+        let left: Vec<Bdd> = ["v1 | v2", "v3", "true", "v4 => v5", "v1 | v2", "false"]
+            .iter()
+            .map(|e| variables.eval_expression_string(e))
+            .collect();
+        let right: Vec<Bdd> = ["!v2", "v3", "v1 ^ v5", "v4", "!v2", "v2"]
+            .iter()
+            .map(|e| variables.eval_expression_string(e))
+            .collect();
+        let results = Bdd::pairwise_and(&left, &right);
+        assert_eq!(left.len(), results.len());
+        for i in 0..left.len() {
+            assert_eq!(left[i].and(&right[i]), results[i]);
+        }
+        assert!(Bdd::pairwise_and(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn pairwise_and_commutative_pairs() {
+        let variables = BddVariableSet::new_anonymous(12);
+        let expressions = [
+            "x_0 | x_5",
+            "x_5 ^ x_11",
+            "!x_0 | (x_3 & x_7)",
+            "x_1 & x_2 & !x_9",
+            "(x_3 | x_4) ^ (x_8 & x_10)",
+            "x_11",
+        ];
+        let bdds: Vec<Bdd> = expressions
+            .iter()
+            .map(|e| variables.eval_expression_string(e))
+            .collect();
+        // Every pair in both orders, so each conjunction appears twice in the batch (also
+        // when both operands have the same size).
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        for a in &bdds {
+            for b in &bdds {
+                left.push(a.clone());
+                right.push(b.clone());
+            }
+        }
+        let results = Bdd::pairwise_and(&left, &right);
+        for i in 0..left.len() {
+            assert_eq!(left[i].and(&right[i]), results[i]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn pairwise_and_length_mismatch() {
+        let variables = mk_5_variable_set();
+        Bdd::pairwise_and(&[variables.mk_true()], &[]);
+    }
+
+    #[test]
+    fn and_all_basic() {
+        let variables = mk_5_variable_set();
+        let clauses: Vec<Bdd> = ["v1 | !v2", "v2 | v3 | !v5", "true", "!v1 | v4", "v1 | !v2"]
+            .iter()
+            .map(|e| variables.eval_expression_string(e))
+            .collect();
+        let expected = clauses
+            .iter()
+            .fold(variables.mk_true(), |result, clause| result.and(clause));
+        assert_eq!(expected, variables.and_all(clauses.clone()));
+        assert!(variables.and_all(Vec::new()).is_true());
+        let with_false = clauses.into_iter().chain(vec![variables.mk_false()]);
+        assert!(variables.and_all(with_false).is_false());
+    }
+
+    #[test]
+    fn or_all_xor_all_basic() {
+        let variables = mk_5_variable_set();
+        let operands: Vec<Bdd> = [
+            "v1 & !v2", "v3 ^ v5", "false", "v2 & v4", "v1 & !v2", "true",
+        ]
+        .iter()
+        .map(|e| variables.eval_expression_string(e))
+        .collect();
+        let disjunction = operands[..5]
+            .iter()
+            .fold(variables.mk_false(), |result, operand| result.or(operand));
+        assert_eq!(disjunction, variables.or_all(operands[..5].to_vec()));
+        assert!(variables.or_all(operands.clone()).is_true());
+        assert!(variables.or_all(Vec::new()).is_false());
+
+        let parity = operands
+            .iter()
+            .fold(variables.mk_false(), |result, operand| result.xor(operand));
+        assert_eq!(parity, variables.xor_all(operands.clone()));
+        assert_eq!(
+            variables.eval_expression_string("!(v3 ^ v5 ^ (v2 & v4))"),
+            variables.xor_all(operands)
+        );
+        assert!(variables.xor_all(Vec::new()).is_false());
+        let same = variables.mk_var_by_name("v1");
+        assert!(variables.xor_all(vec![same.clone(), same]).is_false());
+    }
+
+    #[test]
+    #[should_panic]
+    fn and_all_incompatible() {
+        let variables = mk_5_variable_set();
+        variables.and_all(vec![variables.mk_var_by_name("v1"), Bdd::mk_true(4)]);
+    }
+
+    #[test]
+    fn union_all_basic() {
+        let variables = mk_5_variable_set();
+        let sets: Vec<Bdd> = ["v1 & v2", "false", "v3 & !v4", "v1 & v2", "v5"]
+            .iter()
+            .map(|e| variables.eval_expression_string(e))
+            .collect();
+        assert_eq!(
+            variables.eval_expression_string("(v1 & v2) | (v3 & !v4) | v5"),
+            Bdd::union_all(&sets)
+        );
+        assert_eq!(
+            variables.mk_false(),
+            Bdd::union_all(&[variables.mk_false(), variables.mk_false()])
+        );
+        assert_eq!(
+            variables.mk_true(),
+            Bdd::union_all(&[sets[0].clone(), variables.mk_true(), sets[2].clone()])
+        );
+    }
+}
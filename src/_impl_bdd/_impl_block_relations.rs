@@ -0,0 +1,524 @@
+use super::_impl_node_store::NodeStore;
+use crate::{Bdd, BddPointer, BddVariable};
+use std::cmp::Ordering;
+
+impl Bdd {
+    /// **(internal)** Create a `Bdd` of all valuations where the values of `block_a` and
+    /// `block_b` differ in at most `k` positions (`block_a[i]` is compared with `block_b[i]`).
+    ///
+    /// The relation is built directly, one pair of variables at a time (bottom-up), where
+    /// the intermediate functions are indexed by the remaining number of allowed differences.
+    /// When the two blocks are interleaved in the variable ordering, this creates
+    /// $O(n \cdot k)$ nodes without any intermediate `Bdd`s.
+    ///
+    /// *Panics:* The blocks must have the same length and contain unique valid variables
+    /// (no variable can appear in both blocks).
+    pub(crate) fn mk_block_hamming_at_most(
+        num_vars: u16,
+        block_a: &[BddVariable],
+        block_b: &[BddVariable],
+        k: usize,
+    ) -> Bdd {
+        let pairs = block_pairs(num_vars, block_a, block_b);
+        let k = k.min(pairs.len());
+        let mut store = NodeStore::new(num_vars, pairs.len() * (k + 1) * 3);
+        // `layer[d]` is the relation on the already processed pairs with at most `d`
+        // differences.
+        let mut layer: Vec<BddPointer> = vec![BddPointer::one(); k + 1];
+        for (a, b) in pairs.into_iter().rev() {
+            layer = (0..=k)
+                .map(|d| {
+                    let same = layer[d];
+                    let different = if d == 0 {
+                        BddPointer::zero()
+                    } else {
+                        layer[d - 1]
+                    };
+                    let a_false = store.mk_select(b, same, different);
+                    let a_true = store.mk_select(b, different, same);
+                    store.mk_select(a, a_false, a_true)
+                })
+                .collect();
+        }
+        store.into_bdd(layer[k])
+    }
+
+    /// **(internal)** Create a `Bdd` of all valuations where the unsigned value of `block`
+    /// is in the interval `[lo, hi]` (inclusive). The first variable of the block is the most
+    /// significant bit.
+    ///
+    /// The relation is built directly, bit by bit (bottom-up), where the intermediate
+    /// functions are indexed by whether the processed prefix still equals the prefix of `lo`
+    /// and of `hi`. When the block is ordered from the most significant bit in the variable
+    /// ordering, this creates at most $4 \cdot n$ nodes.
+    ///
+    /// *Panics:* The block must contain unique valid variables.
+    pub(crate) fn mk_block_range(num_vars: u16, block: &[BddVariable], lo: u64, hi: u64) -> Bdd {
+        check_block_variables(num_vars, block.to_vec());
+        let hi = hi.min(block_mask(block.len()));
+        if lo > hi {
+            return Bdd::mk_false(num_vars);
+        }
+        let bit = |value: u64, i: usize| -> bool {
+            let significance = block.len() - 1 - i;
+            significance < 64 && (value >> significance) & 1 == 1
+        };
+        let mut store = NodeStore::new(num_vars, block.len() * 4 * 3);
+        // `layer[2 * tight_lo + tight_hi]` is the relation on the already processed bits,
+        // where `tight_lo` (`tight_hi`) means the remaining prefix is equal to the prefix of
+        // `lo` (`hi`), so the bound still has to be checked.
+        let mut layer: [BddPointer; 4] = [BddPointer::one(); 4];
+        for (i, var) in block.iter().enumerate().rev() {
+            let (lo_bit, hi_bit) = (bit(lo, i), bit(hi, i));
+            let mut next = [BddPointer::zero(); 4];
+            for (state, result) in next.iter_mut().enumerate() {
+                let (tight_lo, tight_hi) = (state & 2 != 0, state & 1 != 0);
+                let child = |value: bool| -> BddPointer {
+                    if (tight_lo && !value && lo_bit) || (tight_hi && value && !hi_bit) {
+                        BddPointer::zero()
+                    } else {
+                        let tight_lo = tight_lo && value == lo_bit;
+                        let tight_hi = tight_hi && value == hi_bit;
+                        layer[2 * usize::from(tight_lo) + usize::from(tight_hi)]
+                    }
+                };
+                let (low, high) = (child(false), child(true));
+                *result = store.mk_select(*var, low, high);
+            }
+            layer = next;
+        }
+        store.into_bdd(layer[3])
+    }
+
+    /// **(internal)** Create a `Bdd` of all valuations where the unsigned value of `block`
+    /// is in the cyclic interval from `lo` to `hi` (inclusive) modulo $2^n$. The bounds are
+    /// taken modulo $2^n$ as well, and if `lo > hi`, the interval wraps around, i.e. it is
+    /// $[lo, 2^n - 1] \cup [0, hi]$. The first variable of the block is the most significant bit.
+    ///
+    /// A wrapping interval is the complement of the interval $[hi + 1, lo - 1]$, so it has the
+    /// same size as the relation of `mk_block_range`.
+    ///
+    /// *Panics:* The block must contain unique valid variables.
+    pub(crate) fn mk_block_cyclic_range(
+        num_vars: u16,
+        block: &[BddVariable],
+        lo: u64,
+        hi: u64,
+    ) -> Bdd {
+        let mask = block_mask(block.len());
+        let (lo, hi) = (lo & mask, hi & mask);
+        if lo <= hi {
+            Bdd::mk_block_range(num_vars, block, lo, hi)
+        } else if lo == hi + 1 {
+            check_block_variables(num_vars, block.to_vec());
+            Bdd::mk_true(num_vars)
+        } else {
+            Bdd::mk_block_range(num_vars, block, hi + 1, lo - 1).not()
+        }
+    }
+
+    /// **(internal)** Create a `Bdd` of all valuations where the modular difference
+    /// $(b - a) \bmod 2^n$ of the unsigned values of `block_b` and `block_a` is in the cyclic
+    /// interval from `lo` to `hi` (see `mk_block_cyclic_range`). The first variable of each
+    /// block is the most significant bit.
+    ///
+    /// The difference is computed from the least significant bit (the borrow propagates
+    /// upwards), so the relation is built by an automaton which reads one pair of bits at
+    /// a time, starting with the least significant pair. Its state is the borrow and the
+    /// comparison of the processed bits of the difference with the same bits of `lo` and `hi`
+    /// (18 states in total). When the blocks are interleaved in the variable ordering, every
+    /// level of the result has a bounded number of nodes. However, the least significant
+    /// pair is at the bottom of the ordering, so `mk_select` has to push it below the nodes
+    /// of the more significant pairs, and the construction takes $O(n^2)$ time.
+    ///
+    /// *Panics:* The blocks must have the same length and contain unique valid variables
+    /// (no variable can appear in both blocks).
+    pub(crate) fn mk_block_difference_range(
+        num_vars: u16,
+        block_a: &[BddVariable],
+        block_b: &[BddVariable],
+        lo: u64,
+        hi: u64,
+    ) -> Bdd {
+        check_block_sizes(block_a, block_b);
+        check_block_variables(num_vars, block_a.iter().chain(block_b).cloned().collect());
+        let n = block_a.len();
+        let mask = block_mask(n);
+        let (lo, hi) = (lo & mask, hi & mask);
+        let bit = |value: u64, significance: usize| -> bool {
+            significance < 64 && (value >> significance) & 1 == 1
+        };
+        // A state is `9 * borrow + 3 * cmp_lo + cmp_hi`, where `cmp_lo` (`cmp_hi`) compares
+        // the processed bits of the difference with the same bits of `lo` (`hi`).
+        let state = |borrow: bool, cmp_lo: Ordering, cmp_hi: Ordering| -> usize {
+            let index = |cmp: Ordering| (cmp as i8 + 1) as usize;
+            9 * usize::from(borrow) + 3 * index(cmp_lo) + index(cmp_hi)
+        };
+        let decode = |state: usize| -> (bool, Ordering, Ordering) {
+            let cmp = |index: usize| [Ordering::Less, Ordering::Equal, Ordering::Greater][index];
+            (state >= 9, cmp((state % 9) / 3), cmp(state % 3))
+        };
+        let mut store = NodeStore::new(num_vars, n * 18 * 3);
+        // `layer[state]` is the relation on the remaining (more significant) pairs when
+        // the automaton is in `state`. Above the most significant pair, the borrow is ignored
+        // and the comparisons decide the result.
+        let mut layer: Vec<BddPointer> = (0..18)
+            .map(|state| {
+                let (_, cmp_lo, cmp_hi) = decode(state);
+                let (above_lo, below_hi) = (cmp_lo != Ordering::Less, cmp_hi != Ordering::Greater);
+                let accept = if lo <= hi {
+                    above_lo && below_hi
+                } else {
+                    above_lo || below_hi
+                };
+                BddPointer::from_bool(accept)
+            })
+            .collect();
+        for significance in (0..n).rev() {
+            let (a, b) = (block_a[n - 1 - significance], block_b[n - 1 - significance]);
+            let (lo_bit, hi_bit) = (bit(lo, significance), bit(hi, significance));
+            layer = (0..18)
+                .map(|current| {
+                    let (borrow, cmp_lo, cmp_hi) = decode(current);
+                    let child = |a_value: bool, b_value: bool| -> BddPointer {
+                        let d = b_value ^ a_value ^ borrow;
+                        let next_borrow = (a_value && !b_value) || (a_value == b_value && borrow);
+                        let next_lo = d.cmp(&lo_bit).then(cmp_lo);
+                        let next_hi = d.cmp(&hi_bit).then(cmp_hi);
+                        layer[state(next_borrow, next_lo, next_hi)]
+                    };
+                    let (c00, c01) = (child(false, false), child(false, true));
+                    let (c10, c11) = (child(true, false), child(true, true));
+                    let a_false = store.mk_select(b, c00, c01);
+                    let a_true = store.mk_select(b, c10, c11);
+                    store.mk_select(a, a_false, a_true)
+                })
+                .collect();
+        }
+        store.into_bdd(layer[state(false, Ordering::Equal, Ordering::Equal)])
+    }
+
+    /// **(internal)** Create a `Bdd` of all valuations where the unsigned value of `block_a`
+    /// is strictly smaller than the unsigned value of `block_b`. The first variable of each
+    /// block is the most significant bit.
+    ///
+    /// The relation is built directly, one pair of bits at a time (bottom-up). When the two
+    /// blocks are interleaved in the variable ordering from the most significant bit, this
+    /// creates at most three nodes per pair.
+    ///
+    /// *Panics:* The blocks must have the same length and contain unique valid variables
+    /// (no variable can appear in both blocks).
+    pub(crate) fn mk_block_less_than(
+        num_vars: u16,
+        block_a: &[BddVariable],
+        block_b: &[BddVariable],
+    ) -> Bdd {
+        check_block_sizes(block_a, block_b);
+        check_block_variables(num_vars, block_a.iter().chain(block_b).cloned().collect());
+        let mut store = NodeStore::new(num_vars, block_a.len() * 3);
+        // `less` is the relation on the already processed (less significant) pairs.
+        let mut less = BddPointer::zero();
+        for (a, b) in block_a.iter().zip(block_b).rev() {
+            let a_false = store.mk_select(*b, less, BddPointer::one());
+            let a_true = store.mk_select(*b, BddPointer::zero(), less);
+            less = store.mk_select(*a, a_false, a_true);
+        }
+        store.into_bdd(less)
+    }
+}
+
+/// **(internal)** Validate the two blocks and pair up their variables, sorted by the smaller
+/// variable of each pair.
+///
+/// *Panics:* The blocks must have the same length and contain unique valid variables.
+pub(crate) fn block_pairs(
+    num_vars: u16,
+    block_a: &[BddVariable],
+    block_b: &[BddVariable],
+) -> Vec<(BddVariable, BddVariable)> {
+    check_block_sizes(block_a, block_b);
+    check_block_variables(num_vars, block_a.iter().chain(block_b).cloned().collect());
+    let mut pairs: Vec<(BddVariable, BddVariable)> = block_a
+        .iter()
+        .cloned()
+        .zip(block_b.iter().cloned())
+        .collect();
+    pairs.sort_by_key(|(a, b)| *a.min(b));
+    pairs
+}
+
+/// **(internal)** The largest value of a block with `len` bits (as far as it fits into `u64`).
+fn block_mask(len: usize) -> u64 {
+    if len >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << len) - 1
+    }
+}
+
+/// **(internal)** Check that the two blocks have the same length.
+fn check_block_sizes(block_a: &[BddVariable], block_b: &[BddVariable]) {
+    if block_a.len() != block_b.len() {
+        panic!(
+            "Blocks have different sizes: {} != {}.",
+            block_a.len(),
+            block_b.len()
+        );
+    }
+}
+
+/// **(internal)** Check that the variables of the blocks are valid and unique.
+fn check_block_variables(num_vars: u16, mut all: Vec<BddVariable>) {
+    all.sort();
+    for (i, var) in all.iter().enumerate() {
+        if var.0 >= num_vars {
+            panic!(
+                "Variable {} is not valid in a Bdd with {} variables.",
+                var, num_vars
+            );
+        }
+        if i > 0 && all[i - 1] == *var {
+            panic!("Variable {} appears in the blocks multiple times.", var);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Bdd, BddValuation, BddValuationIterator, BddVariable, BddVariableSet};
+
+    /// Build the relation by explicitly checking every valuation.
+    fn mk_hamming_explicit(
+        variables: &BddVariableSet,
+        a: &[BddVariable],
+        b: &[BddVariable],
+        k: usize,
+    ) -> Bdd {
+        let mut result = variables.mk_false();
+        for valuation in BddValuationIterator::new(variables.num_vars()) {
+            let distance = a
+                .iter()
+                .zip(b)
+                .filter(|(x, y)| valuation.value(**x) != valuation.value(**y))
+                .count();
+            if distance <= k {
+                result = result.or(&Bdd::from(valuation));
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn block_relations_basic() {
+        let variables = BddVariableSet::new_anonymous(6);
+        let v: Vec<BddVariable> = variables.variables();
+        let blocks = [
+            (vec![v[0], v[2], v[4]], vec![v[1], v[3], v[5]]),
+            (vec![v[0], v[1], v[2]], vec![v[3], v[4], v[5]]),
+            (vec![v[5], v[1], v[3]], vec![v[0], v[4], v[2]]),
+        ];
+        for (a, b) in blocks.iter() {
+            for k in 0..5 {
+                assert_eq!(
+                    mk_hamming_explicit(&variables, a, b, k),
+                    variables.mk_block_hamming_at_most(a, b, k)
+                );
+            }
+            assert_eq!(
+                variables.mk_block_hamming_at_most(a, b, 0),
+                variables.mk_block_equality(a, b)
+            );
+        }
+        assert!(variables.mk_block_equality(&[], &[]).is_true());
+    }
+
+    /// The unsigned value of `block` in `valuation` (the first variable is the most
+    /// significant bit).
+    fn block_value(valuation: &BddValuation, block: &[BddVariable]) -> u64 {
+        block
+            .iter()
+            .fold(0, |value, var| 2 * value + u64::from(valuation.value(*var)))
+    }
+
+    /// Build a relation by explicitly checking every valuation.
+    fn mk_explicit<F: Fn(&BddValuation) -> bool>(variables: &BddVariableSet, test: F) -> Bdd {
+        let mut result = variables.mk_false();
+        for valuation in BddValuationIterator::new(variables.num_vars()) {
+            if test(&valuation) {
+                result = result.or(&Bdd::from(valuation));
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn block_range_basic() {
+        let variables = BddVariableSet::new_anonymous(5);
+        let v: Vec<BddVariable> = variables.variables();
+        let blocks = [
+            vec![v[0], v[1], v[2], v[3]],
+            vec![v[4], v[2], v[0]],
+            vec![v[1]],
+            vec![],
+        ];
+        for block in blocks.iter() {
+            for lo in 0..18 {
+                for hi in 0..18 {
+                    let explicit = mk_explicit(&variables, |valuation| {
+                        let value = block_value(valuation, block);
+                        lo <= value && value <= hi
+                    });
+                    assert_eq!(explicit, variables.mk_block_range(block, lo, hi));
+                }
+            }
+        }
+        assert!(variables.mk_block_range(&v, 0, u64::MAX).is_true());
+        assert!(variables.mk_block_range(&v, 32, u64::MAX).is_false());
+    }
+
+    #[test]
+    fn block_range_size() {
+        let variables = BddVariableSet::new_anonymous(100);
+        let v: Vec<BddVariable> = variables.variables();
+        let range = variables.mk_block_range(&v, 12_345_678_901, u64::MAX);
+        assert!(range.size() <= 4 * 100 + 2);
+        // The bound only has 64 bits, so the more significant bits must be zero.
+        let high_zero = variables.mk_block_range(&v[..36], 0, 0);
+        let low_range = variables.mk_block_range(&v[36..], 12_345_678_901, u64::MAX);
+        assert_eq!(high_zero.and(&low_range), range);
+    }
+
+    #[test]
+    fn block_less_than_basic() {
+        let variables = BddVariableSet::new_anonymous(6);
+        let v: Vec<BddVariable> = variables.variables();
+        let blocks = [
+            (vec![v[0], v[2], v[4]], vec![v[1], v[3], v[5]]),
+            (vec![v[0], v[1], v[2]], vec![v[3], v[4], v[5]]),
+            (vec![v[5], v[1], v[3]], vec![v[0], v[4], v[2]]),
+            (vec![], vec![]),
+        ];
+        for (a, b) in blocks.iter() {
+            let explicit = mk_explicit(&variables, |valuation| {
+                block_value(valuation, a) < block_value(valuation, b)
+            });
+            assert_eq!(explicit, variables.mk_block_less_than(a, b));
+        }
+        // Interleaved blocks have a linear relation (the least significant pair only needs
+        // two nodes).
+        let variables = BddVariableSet::new_anonymous(200);
+        let v: Vec<BddVariable> = variables.variables();
+        let a: Vec<BddVariable> = v.iter().cloned().step_by(2).collect();
+        let b: Vec<BddVariable> = v.iter().cloned().skip(1).step_by(2).collect();
+        assert_eq!(3 * 100 + 1, variables.mk_block_less_than(&a, &b).size());
+    }
+
+    #[test]
+    fn block_cyclic_range_basic() {
+        let variables = BddVariableSet::new_anonymous(5);
+        let v: Vec<BddVariable> = variables.variables();
+        let blocks = [vec![v[0], v[1], v[2]], vec![v[4], v[2], v[0], v[3]], vec![]];
+        for block in blocks.iter() {
+            let modulus = 1u64 << block.len();
+            for lo in 0..18 {
+                for hi in 0..18 {
+                    let (lo_mod, hi_mod) = (lo % modulus, hi % modulus);
+                    let explicit = mk_explicit(&variables, |valuation| {
+                        let value = block_value(valuation, block);
+                        if lo_mod <= hi_mod {
+                            lo_mod <= value && value <= hi_mod
+                        } else {
+                            lo_mod <= value || value <= hi_mod
+                        }
+                    });
+                    assert_eq!(explicit, variables.mk_block_cyclic_range(block, lo, hi));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn block_difference_range_basic() {
+        let variables = BddVariableSet::new_anonymous(6);
+        let v: Vec<BddVariable> = variables.variables();
+        let blocks = [
+            (vec![v[0], v[2], v[4]], vec![v[1], v[3], v[5]]),
+            (vec![v[5], v[1], v[3]], vec![v[0], v[4], v[2]]),
+            (vec![v[1]], vec![v[3]]),
+        ];
+        for (a, b) in blocks.iter() {
+            let modulus = 1u64 << a.len();
+            for lo in 0..9 {
+                for hi in 0..9 {
+                    let (lo_mod, hi_mod) = (lo % modulus, hi % modulus);
+                    let explicit = mk_explicit(&variables, |valuation| {
+                        let (x, y) = (block_value(valuation, a), block_value(valuation, b));
+                        let difference = (y + modulus - x) % modulus;
+                        if lo_mod <= hi_mod {
+                            lo_mod <= difference && difference <= hi_mod
+                        } else {
+                            lo_mod <= difference || difference <= hi_mod
+                        }
+                    });
+                    assert_eq!(explicit, variables.mk_block_difference_range(a, b, lo, hi));
+                }
+            }
+            assert_eq!(
+                variables.mk_block_equality(a, b),
+                variables.mk_block_difference_range(a, b, 0, 0)
+            );
+        }
+        assert!(variables
+            .mk_block_difference_range(&[], &[], 0, 0)
+            .is_true());
+    }
+
+    #[test]
+    fn block_difference_range_size() {
+        // Interleaved blocks have a linear relation, also for wide blocks.
+        let variables = BddVariableSet::new_anonymous(160);
+        let v: Vec<BddVariable> = variables.variables();
+        let a: Vec<BddVariable> = v.iter().cloned().step_by(2).collect();
+        let b: Vec<BddVariable> = v.iter().cloned().skip(1).step_by(2).collect();
+        let successor = variables.mk_block_difference_range(&a, &b, 1, 1);
+        assert!(successor.size() <= 6 * 80 + 2);
+        let serial = variables.mk_block_difference_range(&a, &b, 1, (1 << 63) - 1);
+        assert!(serial.size() <= 6 * 80 + 2);
+        // The successor is always in the serial number range.
+        assert_eq!(successor, successor.and(&serial));
+    }
+
+    #[test]
+    #[should_panic]
+    fn block_range_duplicate_variable() {
+        let variables = BddVariableSet::new_anonymous(4);
+        variables.mk_block_range(&[BddVariable(0), BddVariable(0)], 0, 1);
+    }
+
+    #[test]
+    fn block_equality_size() {
+        // Interleaved blocks have a linear equality relation.
+        let variables = BddVariableSet::new_anonymous(200);
+        let v: Vec<BddVariable> = variables.variables();
+        let a: Vec<BddVariable> = v.iter().cloned().step_by(2).collect();
+        let b: Vec<BddVariable> = v.iter().cloned().skip(1).step_by(2).collect();
+        assert_eq!(3 * 100 + 2, variables.mk_block_equality(&a, &b).size());
+    }
+
+    #[test]
+    #[should_panic]
+    fn block_relations_overlapping_blocks() {
+        let variables = BddVariableSet::new_anonymous(4);
+        variables.mk_block_equality(
+            &[BddVariable(0), BddVariable(1)],
+            &[BddVariable(1), BddVariable(2)],
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn block_relations_different_sizes() {
+        let variables = BddVariableSet::new_anonymous(4);
+        variables.mk_block_equality(&[BddVariable(0), BddVariable(1)], &[BddVariable(2)]);
+    }
+}
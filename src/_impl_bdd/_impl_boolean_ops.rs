@@ -1,6 +1,7 @@
 use crate::*;
 use fxhash::FxBuildHasher;
 use std::cmp::{max, min};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Basic boolean logical operations for `Bdd`s:
 /// $\neg, \land, \lor, \Rightarrow, \Leftrightarrow, \oplus$.
@@ -26,8 +27,28 @@ impl Bdd {
 
     /// Create a `Bdd` corresponding to the $\phi \land \psi$ formula, where $\phi$ and $\psi$
     /// are the two given `Bdd`s.
+    ///
+    /// If one of the operands is a cube (a conjunction of literals), a specialised traversal of
+    /// the other operand is used instead of the general `apply`.
     pub fn and(&self, right: &Bdd) -> Bdd {
-        apply(self, right, crate::op_function::and)
+        self.check_compatible(right);
+        if is_cube(right) {
+            and_cube(self, &cube_literals(right))
+        } else if is_cube(self) {
+            and_cube(right, &cube_literals(self))
+        } else {
+            apply(self, right, crate::op_function::and)
+        }
+    }
+
+    /// Create a `Bdd` corresponding to the conjunction of this `Bdd` and the literals fixed
+    /// by the given partial valuation (i.e. `self.and(&vars.mk_conjunctive_clause(cube))`).
+    ///
+    /// The literals are used directly, so the clause never has to be built as a `Bdd`.
+    ///
+    /// *Panics:* The variables of the valuation must be valid variables of this `Bdd`.
+    pub fn and_cube(&self, cube: &BddPartialValuation) -> Bdd {
+        and_cube(self, &cube.to_values())
     }
 
     /// Create a `Bdd` corresponding to the $\phi \lor \psi$ formula, where $\phi$ and $\psi$
@@ -91,7 +112,365 @@ impl Bdd {
     where
         T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
     {
-        apply_with_flip(left.0, right.0, left.1, right.1, flip_output, op_function)
+        apply_with_flip(
+            left.0,
+            right.0,
+            left.1,
+            right.1,
+            flip_output,
+            ApplyControl::unlimited(),
+            op_function,
+        )
+        .unwrap()
+    }
+}
+
+/// Variants of the basic logical operations which give up once the result is too large.
+///
+/// Each operation returns `None` if the result would have more than `max_nodes` nodes
+/// (including the two terminal nodes). The computation is stopped as soon as the partial
+/// result exceeds the limit, so the memory used by an aborted operation is bounded
+/// by `max_nodes` as well as the sizes of the operands.
+impl Bdd {
+    /// Same as `Bdd::and`, but with a limit on the size of the result.
+    pub fn checked_and(&self, right: &Bdd, max_nodes: usize) -> Option<Bdd> {
+        apply_limited(self, right, max_nodes, crate::op_function::and)
+    }
+
+    /// Same as `Bdd::or`, but with a limit on the size of the result.
+    pub fn checked_or(&self, right: &Bdd, max_nodes: usize) -> Option<Bdd> {
+        apply_limited(self, right, max_nodes, crate::op_function::or)
+    }
+
+    /// Same as `Bdd::imp`, but with a limit on the size of the result.
+    pub fn checked_imp(&self, right: &Bdd, max_nodes: usize) -> Option<Bdd> {
+        apply_limited(self, right, max_nodes, crate::op_function::imp)
+    }
+
+    /// Same as `Bdd::iff`, but with a limit on the size of the result.
+    pub fn checked_iff(&self, right: &Bdd, max_nodes: usize) -> Option<Bdd> {
+        apply_limited(self, right, max_nodes, crate::op_function::iff)
+    }
+
+    /// Same as `Bdd::xor`, but with a limit on the size of the result.
+    pub fn checked_xor(&self, right: &Bdd, max_nodes: usize) -> Option<Bdd> {
+        apply_limited(self, right, max_nodes, crate::op_function::xor)
+    }
+
+    /// Same as `Bdd::and_not`, but with a limit on the size of the result.
+    pub fn checked_and_not(&self, right: &Bdd, max_nodes: usize) -> Option<Bdd> {
+        apply_limited(self, right, max_nodes, crate::op_function::and_not)
+    }
+
+    /// Same as `Bdd::binary_op`, but with a limit on the size of the result.
+    pub fn checked_binary_op<T>(
+        left: &Bdd,
+        right: &Bdd,
+        op_function: T,
+        max_nodes: usize,
+    ) -> Option<Bdd>
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        apply_limited(left, right, max_nodes, op_function)
+    }
+}
+
+/// Variants of the basic logical operations which can be cancelled from another thread.
+///
+/// The operations check the `cancelled` flag periodically while computing the result and
+/// return `Err(Cancelled)` once it is set. Operations which do not need a full `apply`
+/// (e.g. with a constant operand) may finish regardless of the flag.
+impl Bdd {
+    /// Same as `Bdd::and`, but can be cancelled using the `cancelled` flag.
+    pub fn cancellable_and(&self, right: &Bdd, cancelled: &AtomicBool) -> Result<Bdd, Cancelled> {
+        apply_cancellable(self, right, cancelled, crate::op_function::and)
+    }
+
+    /// Same as `Bdd::or`, but can be cancelled using the `cancelled` flag.
+    pub fn cancellable_or(&self, right: &Bdd, cancelled: &AtomicBool) -> Result<Bdd, Cancelled> {
+        apply_cancellable(self, right, cancelled, crate::op_function::or)
+    }
+
+    /// Same as `Bdd::imp`, but can be cancelled using the `cancelled` flag.
+    pub fn cancellable_imp(&self, right: &Bdd, cancelled: &AtomicBool) -> Result<Bdd, Cancelled> {
+        apply_cancellable(self, right, cancelled, crate::op_function::imp)
+    }
+
+    /// Same as `Bdd::iff`, but can be cancelled using the `cancelled` flag.
+    pub fn cancellable_iff(&self, right: &Bdd, cancelled: &AtomicBool) -> Result<Bdd, Cancelled> {
+        apply_cancellable(self, right, cancelled, crate::op_function::iff)
+    }
+
+    /// Same as `Bdd::xor`, but can be cancelled using the `cancelled` flag.
+    pub fn cancellable_xor(&self, right: &Bdd, cancelled: &AtomicBool) -> Result<Bdd, Cancelled> {
+        apply_cancellable(self, right, cancelled, crate::op_function::xor)
+    }
+
+    /// Same as `Bdd::and_not`, but can be cancelled using the `cancelled` flag.
+    pub fn cancellable_and_not(
+        &self,
+        right: &Bdd,
+        cancelled: &AtomicBool,
+    ) -> Result<Bdd, Cancelled> {
+        apply_cancellable(self, right, cancelled, crate::op_function::and_not)
+    }
+
+    /// Same as `Bdd::binary_op`, but can be cancelled using the `cancelled` flag.
+    pub fn cancellable_binary_op<T>(
+        left: &Bdd,
+        right: &Bdd,
+        op_function: T,
+        cancelled: &AtomicBool,
+    ) -> Result<Bdd, Cancelled>
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        apply_cancellable(left, right, cancelled, op_function)
+    }
+}
+
+/// Variants of the basic logical operations which report their progress.
+impl Bdd {
+    /// Same as `Bdd::and`, but reports the progress of the computation to `progress`
+    /// (see `ApplyProgress`).
+    pub fn and_with_progress(&self, right: &Bdd, progress: &mut dyn ApplyProgress) -> Bdd {
+        apply_with_progress(self, right, progress, crate::op_function::and)
+    }
+
+    /// Same as `Bdd::or`, but reports the progress of the computation to `progress`
+    /// (see `ApplyProgress`).
+    pub fn or_with_progress(&self, right: &Bdd, progress: &mut dyn ApplyProgress) -> Bdd {
+        apply_with_progress(self, right, progress, crate::op_function::or)
+    }
+
+    /// Same as `Bdd::binary_op`, but reports the progress of the computation to `progress`
+    /// (see `ApplyProgress`).
+    pub fn binary_op_with_progress<T>(
+        left: &Bdd,
+        right: &Bdd,
+        op_function: T,
+        progress: &mut dyn ApplyProgress,
+    ) -> Bdd
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        apply_with_progress(left, right, progress, op_function)
+    }
+}
+
+/// In-place variants of the basic logical operations, where the result replaces
+/// the left operand.
+///
+/// If the result can be derived from the left operand directly (the right operand is constant
+/// or equal to the left operand), the node vector of the left operand is updated in place.
+/// Otherwise, the result is computed using `apply` as usual.
+impl Bdd {
+    /// Replace this `Bdd` with its negation. This never allocates a new node vector.
+    pub fn not_assign(&mut self) {
+        if self.is_true() {
+            self.set_constant(false);
+        } else if self.is_false() {
+            self.set_constant(true);
+        } else {
+            for node in self.0.iter_mut().skip(2) {
+                node.high_link.flip_if_terminal();
+                node.low_link.flip_if_terminal();
+            }
+        }
+    }
+
+    /// Same as `Bdd::and`, but the result is stored in this `Bdd`.
+    pub fn and_assign(&mut self, right: &Bdd) {
+        self.binary_op_assign(right, crate::op_function::and);
+    }
+
+    /// Same as `Bdd::or`, but the result is stored in this `Bdd`.
+    pub fn or_assign(&mut self, right: &Bdd) {
+        self.binary_op_assign(right, crate::op_function::or);
+    }
+
+    /// Same as `Bdd::imp`, but the result is stored in this `Bdd`.
+    pub fn imp_assign(&mut self, right: &Bdd) {
+        self.binary_op_assign(right, crate::op_function::imp);
+    }
+
+    /// Same as `Bdd::iff`, but the result is stored in this `Bdd`.
+    pub fn iff_assign(&mut self, right: &Bdd) {
+        self.binary_op_assign(right, crate::op_function::iff);
+    }
+
+    /// Same as `Bdd::xor`, but the result is stored in this `Bdd`.
+    pub fn xor_assign(&mut self, right: &Bdd) {
+        self.binary_op_assign(right, crate::op_function::xor);
+    }
+
+    /// Same as `Bdd::and_not`, but the result is stored in this `Bdd`.
+    pub fn and_not_assign(&mut self, right: &Bdd) {
+        self.binary_op_assign(right, crate::op_function::and_not);
+    }
+
+    /// Same as `Bdd::binary_op`, but the result is stored in this (left) `Bdd`.
+    pub fn binary_op_assign<T>(&mut self, right: &Bdd, op_function: T)
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        self.check_compatible(right);
+        // If we know the value of the right operand for every value of the left operand,
+        // the result is either a constant, the left operand, or its negation.
+        let outcomes = if right.is_true() || right.is_false() {
+            let value = Some(right.is_true());
+            (
+                op_function(Some(false), value),
+                op_function(Some(true), value),
+            )
+        } else if right == self {
+            (
+                op_function(Some(false), Some(false)),
+                op_function(Some(true), Some(true)),
+            )
+        } else {
+            (None, None)
+        };
+        match outcomes {
+            (Some(low), Some(high)) if low == high => self.set_constant(low),
+            (Some(false), Some(true)) => {}
+            (Some(true), Some(false)) => self.not_assign(),
+            _ => *self = apply(self, right, op_function),
+        }
+    }
+
+    /// **(internal)** Replace this `Bdd` with a constant, reusing its node vector.
+    fn set_constant(&mut self, value: bool) {
+        if !value {
+            self.0.truncate(1);
+        } else if self.0.len() >= 2 {
+            self.0.truncate(2);
+        } else {
+            self.0.push(BddNode::mk_one(self.num_vars()));
+        }
+    }
+}
+
+/// Comparisons of `Bdd`s which do not need to build the result of a logical operation.
+impl Bdd {
+    /// Check whether this `Bdd` and `other` represent the same function on the valuations
+    /// of the `care` set, i.e. whether $(\phi \oplus \psi) \land care$ is unsatisfiable.
+    ///
+    /// Instead of building the intermediate results, the three `Bdd`s are explored
+    /// simultaneously and the exploration stops at the first difference. Parts of the
+    /// functions outside of the care set are never explored.
+    ///
+    /// *Panics:* All three `Bdd`s must have the same number of variables.
+    pub fn equivalent_under(&self, other: &Bdd, care: &Bdd) -> bool {
+        let num_vars = self.num_vars();
+        self.check_compatible(other);
+        self.check_compatible(care);
+        if care.is_false() || self == other {
+            return true;
+        }
+
+        // Every triple of nodes which is visited represents a non-empty part of the care set.
+        // Since a non-zero node always has a satisfying valuation, two different terminals
+        // in such a triple are a witness of a difference.
+        let node_var = |bdd: &Bdd, node: BddPointer| {
+            if node.is_terminal() {
+                BddVariable(num_vars)
+            } else {
+                bdd.var_of(node)
+            }
+        };
+        let mut visited: HashSet<(BddPointer, BddPointer, BddPointer), FxBuildHasher> =
+            HashSet::default();
+        let mut stack = vec![(
+            self.root_pointer(),
+            other.root_pointer(),
+            care.root_pointer(),
+        )];
+        while let Some(task) = stack.pop() {
+            let (l, r, c) = task;
+            if c.is_zero() {
+                continue;
+            }
+            if l.is_terminal() && r.is_terminal() {
+                if l != r {
+                    return false;
+                }
+                continue;
+            }
+            if !visited.insert(task) {
+                continue;
+            }
+            let (l_v, r_v, c_v) = (node_var(self, l), node_var(other, r), node_var(care, c));
+            let var = min(l_v, min(r_v, c_v));
+            let split = |bdd: &Bdd, node: BddPointer, node_var: BddVariable| {
+                if node_var == var {
+                    (bdd.low_link_of(node), bdd.high_link_of(node))
+                } else {
+                    (node, node)
+                }
+            };
+            let (l_low, l_high) = split(self, l, l_v);
+            let (r_low, r_high) = split(other, r, r_v);
+            let (c_low, c_high) = split(care, c, c_v);
+            stack.push((l_low, r_low, c_low));
+            stack.push((l_high, r_high, c_high));
+        }
+        true
+    }
+}
+
+/// **(internal)** Uniqueness table of one variable level: maps the `(low, high)` links
+/// of a decision node to its position in the result `Bdd`.
+type UniqueTable = HashMap<(BddPointer, BddPointer), BddPointer, FxBuildHasher>;
+
+/// **(internal)** Operands with fewer nodes than this (together) use a single uniqueness table.
+const LEVEL_TABLES_MIN_SIZE: usize = 4096;
+
+/// **(internal)** Uniqueness table of the result `Bdd` in `apply`: maps decision nodes to
+/// their position in the result.
+///
+/// For large operands, `apply` uses `LevelTables`, which are split by variable, so each level
+/// only stores the `(low, high)` pair of its nodes. For small operands, a single table keyed
+/// by whole nodes is faster: it is allocated once with the expected capacity, while every
+/// level would grow separately. The same holds when the operands are smaller than the
+/// number of variables, since the vector of levels could then cost more than the whole
+/// computation (e.g. two literals over thousands of variables). The loop of `apply` is
+/// generic over the table, so the choice does not cost anything per node.
+trait NodeTable {
+    fn get(&self, node: &BddNode) -> Option<BddPointer>;
+    fn insert(&mut self, node: BddNode, pointer: BddPointer);
+}
+
+impl NodeTable for HashMap<BddNode, BddPointer, FxBuildHasher> {
+    fn get(&self, node: &BddNode) -> Option<BddPointer> {
+        HashMap::get(self, node).cloned()
+    }
+
+    fn insert(&mut self, node: BddNode, pointer: BddPointer) {
+        HashMap::insert(self, node, pointer);
+    }
+}
+
+/// **(internal)** Per-level uniqueness tables indexed directly by variable. The vector only
+/// grows once a node is created for a variable (empty tables do not allocate).
+#[derive(Default)]
+struct LevelTables(Vec<UniqueTable>);
+
+impl NodeTable for LevelTables {
+    fn get(&self, node: &BddNode) -> Option<BddPointer> {
+        self.0
+            .get(usize::from(node.var.0))
+            .and_then(|level| level.get(&(node.low_link, node.high_link)))
+            .cloned()
+    }
+
+    fn insert(&mut self, node: BddNode, pointer: BddPointer) {
+        let index = usize::from(node.var.0);
+        if self.0.len() <= index {
+            self.0.resize_with(index + 1, UniqueTable::default);
+        }
+        self.0[index].insert((node.low_link, node.high_link), pointer);
     }
 }
 
@@ -100,7 +479,89 @@ fn apply<T>(left: &Bdd, right: &Bdd, terminal_lookup: T) -> Bdd
 where
     T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
 {
-    apply_with_flip(left, right, None, None, None, terminal_lookup)
+    apply_with_flip(
+        left,
+        right,
+        None,
+        None,
+        None,
+        ApplyControl::unlimited(),
+        terminal_lookup,
+    )
+    .unwrap()
+}
+
+/// **(internal)** Same as `apply`, but returns `None` as soon as the result has more than
+/// `max_nodes` nodes.
+fn apply_limited<T>(left: &Bdd, right: &Bdd, max_nodes: usize, terminal_lookup: T) -> Option<Bdd>
+where
+    T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+{
+    let control = ApplyControl {
+        max_nodes,
+        ..ApplyControl::unlimited()
+    };
+    apply_with_flip(left, right, None, None, None, control, terminal_lookup)
+}
+
+/// **(internal)** Same as `apply`, but stops once the `cancelled` flag is set.
+fn apply_cancellable<T>(
+    left: &Bdd,
+    right: &Bdd,
+    cancelled: &AtomicBool,
+    terminal_lookup: T,
+) -> Result<Bdd, Cancelled>
+where
+    T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+{
+    let control = ApplyControl {
+        cancelled: Some(cancelled),
+        ..ApplyControl::unlimited()
+    };
+    apply_with_flip(left, right, None, None, None, control, terminal_lookup).ok_or(Cancelled)
+}
+
+/// **(internal)** Same as `apply`, but reports its progress to `progress`.
+fn apply_with_progress<T>(
+    left: &Bdd,
+    right: &Bdd,
+    progress: &mut dyn ApplyProgress,
+    terminal_lookup: T,
+) -> Bdd
+where
+    T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+{
+    let control = ApplyControl {
+        progress: Some(progress),
+        ..ApplyControl::unlimited()
+    };
+    apply_with_flip(left, right, None, None, None, control, terminal_lookup).unwrap()
+}
+
+/// **(internal)** The number of tasks `apply_with_flip` processes between two checks of the
+/// cancellation flag.
+const CANCEL_CHECK_INTERVAL: usize = 1 << 10;
+
+/// **(internal)** Conditions under which `apply_with_flip` gives up and returns `None`,
+/// together with an optional receiver of progress reports.
+struct ApplyControl<'a> {
+    /// The largest allowed size of the result.
+    max_nodes: usize,
+    /// The computation stops once this flag is set.
+    cancelled: Option<&'a AtomicBool>,
+    /// Receives a report every `ApplyProgress::interval` tasks.
+    progress: Option<&'a mut dyn ApplyProgress>,
+}
+
+impl<'a> ApplyControl<'a> {
+    /// A computation which is never aborted.
+    fn unlimited() -> ApplyControl<'a> {
+        ApplyControl {
+            max_nodes: usize::MAX,
+            cancelled: None,
+            progress: None,
+        }
+    }
 }
 
 /// **(internal)** Universal function to implement standard logical operators.
@@ -118,39 +579,166 @@ where
 /// The reason why we allow this behaviour in apply, is that flipping the pointers in a BDD is cheap,
 /// but breaks the DFS order, which may result in unexpected behaviour. Furthermore, since the
 /// function is generic, in most performance intensive paths, it should be optimized anyway.
+///
+/// The computation can be aborted using the `control` (returning `None`). Every node created
+/// by the computation is part of the result, so it can be aborted as soon as the result has
+/// more than `max_nodes` nodes. The cancellation flag is checked periodically, and progress
+/// is reported after every `interval` tasks.
 fn apply_with_flip<T>(
     left: &Bdd,
     right: &Bdd,
     flip_left_if: Option<BddVariable>,
     flip_right_if: Option<BddVariable>,
     flip_out_if: Option<BddVariable>,
+    control: ApplyControl,
     terminal_lookup: T,
-) -> Bdd
+) -> Option<Bdd>
 where
     T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
 {
     let num_vars = left.num_vars();
-    if right.num_vars() != num_vars {
-        panic!(
-            "Var count mismatch: BDDs are not compatible. {} != {}",
-            num_vars,
-            right.num_vars()
-        );
-    }
+    left.check_compatible(right);
     check_flip_bounds(num_vars, flip_left_if);
     check_flip_bounds(num_vars, flip_right_if);
     check_flip_bounds(num_vars, flip_out_if);
+
+    // If both operands represent the same function, `op(x, x)` only depends on the values
+    // `op(0, 0)` and `op(1, 1)`. If these are equal, every such pair of nodes evaluates to
+    // a constant and we don't have to explore it at all. At the root level, we can also
+    // resolve the identity and negation cases immediately.
+    let same_operands = left == right;
+    let diagonal = (
+        terminal_lookup(Some(false), Some(false)),
+        terminal_lookup(Some(true), Some(true)),
+    );
+    let diagonal_constant = match diagonal {
+        (Some(low), Some(high)) if low == high => Some(low),
+        _ => None,
+    };
+    if same_operands && flip_left_if == flip_right_if {
+        if let Some(value) = diagonal_constant {
+            let result = if value {
+                Bdd::mk_true(num_vars)
+            } else {
+                Bdd::mk_false(num_vars)
+            };
+            return Some(result).filter(|result| result.size() <= control.max_nodes);
+        }
+        if flip_left_if.is_none() && flip_out_if.is_none() {
+            let result = match diagonal {
+                (Some(false), Some(true)) => Some(left.clone()),
+                (Some(true), Some(false)) => Some(left.not()),
+                _ => None,
+            };
+            if let Some(result) = result {
+                return Some(result).filter(|result| result.size() <= control.max_nodes);
+            }
+        }
+    }
+
+    // If one operand is a constant, `op(c, x)` (or `op(x, c)`) only depends on `x`. When the
+    // constant is neutral or negating for this operator, the result is `x` or `!x`. Flipping
+    // variables of a constant has no effect, but flipping `x` or the output is not handled here.
+    if flip_out_if.is_none() {
+        let constant_of = |bdd: &Bdd| -> Option<bool> {
+            if bdd.is_true() {
+                Some(true)
+            } else if bdd.is_false() {
+                Some(false)
+            } else {
+                None
+            }
+        };
+        let shortcut = match (constant_of(left), constant_of(right)) {
+            (Some(value), _) if flip_right_if.is_none() => Some((
+                right,
+                terminal_lookup(Some(value), Some(false)),
+                terminal_lookup(Some(value), Some(true)),
+            )),
+            (_, Some(value)) if flip_left_if.is_none() => Some((
+                left,
+                terminal_lookup(Some(false), Some(value)),
+                terminal_lookup(Some(true), Some(value)),
+            )),
+            _ => None,
+        };
+        if let Some((operand, if_false, if_true)) = shortcut {
+            let result = match (if_false, if_true) {
+                (Some(false), Some(true)) => Some(operand.clone()),
+                (Some(true), Some(false)) => Some(operand.not()),
+                (Some(false), Some(false)) => Some(Bdd::mk_false(num_vars)),
+                (Some(true), Some(true)) => Some(Bdd::mk_true(num_vars)),
+                _ => None,
+            };
+            if let Some(result) = result {
+                return Some(result).filter(|result| result.size() <= control.max_nodes);
+            }
+        }
+    }
+
+    let flips = (flip_left_if, flip_right_if, flip_out_if);
+    let diagonal_constant = diagonal_constant.filter(|_| same_operands);
+    let total = left.size() + right.size();
+    if total < LEVEL_TABLES_MIN_SIZE || total < usize::from(num_vars) {
+        let existing: HashMap<BddNode, BddPointer, FxBuildHasher> =
+            HashMap::with_capacity_and_hasher(
+                max(left.size(), right.size()),
+                FxBuildHasher::default(),
+            );
+        apply_nodes(
+            left,
+            right,
+            flips,
+            control,
+            terminal_lookup,
+            diagonal_constant,
+            existing,
+        )
+    } else {
+        let existing = LevelTables::default();
+        apply_nodes(
+            left,
+            right,
+            flips,
+            control,
+            terminal_lookup,
+            diagonal_constant,
+            existing,
+        )
+    }
+}
+
+/// **(internal)** The main loop of `apply_with_flip`, once the shortcuts are resolved.
+///
+/// The `diagonal_constant` is only given when both operands are the same function.
+fn apply_nodes<T, U>(
+    left: &Bdd,
+    right: &Bdd,
+    (flip_left_if, flip_right_if, flip_out_if): (
+        Option<BddVariable>,
+        Option<BddVariable>,
+        Option<BddVariable>,
+    ),
+    mut control: ApplyControl,
+    terminal_lookup: T,
+    diagonal_constant: Option<bool>,
+    mut existing: U,
+) -> Option<Bdd>
+where
+    T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    U: NodeTable,
+{
+    let num_vars = left.num_vars();
+
     // Result holds the new BDD we are computing. Initially, `0` and `1` nodes are present. We
     // remember if the result is `false` or not (`is_not_empty`). If it is, we just provide
     // a `false` BDD instead of the result. This is easier than explicitly adding `1` later.
     let mut result: Bdd = Bdd::mk_true(num_vars);
     let mut is_not_empty = false;
 
-    // Every node in `result` is inserted into `existing` - this ensures we have no duplicates.
-    let mut existing: HashMap<BddNode, BddPointer, FxBuildHasher> =
-        HashMap::with_capacity_and_hasher(max(left.size(), right.size()), FxBuildHasher::default());
-    existing.insert(BddNode::mk_zero(num_vars), BddPointer::zero());
-    existing.insert(BddNode::mk_one(num_vars), BddPointer::one());
+    // Every decision node in `result` is inserted into `existing` - this ensures we have
+    // no duplicates (terminals are never inserted, because a decision node can never be
+    // equal to a terminal).
 
     // Task is a pair of pointers into the `left` and `right` BDDs.
     #[derive(Eq, PartialEq, Hash, Copy, Clone)]
@@ -167,12 +755,52 @@ where
         right: right.root_pointer(),
     });
 
+    // A task is "diagonal" when it points to the same node in both operands (which are the
+    // same function whenever `diagonal_constant` is given). If the operands are flipped differently, this only holds when the node is
+    // below all flipped variables.
+    let is_diagonal = |task: &Task| -> bool {
+        if task.left != task.right {
+            return false;
+        }
+        if flip_left_if == flip_right_if {
+            return true;
+        }
+        let var = left.var_of(task.left);
+        !matches!(flip_left_if, Some(v) if v >= var)
+            && !matches!(flip_right_if, Some(v) if v >= var)
+    };
+
     // `finished` is a memoization cache of tasks which are already completed, since the same
     // combination of nodes can be often explored multiple times.
     let mut finished: HashMap<Task, BddPointer, FxBuildHasher> =
         HashMap::with_capacity_and_hasher(max(left.size(), right.size()), FxBuildHasher::default());
 
+    // Number of tasks until the next check of the cancellation flag and the next report.
+    let mut until_check: usize = 0;
+    let report_interval = control
+        .progress
+        .as_ref()
+        .map(|progress| max(1, progress.interval()));
+    let mut until_report: usize = report_interval.unwrap_or(usize::MAX);
+    let mut processed: usize = 0;
     while let Some(on_stack) = stack.last() {
+        processed += 1;
+        if let Some(progress) = control.progress.as_mut() {
+            until_report -= 1;
+            if until_report == 0 {
+                progress.report(processed, stack.len() - 1, result.size());
+                until_report = report_interval.unwrap_or(usize::MAX);
+            }
+        }
+        if let Some(cancelled) = control.cancelled {
+            if until_check == 0 {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+                until_check = CANCEL_CHECK_INTERVAL;
+            }
+            until_check -= 1;
+        }
         if finished.contains_key(on_stack) {
             stack.pop();
         } else {
@@ -210,11 +838,13 @@ where
                 right: r_high,
             };
 
-            // Try to solve the tasks using terminal lookup table or from cache.
+            // Try to solve the tasks using terminal lookup table, diagonal constant or from cache.
             let new_low = terminal_lookup(l_low.as_bool(), r_low.as_bool())
+                .or_else(|| diagonal_constant.filter(|_| is_diagonal(&comp_low)))
                 .map(BddPointer::from_bool)
                 .or_else(|| finished.get(&comp_low).cloned());
             let new_high = terminal_lookup(l_high.as_bool(), r_high.as_bool())
+                .or_else(|| diagonal_constant.filter(|_| is_diagonal(&comp_high)))
                 .map(BddPointer::from_bool)
                 .or_else(|| finished.get(&comp_high).cloned());
 
@@ -236,10 +866,13 @@ where
                     };
                     if let Some(index) = existing.get(&node) {
                         // Node already exists, just make it a result of this computation.
-                        finished.insert(*on_stack, *index);
+                        finished.insert(*on_stack, index);
                     } else {
                         // Node does not exist, it needs to be pushed to result.
                         result.push_node(node);
+                        if result.size() > control.max_nodes {
+                            return None;
+                        }
                         existing.insert(node, result.root_pointer());
                         finished.insert(*on_stack, result.root_pointer());
                     }
@@ -267,10 +900,155 @@ where
         }
     }
 
-    if is_not_empty {
+    let result = if is_not_empty {
         result
     } else {
         Bdd::mk_false(num_vars)
+    };
+    // Constant results are smaller than the initial `true` result.
+    Some(result).filter(|result| result.size() <= control.max_nodes)
+}
+
+/// **(internal)** Check if the `Bdd` is a cube (a non-constant conjunction of literals).
+///
+/// A `Bdd` is a cube if every decision node has exactly one link pointing to the `0` terminal.
+/// The nodes are checked from the root, so for most `Bdd`s which are not cubes, the check
+/// stops at the first node and `and` pays almost nothing for it.
+fn is_cube(bdd: &Bdd) -> bool {
+    bdd.size() > 2
+        && bdd
+            .nodes()
+            .skip(2)
+            .rev()
+            .all(|node| node.low_link.is_zero() != node.high_link.is_zero())
+}
+
+/// **(internal)** The literals of a cube (see `is_cube`), sorted by variable.
+fn cube_literals(bdd: &Bdd) -> Vec<(BddVariable, bool)> {
+    // In a cube, the nodes form a single path, so they are ordered from the bottom to the root.
+    bdd.nodes()
+        .skip(2)
+        .rev()
+        .map(|node| (node.var, node.low_link.is_zero()))
+        .collect()
+}
+
+/// **(internal)** Compute the conjunction of a `Bdd` with a cube given as a sorted
+/// list of literals.
+///
+/// This is a special case of `apply` where the right operand is a path: a task is a pair of
+/// a node in `left` and the number of literals of the cube that are already resolved. As a
+/// result, the tasks can be resolved without examining a second `Bdd` or evaluating a terminal
+/// lookup function. The tasks are explored in the same order as in `apply`, so the result is
+/// the same (including the order of nodes).
+fn and_cube(left: &Bdd, cube: &[(BddVariable, bool)]) -> Bdd {
+    let num_vars = left.num_vars();
+    if let Some((var, _)) = cube.iter().find(|(var, _)| var.0 >= num_vars) {
+        panic!(
+            "Var count mismatch: BDDs are not compatible. Variable {} in a Bdd with {} variables.",
+            var, num_vars
+        );
+    }
+    if left.is_false() {
+        return left.clone();
+    }
+
+    let mut result: Bdd = Bdd::mk_true(num_vars);
+    let mut existing: HashMap<BddNode, BddPointer, FxBuildHasher> =
+        HashMap::with_capacity_and_hasher(left.size(), FxBuildHasher::default());
+
+    // When a task reaches a node, all literals above the node variable must be resolved. Most
+    // tasks (all tasks below the last literal) are therefore uniquely identified by the node
+    // and their results can be stored in a vector. Only the tasks which still need to resolve
+    // literals above their node are stored in a hash map.
+    // The number of literals above a variable is found by a binary search, so that the
+    // traversal does not depend on the number of variables.
+    let is_node_task = |task: &(BddPointer, usize)| {
+        let node_var = left.var_of(task.0);
+        cube.partition_point(|(var, _)| *var < node_var) == task.1
+    };
+    let mut finished_nodes: Vec<Option<BddPointer>> = vec![None; left.size()];
+    let mut finished_other: HashMap<(BddPointer, usize), BddPointer, FxBuildHasher> =
+        HashMap::default();
+    let lookup = |finished_nodes: &Vec<Option<BddPointer>>,
+                  finished_other: &HashMap<(BddPointer, usize), BddPointer, FxBuildHasher>,
+                  task: (BddPointer, usize)| {
+        if task.0.is_zero() {
+            Some(BddPointer::zero())
+        } else if task.0.is_one() && task.1 == cube.len() {
+            Some(BddPointer::one())
+        } else if is_node_task(&task) {
+            finished_nodes[task.0.to_index()]
+        } else {
+            finished_other.get(&task).cloned()
+        }
+    };
+
+    let root = (left.root_pointer(), 0);
+    let mut stack: Vec<(BddPointer, usize)> = vec![root];
+    while let Some(on_stack) = stack.last().cloned() {
+        if lookup(&finished_nodes, &finished_other, on_stack).is_some() {
+            stack.pop();
+            continue;
+        }
+        let (node, resolved) = on_stack;
+        let node_var = left.var_of(node);
+        // The literal is either above the node (the node is not advanced) or on the same
+        // variable (the node is advanced to the corresponding child).
+        let (decision_var, comp_low, comp_high) = match cube.get(resolved) {
+            Some(&(var, value)) if var <= node_var => {
+                let next = if var < node_var {
+                    node
+                } else if value {
+                    left.high_link_of(node)
+                } else {
+                    left.low_link_of(node)
+                };
+                let zero = (BddPointer::zero(), resolved + 1);
+                if value {
+                    (var, zero, (next, resolved + 1))
+                } else {
+                    (var, (next, resolved + 1), zero)
+                }
+            }
+            _ => (
+                node_var,
+                (left.low_link_of(node), resolved),
+                (left.high_link_of(node), resolved),
+            ),
+        };
+
+        let new_low = lookup(&finished_nodes, &finished_other, comp_low);
+        let new_high = lookup(&finished_nodes, &finished_other, comp_high);
+        if let (Some(new_low), Some(new_high)) = (new_low, new_high) {
+            let new_pointer = if new_low == new_high {
+                new_low
+            } else {
+                let node = BddNode::mk_node(decision_var, new_low, new_high);
+                *existing.entry(node).or_insert_with(|| {
+                    result.push_node(node);
+                    result.root_pointer()
+                })
+            };
+            if is_node_task(&on_stack) {
+                finished_nodes[node.to_index()] = Some(new_pointer);
+            } else {
+                finished_other.insert(on_stack, new_pointer);
+            }
+            stack.pop();
+        } else {
+            if new_low.is_none() {
+                stack.push(comp_low);
+            }
+            if new_high.is_none() {
+                stack.push(comp_high);
+            }
+        }
+    }
+
+    match lookup(&finished_nodes, &finished_other, root) {
+        Some(pointer) if pointer.is_zero() => Bdd::mk_false(num_vars),
+        _ => result,
     }
 }
 
@@ -0,0 +1,466 @@
+use super::_impl_node_store::NodeStore;
+use crate::{Bdd, BddPointer, BddVariable};
+use fxhash::FxBuildHasher;
+use std::collections::HashMap;
+
+/// Simplification of `Bdd`s with respect to a care set (don't-care minimization).
+impl Bdd {
+    /// Compute the generalized cofactor of this `Bdd` with respect to the `care` set
+    /// (the *constrain* operator of Coudert and Madre).
+    ///
+    /// The result agrees with this `Bdd` on all valuations of the care set, i.e.
+    /// `result.and(care) == self.and(care)`, and is typically smaller than this `Bdd`.
+    /// Outside of the care set, every valuation is mapped to the "closest" valuation in the
+    /// care set (with respect to the variable ordering). If `care` is a cube, the result is
+    /// the restriction of this `Bdd` to the literals of the cube (see `Bdd::restrict`).
+    ///
+    /// If the care set is empty, the result is `false`.
+    ///
+    /// *Panics:* Both `Bdd`s must have the same number of variables.
+    pub fn constrain(&self, care: &Bdd) -> Bdd {
+        self.check_compatible(care);
+        if care.is_false() {
+            return care.clone();
+        }
+        if care.is_true() || self.is_true() || self.is_false() {
+            return self.clone();
+        }
+        let mut state = CareState::new(self, care);
+        let root = state.constrain(self.root_pointer(), state.care_root);
+        state.store.into_bdd(root)
+    }
+
+    /// Simplify this `Bdd` with respect to the `care` set using the *restrict* heuristic
+    /// of Coudert and Madre.
+    ///
+    /// Similar to `Bdd::constrain`, the result agrees with this `Bdd` on all valuations of the
+    /// care set, i.e. `result.and(care) == self.and(care)`. However, variables of the care set
+    /// which do not appear in this `Bdd` are first eliminated from the care set (existentially),
+    /// so the result only depends on the variables of this `Bdd`. Furthermore, the result is
+    /// guaranteed to be no larger than this `Bdd` (if the heuristic produces a larger `Bdd`,
+    /// this `Bdd` is returned unchanged).
+    ///
+    /// If the care set is empty, the result is `false`.
+    ///
+    /// *Panics:* Both `Bdd`s must have the same number of variables.
+    pub fn restrict_with(&self, care: &Bdd) -> Bdd {
+        self.check_compatible(care);
+        if care.is_false() {
+            return care.clone();
+        }
+        if care.is_true() || self.is_true() || self.is_false() {
+            return self.clone();
+        }
+        let mut state = CareState::new(self, care);
+        let root = state.restrict(self.root_pointer(), state.care_root);
+        let result = state.store.into_bdd(root);
+        if result.size() > self.size() {
+            self.clone()
+        } else {
+            result
+        }
+    }
+}
+
+/// **(internal)** Working state of a care set simplification.
+///
+/// The nodes of the care set are copied into the store up front, so that they can be
+/// combined with other nodes derived from the care set. The nodes of the simplified `Bdd` are
+/// only copied into the store once they are known to be a part of the result.
+struct CareState<'a> {
+    function: &'a Bdd,
+    care_root: BddPointer,
+    store: NodeStore,
+    /// Position of the nodes of `function` copied into the store.
+    imported: Vec<Option<BddPointer>>,
+    /// Results for pairs of a `function` node and a care set node.
+    cache: HashMap<(BddPointer, BddPointer), BddPointer, FxBuildHasher>,
+    /// Results of disjunctions of pairs of care set nodes (in the store).
+    or_cache: HashMap<(BddPointer, BddPointer), BddPointer, FxBuildHasher>,
+    /// Work stack of `import` (empty between calls).
+    import_stack: Vec<BddPointer>,
+    /// Work and result stacks of `or` (empty between calls).
+    or_stack: Vec<OrTask>,
+    or_results: Vec<BddPointer>,
+}
+
+impl<'a> CareState<'a> {
+    fn new(function: &'a Bdd, care: &Bdd) -> CareState<'a> {
+        let mut store = NodeStore::new(function.num_vars(), function.size() + care.size());
+        let mut care_nodes: Vec<BddPointer> = Vec::with_capacity(care.size());
+        for node in care.pointers() {
+            let pointer = if node.is_terminal() {
+                node
+            } else {
+                let low = care_nodes[care.low_link_of(node).to_index()];
+                let high = care_nodes[care.high_link_of(node).to_index()];
+                store.mk_node(care.var_of(node), low, high)
+            };
+            care_nodes.push(pointer);
+        }
+        CareState {
+            function,
+            care_root: care_nodes[care.size() - 1],
+            store,
+            imported: vec![None; function.size()],
+            cache: HashMap::default(),
+            or_cache: HashMap::default(),
+            import_stack: Vec::new(),
+            or_stack: Vec::new(),
+            or_results: Vec::new(),
+        }
+    }
+
+    /// The variable of a `function` node (terminals are below all variables).
+    fn function_var(&self, node: BddPointer) -> BddVariable {
+        if node.is_terminal() {
+            BddVariable(self.function.num_vars())
+        } else {
+            self.function.var_of(node)
+        }
+    }
+
+    /// The two cofactors of a `function` node with respect to `var`.
+    fn function_cofactors(&self, node: BddPointer, var: BddVariable) -> (BddPointer, BddPointer) {
+        if self.function_var(node) == var {
+            (
+                self.function.low_link_of(node),
+                self.function.high_link_of(node),
+            )
+        } else {
+            (node, node)
+        }
+    }
+
+    /// Constrain the `function` node to the (non-empty) care set node.
+    fn constrain(&mut self, node: BddPointer, care: BddPointer) -> BddPointer {
+        self.simplify(node, care, false)
+    }
+
+    /// Restrict the `function` node to the (non-empty) care set node.
+    fn restrict(&mut self, node: BddPointer, care: BddPointer) -> BddPointer {
+        self.simplify(node, care, true)
+    }
+
+    /// Compute `constrain` (or `restrict` if `restrict` is set) for the `function` node and
+    /// the (non-empty) care set node.
+    ///
+    /// The pairs of nodes are explored using an explicit stack. A pair is expanded into its
+    /// cofactor pairs: if one of the care set cofactors is empty, the result is the result
+    /// of the other pair (`Forward`), otherwise the two results are joined into a node. When
+    /// restricting, a care set node whose variable is above the `function` node is first
+    /// replaced with the disjunction of its children (again using `Forward`).
+    fn simplify(&mut self, node: BddPointer, care: BddPointer, restrict: bool) -> BddPointer {
+        let mut stack: Vec<CareTask> = vec![CareTask::Expand(node, care)];
+        let mut results: Vec<BddPointer> = Vec::new();
+        while let Some(task) = stack.pop() {
+            match task {
+                CareTask::Expand(node, care) => {
+                    if let Some(result) = self.resolved(node, care) {
+                        results.push(result);
+                        continue;
+                    }
+                    let var = self.function_var(node);
+                    let care_var = self.store.var_of(care);
+                    if restrict && care_var < var {
+                        // The function does not depend on the top variable of the care set.
+                        let (care_low, care_high) = self.store.links_of(care);
+                        let care_merged = self.or(care_low, care_high);
+                        stack.push(CareTask::Forward((node, care)));
+                        stack.push(CareTask::Expand(node, care_merged));
+                        continue;
+                    }
+                    let var = var.min(care_var);
+                    let (low, high) = self.function_cofactors(node, var);
+                    let (care_low, care_high) = self.store.cofactors(care, var);
+                    if care_low.is_zero() {
+                        stack.push(CareTask::Forward((node, care)));
+                        stack.push(CareTask::Expand(high, care_high));
+                    } else if care_high.is_zero() {
+                        stack.push(CareTask::Forward((node, care)));
+                        stack.push(CareTask::Expand(low, care_low));
+                    } else {
+                        // The low pair is on top, so it is finished first.
+                        stack.push(CareTask::Join(var, (node, care)));
+                        stack.push(CareTask::Expand(high, care_high));
+                        stack.push(CareTask::Expand(low, care_low));
+                    }
+                }
+                CareTask::Forward(task) => {
+                    let result = *results.last().unwrap();
+                    self.cache.insert(task, result);
+                }
+                CareTask::Join(var, task) => {
+                    let high = results.pop().unwrap();
+                    let low = results.pop().unwrap();
+                    let result = self.store.mk_node(var, low, high);
+                    self.cache.insert(task, result);
+                    results.push(result);
+                }
+            }
+        }
+        results.pop().unwrap()
+    }
+
+    /// The result for a `function` node and a care set node if it is trivial (the care set
+    /// is `true` or the node is a terminal) or already in the cache.
+    fn resolved(&mut self, node: BddPointer, care: BddPointer) -> Option<BddPointer> {
+        if care.is_one() || node.is_terminal() {
+            Some(self.import(node))
+        } else {
+            self.cache.get(&(node, care)).cloned()
+        }
+    }
+
+    /// Disjunction of two nodes in the store.
+    ///
+    /// Same as in `simplify`, the pairs of nodes are explored using an explicit stack.
+    fn or(&mut self, left: BddPointer, right: BddPointer) -> BddPointer {
+        if let Some(result) = self.or_resolved(left, right) {
+            return result;
+        }
+        let mut stack = std::mem::take(&mut self.or_stack);
+        let mut results = std::mem::take(&mut self.or_results);
+        stack.push(OrTask::Expand(left, right));
+        while let Some(task) = stack.pop() {
+            match task {
+                OrTask::Expand(left, right) => {
+                    if let Some(result) = self.or_resolved(left, right) {
+                        results.push(result);
+                        continue;
+                    }
+                    let var = self.store.var_of(left).min(self.store.var_of(right));
+                    let (l_low, l_high) = self.store.cofactors(left, var);
+                    let (r_low, r_high) = self.store.cofactors(right, var);
+                    // The low pair is on top, so it is finished first.
+                    stack.push(OrTask::Join(var, or_key(left, right)));
+                    stack.push(OrTask::Expand(l_high, r_high));
+                    stack.push(OrTask::Expand(l_low, r_low));
+                }
+                OrTask::Join(var, key) => {
+                    let high = results.pop().unwrap();
+                    let low = results.pop().unwrap();
+                    let result = self.store.mk_node(var, low, high);
+                    self.or_cache.insert(key, result);
+                    results.push(result);
+                }
+            }
+        }
+        let result = results.pop().unwrap();
+        self.or_stack = stack;
+        self.or_results = results;
+        result
+    }
+
+    /// The disjunction of two nodes in the store if it is trivial (a constant or the same
+    /// node on both sides) or already in the cache.
+    fn or_resolved(&self, left: BddPointer, right: BddPointer) -> Option<BddPointer> {
+        if left.is_one() || right.is_zero() || left == right {
+            Some(left)
+        } else if right.is_one() || left.is_zero() {
+            Some(right)
+        } else {
+            self.or_cache.get(&or_key(left, right)).cloned()
+        }
+    }
+
+    /// Copy the sub-graph of a `function` node into the store, using an explicit stack
+    /// (the same way as `NodeStore::into_bdd`).
+    fn import(&mut self, node: BddPointer) -> BddPointer {
+        if node.is_terminal() {
+            return node;
+        }
+        if let Some(result) = self.imported[node.to_index()] {
+            return result;
+        }
+        let function = self.function;
+        let imported = &mut self.imported;
+        let position = |imported: &[Option<BddPointer>], node: BddPointer| {
+            if node.is_terminal() {
+                Some(node)
+            } else {
+                imported[node.to_index()]
+            }
+        };
+        let stack = &mut self.import_stack;
+        stack.push(node);
+        while let Some(top) = stack.last().cloned() {
+            if imported[top.to_index()].is_some() {
+                stack.pop();
+                continue;
+            }
+            let low = position(imported, function.low_link_of(top));
+            let high = position(imported, function.high_link_of(top));
+            if let (Some(low), Some(high)) = (low, high) {
+                imported[top.to_index()] =
+                    Some(self.store.mk_node(function.var_of(top), low, high));
+                stack.pop();
+            } else {
+                if low.is_none() {
+                    stack.push(function.low_link_of(top));
+                }
+                if high.is_none() {
+                    stack.push(function.high_link_of(top));
+                }
+            }
+        }
+        imported[node.to_index()].unwrap()
+    }
+}
+
+/// **(internal)** Disjunction is commutative, so each pair of nodes is stored in
+/// the `or_cache` in a fixed order.
+fn or_key(left: BddPointer, right: BddPointer) -> (BddPointer, BddPointer) {
+    if left < right {
+        (left, right)
+    } else {
+        (right, left)
+    }
+}
+
+/// **(internal)** A step of `CareState::or`: either compute the disjunction of a pair of
+/// nodes, or join the two (already computed) cofactor results of a pair into a node.
+enum OrTask {
+    Expand(BddPointer, BddPointer),
+    Join(BddVariable, (BddPointer, BddPointer)),
+}
+
+/// **(internal)** A step of a care set simplification: compute the result for a pair of
+/// a `function` node and a care set node, use the result of the last finished pair as the
+/// result of the given pair, or join the two last results into a node of the given variable
+/// (the result of the given pair).
+enum CareTask {
+    Expand(BddPointer, BddPointer),
+    Forward((BddPointer, BddPointer)),
+    Join(BddVariable, (BddPointer, BddPointer)),
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{Bdd, BddPartialValuation, BddVariable, BddVariableSet};
+
+    fn test_functions() -> Vec<Bdd> {
+        let variables = mk_5_variable_set();
+        [
+            "v1 ^ v2",
+            "v1 & !v4",
+            "v2 => v5",
+            "(v1 & v2) | (v3 & v5)",
+            "!v3 | v4",
+            "v3 <=> (v4 ^ v5)",
+            "(v1 => (v2 <=> v3)) & (!v1 => !(v2 <=> v5))",
+            "true",
+            "false",
+        ]
+        .iter()
+        .map(|e| variables.eval_expression_string(e))
+        .collect()
+    }
+
+    #[test]
+    fn constrain_agrees_on_care_set() {
+        let functions = test_functions();
+        for f in &functions {
+            for care in &functions {
+                let constrained = f.constrain(care);
+                assert_eq!(f.and(care), constrained.and(care));
+                // Constraining is idempotent.
+                assert_eq!(constrained, constrained.constrain(care));
+            }
+        }
+    }
+
+    #[test]
+    fn constrain_basic() {
+        let variables = mk_5_variable_set();
+        let f = variables.eval_expression_string("(v1 & v2) | (v3 & v5)");
+        assert!(f.constrain(&variables.mk_false()).is_false());
+        assert_eq!(f, f.constrain(&variables.mk_true()));
+        assert!(f.constrain(&f).is_true());
+        // With a cube, constrain is the same as restriction.
+        let cube =
+            BddPartialValuation::from_values(&[(BddVariable(1), false), (BddVariable(4), true)]);
+        assert_eq!(
+            f.restrict(&cube),
+            f.constrain(&variables.mk_conjunctive_clause(&cube))
+        );
+        let care = variables.eval_expression_string("v1 <=> v2");
+        assert_eq!(
+            variables.eval_expression_string("v1 | (v3 & v5)"),
+            f.constrain(&care)
+        );
+    }
+
+    #[test]
+    fn constrain_deep_chain() {
+        // The chains are much deeper than what a recursive traversal can handle.
+        let variables = BddVariableSet::new_anonymous(60_000);
+        let all = variables.variables();
+        let cube = |vars: &[BddVariable]| {
+            let values: Vec<(BddVariable, bool)> = vars.iter().map(|v| (*v, true)).collect();
+            variables.mk_conjunctive_clause(&BddPartialValuation::from_values(&values))
+        };
+        let last = variables.mk_var(all[all.len() - 1]);
+        let f = cube(&all);
+        // The care set follows the chain and only leaves the last variable unknown.
+        assert_eq!(last, f.constrain(&cube(&all[..all.len() - 1])));
+        // The care set is satisfied after the first variable, so the rest is copied.
+        let care = variables.mk_var(all[0]);
+        assert_eq!(cube(&all[1..]), f.constrain(&care));
+    }
+
+    #[test]
+    fn restrict_with_agrees_on_care_set() {
+        let functions = test_functions();
+        for f in &functions {
+            for care in &functions {
+                let restricted = f.restrict_with(care);
+                assert_eq!(f.and(care), restricted.and(care));
+                assert!(restricted.size() <= f.size());
+            }
+        }
+    }
+
+    #[test]
+    fn restrict_with_deep_chain() {
+        let variables = BddVariableSet::new_anonymous(60_000);
+        let all = variables.variables();
+        let n = all.len();
+        let cube = |vars: &[BddVariable]| {
+            let values: Vec<(BddVariable, bool)> = vars.iter().map(|v| (*v, true)).collect();
+            variables.mk_conjunctive_clause(&BddPartialValuation::from_values(&values))
+        };
+        let f = cube(&all[1..]);
+        // The function does not depend on the first variable of the care set, so its
+        // two (deep) cofactors are merged before the function is restricted.
+        let first = variables.mk_var(all[0]);
+        let care = first
+            .and(&cube(&all[2..]))
+            .or(&first.not().and(&cube(&all[1..n - 1])));
+        let restricted = f.restrict_with(&care);
+        assert_eq!(
+            variables.mk_var(all[1]).and(&variables.mk_var(all[n - 1])),
+            restricted
+        );
+        assert_eq!(f.and(&care), restricted.and(&care));
+    }
+
+    #[test]
+    fn restrict_with_basic() {
+        let variables = mk_5_variable_set();
+        let f = variables.eval_expression_string("(v1 & v2) | (v3 & v5)");
+        assert!(f.restrict_with(&variables.mk_false()).is_false());
+        assert_eq!(f, f.restrict_with(&variables.mk_true()));
+        assert!(f.restrict_with(&f).is_true());
+        // Care set variables which do not appear in the function are eliminated first,
+        // whereas constrain introduces them into the result.
+        let f = variables.eval_expression_string("v2 & v3");
+        let care = variables.eval_expression_string("v1 => v2");
+        assert_eq!(f, f.restrict_with(&care));
+        assert_eq!(
+            variables.eval_expression_string("(v1 => v3) & (!v1 => v2 & v3)"),
+            f.constrain(&care)
+        );
+    }
+}
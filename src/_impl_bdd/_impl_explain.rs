@@ -0,0 +1,258 @@
+use crate::{Bdd, BddPartialValuation, BddPointer, BddValuation, BddVariable};
+
+/// Methods which explain the value of a `Bdd` in a specific valuation.
+impl Bdd {
+    /// Compute a subset-minimal part of the given `valuation` which is sufficient for this
+    /// `Bdd` to evaluate to the same value as in `valuation` (also known as an abductive
+    /// explanation, or a prime implicant of the function or its negation which is consistent
+    /// with `valuation`).
+    ///
+    /// The literals of `vars` are removed greedily in the given order, as long as the
+    /// remaining literals still imply the value of the function. Literals of variables which
+    /// do not appear in `vars` are always kept, so to obtain a minimal explanation over all
+    /// variables, `vars` should list every variable (the order then expresses which literals
+    /// are preferably removed from the explanation). The result is minimal in the sense that
+    /// no other literal of `vars` can be removed, but it is not necessarily the smallest
+    /// explanation.
+    ///
+    /// *Panics:* `valuation` must have the same number of variables as this `Bdd`.
+    pub fn sufficient_reason(
+        &self,
+        valuation: &BddValuation,
+        vars: &[BddVariable],
+    ) -> BddPartialValuation {
+        let num_vars = self.num_vars();
+        assert_eq!(
+            num_vars,
+            valuation.num_vars(),
+            "Valuation and Bdd have different number of variables."
+        );
+        let expected = self.eval_in(valuation);
+        let mut reason = BddPartialValuation::empty();
+        for i in 0..num_vars {
+            let var = BddVariable(i);
+            reason.set_value(var, valuation.value(var));
+        }
+        for var in vars {
+            reason.unset_value(*var);
+            let restricted = self.restrict(&reason);
+            let implied = if expected {
+                restricted.is_true()
+            } else {
+                restricted.is_false()
+            };
+            if !implied {
+                reason.set_value(*var, valuation.value(*var));
+            }
+        }
+        reason
+    }
+
+    /// Find the cheapest valuation in which this `Bdd` evaluates to the opposite value than
+    /// in the given `valuation` (a counterfactual explanation). Flipping the value of a variable
+    /// `v` costs `cost[v]` and the cost of a valuation is the sum of the costs of all variables
+    /// in which it differs from `valuation`. Returns `None` if the `Bdd` is constant, i.e.
+    /// no change of the valuation can change its value.
+    ///
+    /// This is a shortest path search in the `Bdd`: following the edge of a decision node that
+    /// disagrees with `valuation` costs the flip cost of its variable, while skipped variables
+    /// keep their original values for free. Since the `Bdd` is acyclic and the nodes are sorted
+    /// topologically, the distances are computed in a single pass over its nodes (the paths
+    /// into the terminal which is not the value of `valuation` are considered). When several
+    /// paths are equally expensive, the result prefers to keep the original values.
+    ///
+    /// *Panics:* `valuation` must have the same number of variables as this `Bdd` and `cost`
+    /// must contain a non-negative value for every variable.
+    pub fn counterfactual(&self, valuation: &BddValuation, cost: &[f64]) -> Option<BddValuation> {
+        let num_vars = self.num_vars();
+        assert_eq!(
+            num_vars,
+            valuation.num_vars(),
+            "Valuation and Bdd have different number of variables."
+        );
+        assert_eq!(
+            usize::from(num_vars),
+            cost.len(),
+            "Cost must be given for every variable."
+        );
+        assert!(
+            cost.iter().all(|c| *c >= 0.0),
+            "Flip costs must be non-negative."
+        );
+        let target = if self.eval_in(valuation) {
+            BddPointer::zero()
+        } else {
+            BddPointer::one()
+        };
+        if self.root_pointer().is_terminal() {
+            return None;
+        }
+
+        // `distance[i]` is the cheapest cost of reaching the target terminal from node `i`,
+        // using only the variables of node `i` and below.
+        let mut distance: Vec<f64> = vec![f64::INFINITY; self.size()];
+        distance[target.to_index()] = 0.0;
+        let edge_costs = |node: BddPointer, distance: &[f64]| {
+            let var = self.var_of(node);
+            let flip = cost[usize::from(var.0)];
+            let (low_flip, high_flip) = if valuation.value(var) {
+                (flip, 0.0)
+            } else {
+                (0.0, flip)
+            };
+            (
+                low_flip + distance[self.low_link_of(node).to_index()],
+                high_flip + distance[self.high_link_of(node).to_index()],
+            )
+        };
+        for node in self.pointers().skip(2) {
+            let (low, high) = edge_costs(node, &distance);
+            distance[node.to_index()] = low.min(high);
+        }
+        if distance[self.root_pointer().to_index()].is_infinite() {
+            return None;
+        }
+
+        let mut result = valuation.clone();
+        let mut node = self.root_pointer();
+        while !node.is_terminal() {
+            let var = self.var_of(node);
+            let (low, high) = edge_costs(node, &distance);
+            let go_high = if low == high {
+                valuation.value(var)
+            } else {
+                high < low
+            };
+            if go_high {
+                result.set(var);
+                node = self.high_link_of(node);
+            } else {
+                result.clear(var);
+                node = self.low_link_of(node);
+            }
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{BddPartialValuation, BddValuation, BddVariable};
+
+    #[test]
+    fn sufficient_reason_basic() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("(v1 & v2) | (v3 & !v4)");
+        let all: Vec<BddVariable> = variables.variables();
+        let (v1, v2, v3, v4) = (
+            BddVariable(0),
+            BddVariable(1),
+            BddVariable(2),
+            BddVariable(3),
+        );
+
+        // Both terms are true, the order decides which one is the explanation.
+        let valuation = BddValuation::new(vec![true, true, true, false, true]);
+        assert_eq!(
+            BddPartialValuation::from_values(&[(v3, true), (v4, false)]),
+            bdd.sufficient_reason(&valuation, &all)
+        );
+        let reversed: Vec<BddVariable> = all.iter().rev().cloned().collect();
+        assert_eq!(
+            BddPartialValuation::from_values(&[(v1, true), (v2, true)]),
+            bdd.sufficient_reason(&valuation, &reversed)
+        );
+
+        // Explanation of a negative classification.
+        let valuation = BddValuation::new(vec![false, true, true, true, false]);
+        let reason = bdd.sufficient_reason(&valuation, &all);
+        assert_eq!(
+            BddPartialValuation::from_values(&[(v1, false), (v4, true)]),
+            reason
+        );
+        assert!(bdd.and_cube(&reason).is_false());
+
+        // Variables that are not listed are kept.
+        let reason = bdd.sufficient_reason(&valuation, &[v1, v2, v3]);
+        assert_eq!(3, reason.cardinality());
+        assert!(reason.has_value(BddVariable(4)));
+    }
+
+    #[test]
+    fn sufficient_reason_is_minimal() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("(v1 => v2) & (v3 | v4) & !(v4 & v5)");
+        let all: Vec<BddVariable> = variables.variables();
+        for valuation in variables.mk_true().sat_valuations() {
+            let expected = bdd.eval_in(&valuation);
+            let reason = bdd.sufficient_reason(&valuation, &all);
+            let restricted = bdd.restrict(&reason);
+            assert!(restricted.is_true() == expected && restricted.is_false() != expected);
+            for (var, _) in reason.to_values() {
+                let mut weaker = reason.clone();
+                weaker.unset_value(var);
+                let restricted = bdd.restrict(&weaker);
+                assert!(!restricted.is_true() && !restricted.is_false());
+            }
+        }
+    }
+
+    #[test]
+    fn counterfactual_basic() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("(v1 & v2) | (v3 & !v4)");
+        let valuation = BddValuation::new(vec![true, false, false, true, true]);
+        assert!(!bdd.eval_in(&valuation));
+
+        // Flipping `v2` is the only single change which makes the function true.
+        let uniform = vec![1.0; 5];
+        let flipped = bdd.counterfactual(&valuation, &uniform).unwrap();
+        assert_eq!(
+            BddValuation::new(vec![true, true, false, true, true]),
+            flipped
+        );
+
+        // If `v2` is expensive, flipping both `v3` and `v4` is cheaper.
+        let costs = vec![1.0, 5.0, 1.0, 1.0, 1.0];
+        let flipped = bdd.counterfactual(&valuation, &costs).unwrap();
+        assert_eq!(
+            BddValuation::new(vec![true, false, true, false, true]),
+            flipped
+        );
+
+        // Constant functions cannot be flipped.
+        assert_eq!(
+            None,
+            variables.mk_true().counterfactual(&valuation, &uniform)
+        );
+        assert_eq!(
+            None,
+            variables.mk_false().counterfactual(&valuation, &uniform)
+        );
+    }
+
+    #[test]
+    fn counterfactual_is_optimal() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("(v1 => v2) & (v3 | v4) & !(v4 & v5)");
+        let costs = vec![3.0, 1.0, 4.0, 1.5, 2.0];
+        let distance = |a: &BddValuation, b: &BddValuation| -> f64 {
+            (0..5)
+                .filter(|i| a.value(BddVariable(*i)) != b.value(BddVariable(*i)))
+                .map(|i| costs[usize::from(i)])
+                .sum()
+        };
+        for valuation in variables.mk_true().sat_valuations() {
+            let flipped = bdd.counterfactual(&valuation, &costs).unwrap();
+            assert_ne!(bdd.eval_in(&valuation), bdd.eval_in(&flipped));
+            let best = variables
+                .mk_true()
+                .sat_valuations()
+                .filter(|v| bdd.eval_in(v) != bdd.eval_in(&valuation))
+                .map(|v| distance(&valuation, &v))
+                .fold(f64::INFINITY, f64::min);
+            assert_eq!(best, distance(&valuation, &flipped));
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::*;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+
+/// Export of `Bdd`s into the `.nnf` format used by knowledge compilation tools.
+impl Bdd {
+    /// Write this `Bdd` into the given `output` as a d-DNNF in the `.nnf` format (as used by
+    /// the `c2d` compiler and tools built around it, like `d4` or model counters).
+    ///
+    /// Every decision node becomes a deterministic disjunction of two decomposable
+    /// conjunctions: $(\neg x \land low) \lor (x \land high)$. Variable $x_i$ corresponds to
+    /// the literal `i + 1` and the root is the last node of the output.
+    pub fn write_as_nnf(&self, output: &mut dyn Write) -> Result<(), std::io::Error> {
+        // Lines of the output and the line which represents each `Bdd` node (if any).
+        let mut lines: Vec<String> = Vec::new();
+        let mut edges = 0;
+        if self.is_false() {
+            lines.push("O 0 0".to_string());
+        } else if self.is_true() {
+            lines.push("A 0".to_string());
+        } else {
+            let mut literals: HashMap<(BddVariable, bool), usize> = HashMap::new();
+            let mut node_line: Vec<usize> = vec![0; self.size()];
+            for node in self.pointers().skip(2) {
+                let var = self.var_of(node);
+                let mut branches = Vec::with_capacity(2);
+                for (child, value) in [
+                    (self.low_link_of(node), false),
+                    (self.high_link_of(node), true),
+                ]
+                .iter()
+                {
+                    if child.is_zero() {
+                        continue;
+                    }
+                    let literal = *literals.entry((var, *value)).or_insert_with(|| {
+                        let literal = i32::from(var.0) + 1;
+                        lines.push(format!("L {}", if *value { literal } else { -literal }));
+                        lines.len() - 1
+                    });
+                    if child.is_one() {
+                        branches.push(literal);
+                    } else {
+                        lines.push(format!("A 2 {} {}", literal, node_line[child.to_index()]));
+                        edges += 2;
+                        branches.push(lines.len() - 1);
+                    }
+                }
+                if let [branch] = branches[..] {
+                    node_line[node.to_index()] = branch;
+                } else {
+                    lines.push(format!("O {} 2 {} {}", var.0 + 1, branches[0], branches[1]));
+                    edges += 2;
+                    node_line[node.to_index()] = lines.len() - 1;
+                }
+            }
+            // The root is processed last and its lines are never shared with other nodes
+            // (no other node has the same variable), so it is the last line of the output.
+            debug_assert_eq!(node_line[self.root_pointer().to_index()], lines.len() - 1);
+        }
+        writeln!(output, "nnf {} {} {}", lines.len(), edges, self.num_vars())?;
+        for line in lines {
+            writeln!(output, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    /// Convert this `Bdd` to a d-DNNF string in the `.nnf` format (see `Bdd::write_as_nnf`).
+    pub fn to_nnf_string(&self) -> String {
+        let mut buffer: Vec<u8> = Vec::new();
+        self.write_as_nnf(&mut buffer)
+            .expect("Cannot write BDD to .nnf string.");
+        String::from_utf8(buffer).expect("Invalid UTF formatting in string.")
+    }
+}
+
+impl BddVariableSet {
+    /// Read a negation normal form formula (for example a d-DNNF produced by a knowledge
+    /// compiler) in the `.nnf` format and convert it into a `Bdd` in this variable set.
+    ///
+    /// Literal `i` corresponds to the variable $x_{i-1}$. The conversion builds a `Bdd` for
+    /// every node of the formula, so it is only practical if the intermediate functions have
+    /// reasonably small `Bdd`s (for example, when the node supports are small).
+    pub fn read_nnf(&self, input: &mut dyn Read) -> Result<Bdd, String> {
+        let mut data = String::new();
+        input
+            .read_to_string(&mut data)
+            .map_err(|e| format!("{}", e))?;
+        let mut lines = data
+            .lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty() && !l.starts_with('c'));
+        let header: Vec<&str> = lines
+            .next()
+            .ok_or_else(|| "Missing .nnf header.".to_string())?
+            .split_whitespace()
+            .collect();
+        if header.len() != 4 || header[0] != "nnf" {
+            return Err(format!("Invalid .nnf header: {}.", header.join(" ")));
+        }
+        let mut nodes: Vec<Bdd> = Vec::new();
+        for line in lines {
+            let items: Vec<&str> = line.split_whitespace().collect();
+            let numbers = items[1..]
+                .iter()
+                .map(|i| i.parse::<i64>())
+                .collect::<Result<Vec<i64>, _>>()
+                .map_err(|e| format!("Invalid .nnf line `{}`: {}.", line, e))?;
+            let children = |skip: usize| -> Result<Vec<&Bdd>, String> {
+                let count = *numbers
+                    .get(skip)
+                    .ok_or_else(|| format!("Invalid .nnf line `{}`.", line))?;
+                if numbers.len() != skip + 1 + count as usize {
+                    return Err(format!("Invalid number of children in `{}`.", line));
+                }
+                numbers[(skip + 1)..]
+                    .iter()
+                    .map(|child| {
+                        nodes
+                            .get(*child as usize)
+                            .ok_or_else(|| format!("Invalid child {} in `{}`.", child, line))
+                    })
+                    .collect()
+            };
+            let bdd = match (items[0], numbers.as_slice()) {
+                ("L", [literal]) if *literal != 0 => {
+                    let var = literal.abs() - 1;
+                    if var >= i64::from(self.num_vars) {
+                        return Err(format!("Unknown variable in literal {}.", literal));
+                    }
+                    self.mk_literal(BddVariable(var as u16), *literal > 0)
+                }
+                ("A", _) => children(0)?
+                    .into_iter()
+                    .fold(self.mk_true(), |acc, child| acc.and(child)),
+                ("O", _) => children(1)?
+                    .into_iter()
+                    .fold(self.mk_false(), |acc, child| acc.or(child)),
+                _ => return Err(format!("Invalid .nnf line `{}`.", line)),
+            };
+            nodes.push(bdd);
+        }
+        nodes
+            .pop()
+            .ok_or_else(|| "The .nnf formula has no nodes.".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+
+    #[test]
+    fn nnf_export_small() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("v1 & !v3");
+        assert_eq!("nnf 3 2 5\nL -3\nL 1\nA 2 1 0\n", bdd.to_nnf_string());
+        let bdd = variables.eval_expression_string("v2 ^ v5");
+        assert_eq!(
+            "nnf 7 6 5\nL -5\nL 5\nL -2\nA 2 2 1\nL 2\nA 2 4 0\nO 2 2 3 5\n",
+            bdd.to_nnf_string()
+        );
+    }
+
+    #[test]
+    fn nnf_round_trip() {
+        let variables = mk_5_variable_set();
+        for expression in [
+            "true",
+            "false",
+            "v3",
+            "!v1",
+            "(v1 => (v2 <=> v3)) & (!v1 => !(v2 <=> v5))",
+            "(v1 ^ v3) | (v2 & v4 & !v5)",
+        ]
+        .iter()
+        {
+            let bdd = variables.eval_expression_string(expression);
+            let nnf = bdd.to_nnf_string();
+            assert_eq!(bdd, variables.read_nnf(&mut nnf.as_bytes()).unwrap());
+        }
+    }
+
+    #[test]
+    fn nnf_import_errors() {
+        let variables = mk_5_variable_set();
+        let read = |s: &str| variables.read_nnf(&mut s.as_bytes());
+        assert!(read("").is_err());
+        assert!(read("nnf 1 0").is_err());
+        assert!(read("nnf 1 0 6\nL 6").is_err());
+        assert!(read("nnf 2 2 5\nL 1\nA 2 0 3").is_err());
+        assert!(read("nnf 1 0 5\nX 1").is_err());
+        assert!(read("nnf 0 0 5").is_err());
+        assert_eq!(
+            variables.eval_expression_string("v1 | !v2"),
+            read("c comment\nnnf 3 2 5\nL 1\nL -2\nO 0 2 0 1").unwrap()
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{Bdd, BddNode, BddPointer, BddVariable};
+use fxhash::FxBuildHasher;
+use std::collections::HashMap;
+
+/// Conversions between `Bdd`s and explicit sets of valuation indices.
+impl Bdd {
+    /// Convert this `Bdd` into an explicit set of valuations of the given `vars`. Each valuation
+    /// is encoded as an integer index where bit `i` is the value of `vars[i]` (i.e. `vars[0]`
+    /// is the least significant bit). Variables which are not in `vars` are existentially
+    /// projected away first, so the result contains every valuation of `vars` which can be
+    /// extended to a satisfying valuation of this `Bdd`.
+    ///
+    /// The indices are sorted in increasing order, so they can be directly used to create
+    /// other sorted set representations (like roaring bitmaps). Keep in mind that the result
+    /// can be exponentially larger than the `Bdd`.
+    ///
+    /// *Panics:* `vars` must contain at most 64 unique valid variables.
+    pub fn to_index_set(&self, vars: &[BddVariable]) -> Vec<u64> {
+        let levels = index_levels(self.num_vars(), vars);
+        let other: Vec<BddVariable> = (0..self.num_vars())
+            .map(BddVariable)
+            .filter(|v| !vars.contains(v))
+            .collect();
+        let projected = self.project(&other);
+        let mut result = Vec::new();
+        collect_indices(
+            &projected,
+            &levels,
+            projected.root_pointer(),
+            0,
+            0,
+            &mut result,
+        );
+        result.sort_unstable();
+        result
+    }
+
+    /// **(internal)** Create a `Bdd` with `num_vars` variables that contains exactly the
+    /// valuations of `vars` encoded in `indices` (using the same encoding as `to_index_set`).
+    /// The remaining variables are unconstrained.
+    ///
+    /// The nodes are created directly (without `apply`): the indices are sorted such that
+    /// every decision node corresponds to a contiguous block of indices and the blocks are
+    /// split recursively, high half first, so the nodes are created in the canonical order.
+    pub(crate) fn from_index_set(num_vars: u16, vars: &[BddVariable], indices: &[u64]) -> Bdd {
+        let levels = index_levels(num_vars, vars);
+        let depth = levels.len();
+        // Re-encode the indices so that the smallest variable is the most significant bit.
+        let mut keys: Vec<u64> = indices
+            .iter()
+            .map(|index| {
+                if depth < 64 && (index >> depth) != 0 {
+                    panic!("Index {} is out of range for {} variables.", index, depth);
+                }
+                levels
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (_, bit))| (index >> bit) & 1 == 1)
+                    .fold(0u64, |key, (level, _)| key | (1 << (depth - 1 - level)))
+            })
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+
+        let mut result = Bdd::mk_true(num_vars);
+        let mut existing: HashMap<BddNode, BddPointer, FxBuildHasher> =
+            HashMap::with_capacity_and_hasher(keys.len(), FxBuildHasher::default());
+        let root = build_from_keys(&mut result, &mut existing, &levels, &keys, 0);
+        if root.is_zero() {
+            Bdd::mk_false(num_vars)
+        } else if root.is_one() {
+            Bdd::mk_true(num_vars)
+        } else {
+            result
+        }
+    }
+}
+
+/// **(internal)** Pair every variable of `vars` with its bit position in the index and sort
+/// the pairs by variable.
+fn index_levels(num_vars: u16, vars: &[BddVariable]) -> Vec<(BddVariable, usize)> {
+    if vars.len() > 64 {
+        panic!("At most 64 variables can be encoded in an index.");
+    }
+    let mut levels: Vec<(BddVariable, usize)> =
+        vars.iter().enumerate().map(|(i, v)| (*v, i)).collect();
+    levels.sort();
+    for (i, (var, _)) in levels.iter().enumerate() {
+        if var.0 >= num_vars {
+            panic!(
+                "Variable {} is not valid in a Bdd with {} variables.",
+                var, num_vars
+            );
+        }
+        if i > 0 && levels[i - 1].0 == *var {
+            panic!("Variable {} appears in the index multiple times.", var);
+        }
+    }
+    levels
+}
+
+/// **(internal)** Append the indices of all valuations of `levels[level..]` accepted by
+/// `node` (extended with the bits already set in `index`) to `result`.
+fn collect_indices(
+    bdd: &Bdd,
+    levels: &[(BddVariable, usize)],
+    node: BddPointer,
+    level: usize,
+    index: u64,
+    result: &mut Vec<u64>,
+) {
+    if node.is_zero() {
+        return;
+    }
+    if level == levels.len() {
+        result.push(index);
+        return;
+    }
+    let (var, bit) = levels[level];
+    let (low, high) = if !node.is_terminal() && bdd.var_of(node) == var {
+        (bdd.low_link_of(node), bdd.high_link_of(node))
+    } else {
+        (node, node)
+    };
+    collect_indices(bdd, levels, low, level + 1, index, result);
+    collect_indices(bdd, levels, high, level + 1, index | (1 << bit), result);
+}
+
+/// **(internal)** Create the nodes for the sorted `keys`, which all share their first `level`
+/// most significant bits, and return the pointer to the resulting function.
+fn build_from_keys(
+    result: &mut Bdd,
+    existing: &mut HashMap<BddNode, BddPointer, FxBuildHasher>,
+    levels: &[(BddVariable, usize)],
+    keys: &[u64],
+    level: usize,
+) -> BddPointer {
+    if keys.is_empty() {
+        return BddPointer::zero();
+    }
+    if level == levels.len() {
+        return BddPointer::one();
+    }
+    let bit = levels.len() - 1 - level;
+    let split = keys
+        .iter()
+        .position(|k| (k >> bit) & 1 == 1)
+        .unwrap_or(keys.len());
+    // High link first, to follow the canonical node order.
+    let high = build_from_keys(result, existing, levels, &keys[split..], level + 1);
+    let low = build_from_keys(result, existing, levels, &keys[..split], level + 1);
+    if low == high {
+        return low;
+    }
+    let node = BddNode::mk_node(levels[level].0, low, high);
+    *existing.entry(node).or_insert_with(|| {
+        result.push_node(node);
+        result.root_pointer()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::BddVariable;
+
+    #[test]
+    fn index_set_basic() {
+        let variables = mk_5_variable_set();
+        let (v1, v2, v3) = (BddVariable(0), BddVariable(1), BddVariable(2));
+        let bdd = variables.eval_expression_string("(v1 & !v2) | (v3 & v4)");
+        assert_eq!(vec![1, 4, 5, 6, 7], bdd.to_index_set(&[v1, v2, v3]));
+        // The bit order follows the order of the given variables.
+        assert_eq!(vec![1, 2, 3, 5, 7], bdd.to_index_set(&[v3, v1, v2]));
+        assert_eq!(vec![0, 1], bdd.to_index_set(&[v1]));
+        assert_eq!(vec![0], bdd.to_index_set(&[]));
+        assert!(variables.mk_false().to_index_set(&[v1, v2]).is_empty());
+
+        let expected = variables.eval_expression_string("(v1 & !v2) | v3");
+        assert_eq!(
+            expected,
+            variables.mk_from_index_set(&[v1, v2, v3], &[1, 4, 5, 6, 7])
+        );
+        assert_eq!(
+            expected,
+            variables.mk_from_index_set(&[v3, v1, v2], &[7, 1, 2, 3, 5, 2])
+        );
+        assert_eq!(
+            variables.mk_false(),
+            variables.mk_from_index_set(&[v1], &[])
+        );
+        assert_eq!(
+            variables.mk_true(),
+            variables.mk_from_index_set(&[v1], &[0, 1])
+        );
+        assert_eq!(variables.mk_true(), variables.mk_from_index_set(&[], &[0]));
+    }
+
+    #[test]
+    fn index_set_round_trip() {
+        let variables = mk_5_variable_set();
+        let all = variables.variables();
+        let reversed: Vec<BddVariable> = all.iter().rev().cloned().collect();
+        for expression in &[
+            "(v1 => v2) & (v3 | v4) & !(v4 & v5)",
+            "v1 ^ v2 ^ v3 ^ v4 ^ v5",
+            "(v2 & v4) | !v5",
+            "true",
+        ] {
+            let bdd = variables.eval_expression_string(expression);
+            for vars in &[&all, &reversed] {
+                let indices = bdd.to_index_set(vars);
+                assert_eq!(bdd.cardinality(), indices.len() as f64);
+                assert_eq!(bdd, variables.mk_from_index_set(vars, &indices));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_set_out_of_range() {
+        let variables = mk_5_variable_set();
+        variables.mk_from_index_set(&[BddVariable(0), BddVariable(1)], &[4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_set_duplicate_variable() {
+        let variables = mk_5_variable_set();
+        variables
+            .mk_true()
+            .to_index_set(&[BddVariable(0), BddVariable(0)]);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{Bdd, BddNode, BddPointer, BddVariable};
+use fxhash::FxBuildHasher;
+use std::collections::HashMap;
+
+/// **(internal)** A growable set of reduced decision nodes which are not (yet) stored in
+/// the canonical order.
+///
+/// Operations which cannot create their result nodes in the canonical order (DFS post-order,
+/// high link first) build them in a `NodeStore` instead. The store only ever contains unique
+/// nodes with different children, so once the result is known, it is enough to copy the nodes
+/// reachable from the result root into a `Bdd` using `into_bdd`.
+pub(crate) struct NodeStore {
+    num_vars: u16,
+    nodes: Vec<BddNode>,
+    existing: HashMap<BddNode, BddPointer, FxBuildHasher>,
+    /// Results of `mk_select` (keyed by variable, low and high pointer).
+    selected: HashMap<(BddVariable, BddPointer, BddPointer), BddPointer, FxBuildHasher>,
+    /// Work stacks of `mk_select` (empty between calls).
+    select_stack: Vec<SelectTask>,
+    select_results: Vec<BddPointer>,
+}
+
+impl NodeStore {
+    /// Create a store with the two terminal nodes, expecting roughly `capacity` nodes.
+    pub(crate) fn new(num_vars: u16, capacity: usize) -> NodeStore {
+        NodeStore {
+            num_vars,
+            nodes: vec![BddNode::mk_zero(num_vars), BddNode::mk_one(num_vars)],
+            existing: HashMap::with_capacity_and_hasher(capacity, FxBuildHasher::default()),
+            selected: HashMap::default(),
+            select_stack: Vec::new(),
+            select_results: Vec::new(),
+        }
+    }
+
+    /// The decision variable of the given node (terminals have the variable `num_vars`, so
+    /// they are always below every decision node).
+    pub(crate) fn var_of(&self, node: BddPointer) -> BddVariable {
+        self.nodes[node.to_index()].var
+    }
+
+    /// The low and high link of the given node.
+    pub(crate) fn links_of(&self, node: BddPointer) -> (BddPointer, BddPointer) {
+        let node = self.nodes[node.to_index()];
+        (node.low_link, node.high_link)
+    }
+
+    /// The two cofactors of `node` with respect to `var`, assuming `var` is not below the
+    /// variable of `node`.
+    pub(crate) fn cofactors(&self, node: BddPointer, var: BddVariable) -> (BddPointer, BddPointer) {
+        if self.var_of(node) == var {
+            self.links_of(node)
+        } else {
+            (node, node)
+        }
+    }
+
+    /// Find or create a node with the given variable and links. The variable must be above
+    /// the variables of both links.
+    pub(crate) fn mk_node(
+        &mut self,
+        var: BddVariable,
+        low: BddPointer,
+        high: BddPointer,
+    ) -> BddPointer {
+        if low == high {
+            return low;
+        }
+        let node = BddNode::mk_node(var, low, high);
+        let nodes = &mut self.nodes;
+        *self.existing.entry(node).or_insert_with(|| {
+            nodes.push(node);
+            BddPointer::from_index(nodes.len() - 1)
+        })
+    }
+
+    /// Create the function $(var \land high) \lor (\neg var \land low)$ for any variable,
+    /// i.e. also when `var` is not above the variables of the links (in which case the
+    /// links are split by their top variable first).
+    ///
+    /// The split pairs of links are explored using an explicit stack, so the depth of the
+    /// links is not limited by the size of the call stack.
+    pub(crate) fn mk_select(
+        &mut self,
+        var: BddVariable,
+        low: BddPointer,
+        high: BddPointer,
+    ) -> BddPointer {
+        let top = match self.try_select(var, low, high) {
+            Ok(result) => return result,
+            Err(top) => top,
+        };
+        // The stacks are kept between calls, so that they are only allocated once.
+        let mut stack = std::mem::take(&mut self.select_stack);
+        let mut results = std::mem::take(&mut self.select_results);
+        stack.push(SelectTask::Split(top, low, high));
+        while let Some(task) = stack.pop() {
+            match task {
+                SelectTask::Expand(low, high) => match self.try_select(var, low, high) {
+                    Ok(result) => results.push(result),
+                    Err(top) => stack.push(SelectTask::Split(top, low, high)),
+                },
+                SelectTask::Split(top, low, high) => {
+                    let (low_low, low_high) = self.cofactors(low, top);
+                    let (high_low, high_high) = self.cofactors(high, top);
+                    // The high pair is only resolved once the low pair is finished, since it
+                    // can use the results computed for the low pair.
+                    match self.try_select(var, low_low, high_low) {
+                        Ok(new_low) => match self.try_select(var, low_high, high_high) {
+                            Ok(new_high) => {
+                                let result = self.mk_node(top, new_low, new_high);
+                                self.selected.insert((var, low, high), result);
+                                results.push(result);
+                            }
+                            Err(high_top) => {
+                                results.push(new_low);
+                                stack.push(SelectTask::Join(top, low, high));
+                                stack.push(SelectTask::Split(high_top, low_high, high_high));
+                            }
+                        },
+                        Err(low_top) => {
+                            stack.push(SelectTask::Join(top, low, high));
+                            stack.push(SelectTask::Expand(low_high, high_high));
+                            stack.push(SelectTask::Split(low_top, low_low, high_low));
+                        }
+                    }
+                }
+                SelectTask::Join(top, low, high) => {
+                    let new_high = results.pop().unwrap();
+                    let new_low = results.pop().unwrap();
+                    let result = self.mk_node(top, new_low, new_high);
+                    self.selected.insert((var, low, high), result);
+                    results.push(result);
+                }
+            }
+        }
+        let result = results.pop().unwrap();
+        self.select_stack = stack;
+        self.select_results = results;
+        result
+    }
+
+    /// **(internal)** The result of `mk_select` if it does not need to split both links
+    /// (the links are equal, `var` is above both links or at the top of one of them), or
+    /// if it is already known. Otherwise, returns the top variable of the links.
+    #[inline]
+    fn try_select(
+        &mut self,
+        var: BddVariable,
+        low: BddPointer,
+        high: BddPointer,
+    ) -> Result<BddPointer, BddVariable> {
+        if low == high {
+            return Ok(low);
+        }
+        let top = self.var_of(low).min(self.var_of(high));
+        if var < top {
+            return Ok(self.mk_node(var, low, high));
+        }
+        if let Some(result) = self.selected.get(&(var, low, high)) {
+            return Ok(*result);
+        }
+        if var == top {
+            let (low_low, _) = self.cofactors(low, top);
+            let (_, high_high) = self.cofactors(high, top);
+            let result = self.mk_node(var, low_low, high_high);
+            self.selected.insert((var, low, high), result);
+            return Ok(result);
+        }
+        Err(top)
+    }
+
+    /// Copy the nodes reachable from `root` into a `Bdd` in the canonical order.
+    pub(crate) fn into_bdd(self, root: BddPointer) -> Bdd {
+        if root.is_zero() {
+            return Bdd::mk_false(self.num_vars);
+        }
+        if root.is_one() {
+            return Bdd::mk_true(self.num_vars);
+        }
+        let mut result = Bdd::mk_true(self.num_vars);
+        let mut position: Vec<Option<BddPointer>> = vec![None; self.nodes.len()];
+        position[0] = Some(BddPointer::zero());
+        position[1] = Some(BddPointer::one());
+        let mut stack: Vec<BddPointer> = vec![root];
+        while let Some(top) = stack.last().cloned() {
+            if position[top.to_index()].is_some() {
+                stack.pop();
+                continue;
+            }
+            let node = self.nodes[top.to_index()];
+            let low = position[node.low_link.to_index()];
+            let high = position[node.high_link.to_index()];
+            if let (Some(low), Some(high)) = (low, high) {
+                result.push_node(BddNode::mk_node(node.var, low, high));
+                position[top.to_index()] = Some(result.root_pointer());
+                stack.pop();
+            } else {
+                if low.is_none() {
+                    stack.push(node.low_link);
+                }
+                if high.is_none() {
+                    stack.push(node.high_link);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// **(internal)** A step of `NodeStore::mk_select`: resolve a pair of links, split a pair
+/// which needs it by the given top variable of the links, or join the results for the two
+/// cofactor pairs of the links into a node of their top variable.
+enum SelectTask {
+    Expand(BddPointer, BddPointer),
+    Split(BddVariable, BddPointer, BddPointer),
+    Join(BddVariable, BddPointer, BddPointer),
+}
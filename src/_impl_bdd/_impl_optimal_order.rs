@@ -0,0 +1,233 @@
+use crate::{Bdd, BddValuation, BddVariable};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// **(internal)** The largest support for which an optimal ordering can be computed.
+const MAX_OPTIMAL_ORDER_VARS: usize = 16;
+
+/// Computation of optimal variable orderings.
+impl Bdd {
+    /// Compute a variable ordering which minimizes the size of this `Bdd`, using the dynamic
+    /// programming algorithm of Friedman and Supowit.
+    ///
+    /// The result is the optimal ordering (a permutation of all variables, from the top of
+    /// the diagram to the bottom) and the reordered `Bdd`, in which the variable `order[i]` is
+    /// renamed to `BddVariable(i)` (see `Bdd::permute_variables`). The variables which do not
+    /// appear in this `Bdd` are placed below the used variables, in their original order.
+    ///
+    /// For every set of variables $A$ placed at the top of the diagram, the algorithm keeps
+    /// the distinct cofactors of the function with respect to $A$ (as truth tables) and the
+    /// smallest number of nodes needed for the variables of $A$. The number of nodes of
+    /// a variable $v$ placed directly below $A$ is the number of these cofactors which depend
+    /// on $v$. The running time is $O(n^2 \cdot 3^n)$ for $n$ used variables, so this is only
+    /// useful as a ground truth for evaluating ordering heuristics.
+    ///
+    /// *Panics:* At most 16 variables can appear in this `Bdd`.
+    pub fn optimal_order(&self) -> (Vec<BddVariable>, Bdd) {
+        let support: Vec<BddVariable> = self.support_set().into_iter().collect();
+        if support.len() > MAX_OPTIMAL_ORDER_VARS {
+            panic!(
+                "Optimal order can be computed for at most {} variables, but {} are used.",
+                MAX_OPTIMAL_ORDER_VARS,
+                support.len()
+            );
+        }
+        let mut order: Vec<BddVariable> = optimal_support_order(self, &support)
+            .into_iter()
+            .map(|i| support[i])
+            .collect();
+        for var in 0..self.num_vars() {
+            if !order.contains(&BddVariable(var)) {
+                order.push(BddVariable(var));
+            }
+        }
+        let mapping: HashMap<BddVariable, BddVariable> = order
+            .iter()
+            .enumerate()
+            .map(|(i, var)| (*var, BddVariable(i as u16)))
+            .collect();
+        let reordered = self.permute_variables(&mapping);
+        (order, reordered)
+    }
+}
+
+/// **(internal)** Truth table of a function of `r` variables (bit `i` of the index is the value
+/// of the `i`-th variable), stored in 64-bit words.
+type TruthTable = Vec<u64>;
+
+/// **(internal)** Compute the optimal order of the `support` variables, returned as indices
+/// into `support`.
+fn optimal_support_order(bdd: &Bdd, support: &[BddVariable]) -> Vec<usize> {
+    let n = support.len();
+    let full: usize = (1 << n) - 1;
+    // `cost[a]` is the minimal number of nodes of the variables in the set `a` when they are
+    // placed at the top, `last[a]` is the bottom-most of these variables in the best order.
+    let mut cost: Vec<usize> = vec![usize::MAX; full + 1];
+    let mut last: Vec<usize> = vec![0; full + 1];
+    cost[0] = 0;
+    // Distinct cofactors with respect to the sets in the current layer (of the same size).
+    let mut layer: BTreeMap<usize, Vec<TruthTable>> = BTreeMap::new();
+    layer.insert(0, vec![truth_table(bdd, support)]);
+    for size in 0..n {
+        let remaining = n - size;
+        let mut next_layer: BTreeMap<usize, BTreeSet<TruthTable>> = BTreeMap::new();
+        for (set, cofactors) in &layer {
+            for v in (0..n).filter(|v| set & (1 << v) == 0) {
+                // Position of `v` among the variables which are not in `set`.
+                let position = (0..v).filter(|u| set & (1 << u) == 0).count();
+                let next = next_layer.entry(set | (1 << v)).or_default();
+                let mut nodes = 0;
+                for table in cofactors {
+                    let low = cofactor(table, remaining, position, false);
+                    let high = cofactor(table, remaining, position, true);
+                    if low != high {
+                        nodes += 1;
+                    }
+                    next.insert(low);
+                    next.insert(high);
+                }
+                let next_set = set | (1 << v);
+                if cost[*set] + nodes < cost[next_set] {
+                    cost[next_set] = cost[*set] + nodes;
+                    last[next_set] = v;
+                }
+            }
+        }
+        layer = next_layer
+            .into_iter()
+            .map(|(set, tables)| (set, tables.into_iter().collect()))
+            .collect();
+    }
+    let mut order = Vec::with_capacity(n);
+    let mut set = full;
+    while set != 0 {
+        order.push(last[set]);
+        set &= !(1 << last[set]);
+    }
+    order.reverse();
+    order
+}
+
+/// **(internal)** Truth table of `bdd` over the `support` variables (the other variables do not
+/// influence the value of the `Bdd`).
+fn truth_table(bdd: &Bdd, support: &[BddVariable]) -> TruthTable {
+    let mut table = vec![0; table_words(support.len())];
+    let mut valuation = BddValuation::all_false(bdd.num_vars());
+    for index in 0..(1usize << support.len()) {
+        for (i, var) in support.iter().enumerate() {
+            if index & (1 << i) != 0 {
+                valuation.set(*var);
+            } else {
+                valuation.clear(*var);
+            }
+        }
+        if bdd.eval_in(&valuation) {
+            table[index / 64] |= 1 << (index % 64);
+        }
+    }
+    table
+}
+
+/// **(internal)** Number of words of a truth table of `r` variables.
+fn table_words(r: usize) -> usize {
+    if r <= 6 {
+        1
+    } else {
+        1 << (r - 6)
+    }
+}
+
+/// **(internal)** Fix the variable at `position` of a table of `r` variables to `value`.
+fn cofactor(table: &[u64], r: usize, position: usize, value: bool) -> TruthTable {
+    let mut result = vec![0; table_words(r - 1)];
+    let low_mask = (1usize << position) - 1;
+    for index in 0..(1usize << (r - 1)) {
+        let source =
+            ((index & !low_mask) << 1) | (usize::from(value) << position) | (index & low_mask);
+        if table[source / 64] & (1 << (source % 64)) != 0 {
+            result[index / 64] |= 1 << (index % 64);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{Bdd, BddVariable, BddVariableSet};
+    use std::collections::HashMap;
+
+    /// All permutations of the given variables.
+    fn permutations(vars: &[BddVariable]) -> Vec<Vec<BddVariable>> {
+        if vars.is_empty() {
+            return vec![Vec::new()];
+        }
+        let mut result = Vec::new();
+        for (i, var) in vars.iter().enumerate() {
+            let mut rest = vars.to_vec();
+            rest.remove(i);
+            for mut permutation in permutations(&rest) {
+                permutation.insert(0, *var);
+                result.push(permutation);
+            }
+        }
+        result
+    }
+
+    fn reorder(bdd: &Bdd, order: &[BddVariable]) -> Bdd {
+        let mapping: HashMap<BddVariable, BddVariable> = order
+            .iter()
+            .enumerate()
+            .map(|(i, var)| (*var, BddVariable(i as u16)))
+            .collect();
+        bdd.permute_variables(&mapping)
+    }
+
+    #[test]
+    fn optimal_order_is_optimal() {
+        let variables = mk_5_variable_set();
+        let all = variables.variables();
+        for expression in &[
+            "(v1 & v4) | (v2 & v5) | (v3 & !v1)",
+            "(v1 <=> v3) & (v2 <=> v5) & v4",
+            "v1 ^ v2 ^ v3 ^ v4 ^ v5",
+            "(v5 => v1) & (v2 | v3)",
+            "true",
+            "false",
+        ] {
+            let bdd = variables.eval_expression_string(expression);
+            let (order, reordered) = bdd.optimal_order();
+            assert_eq!(5, order.len());
+            assert_eq!(reorder(&bdd, &order), reordered);
+            let best = permutations(&all)
+                .iter()
+                .map(|order| reorder(&bdd, order).size())
+                .min()
+                .unwrap();
+            assert_eq!(best, reordered.size());
+        }
+    }
+
+    #[test]
+    fn optimal_order_equality() {
+        // Equality of two blocks is only small if the blocks are interleaved.
+        let variables = BddVariableSet::new_anonymous(8);
+        let v = variables.variables();
+        let bdd = variables.mk_block_equality(&v[..4], &v[4..]);
+        let (order, reordered) = bdd.optimal_order();
+        assert_eq!(3 * 4 + 2, reordered.size());
+        for pair in order.chunks(2) {
+            assert_eq!(4, (pair[0].0 as i32 - pair[1].0 as i32).abs());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn optimal_order_too_many_variables() {
+        let variables = BddVariableSet::new_anonymous(17);
+        let v = variables.variables();
+        let clause = v.iter().fold(variables.mk_false(), |result, var| {
+            result.or(&variables.mk_var(*var))
+        });
+        clause.optimal_order();
+    }
+}
@@ -0,0 +1,250 @@
+use crate::Bdd;
+use std::any::Any;
+use std::ops::Deref;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+use std::sync::{Barrier, Mutex};
+use std::thread;
+
+/// **(internal)** A value in the reduction tree of `par_fold_op`: either one of the borrowed
+/// operands, or a computed intermediate result.
+enum FoldValue<'a> {
+    Operand(&'a Bdd),
+    Result(Bdd),
+}
+
+/// **(internal)** Take the value out of a slot of the reduction tree.
+fn take<'a>(slot: &Mutex<Option<FoldValue<'a>>>) -> Option<FoldValue<'a>> {
+    slot.lock().unwrap().take()
+}
+
+impl Deref for FoldValue<'_> {
+    type Target = Bdd;
+
+    fn deref(&self) -> &Bdd {
+        match self {
+            FoldValue::Operand(bdd) => bdd,
+            FoldValue::Result(bdd) => bdd,
+        }
+    }
+}
+
+/// Operations that use multiple threads to process many `Bdd`s at once.
+impl Bdd {
+    /// Combine all `operands` using the binary operation `op`, using up to `threads` threads.
+    ///
+    /// The operands are combined using a fixed balanced reduction tree: in every round,
+    /// neighbouring pairs of intermediate results are combined (the last one is kept for the
+    /// next round if the number of results is odd), and the pairs of one round are distributed
+    /// among the threads. The shape of the tree only depends on the number of operands, so the
+    /// result is the same for every number of threads (even if `op` is not associative).
+    ///
+    /// The worker threads are started once and live for the whole fold (they wait for each
+    /// other after every round). The operands are borrowed, not copied.
+    ///
+    /// *Panics:* `operands` must not be empty. As with all binary operations, the operands must
+    /// have the same number of variables. If `op` panics, the panic is resumed on the calling
+    /// thread once all workers are finished.
+    pub fn par_fold_op<F>(operands: &[Bdd], op: F, threads: usize) -> Bdd
+    where
+        F: Fn(&Bdd, &Bdd) -> Bdd + Sync,
+    {
+        if operands.is_empty() {
+            panic!("Cannot fold an empty list of Bdds.");
+        }
+        for operand in operands {
+            operands[0].check_compatible(operand);
+        }
+        let workers = threads.max(1).min(operands.len() / 2).max(1);
+
+        // Every round of the reduction tree has its own slots. A slot of the first round is
+        // a borrowed operand, the other slots are filled by the workers. A slot is only ever
+        // accessed by one worker in a round, and the rounds are separated by a barrier.
+        let mut sizes = vec![operands.len()];
+        while sizes[sizes.len() - 1] > 1 {
+            // The pairs and the odd value which is kept for the next round.
+            let size = sizes[sizes.len() - 1];
+            sizes.push(size / 2 + size % 2);
+        }
+        let rounds: Vec<Vec<Mutex<Option<FoldValue>>>> = sizes
+            .iter()
+            .enumerate()
+            .map(|(round, size)| {
+                (0..*size)
+                    .map(|i| {
+                        let value = if round == 0 {
+                            Some(FoldValue::Operand(&operands[i]))
+                        } else {
+                            None
+                        };
+                        Mutex::new(value)
+                    })
+                    .collect()
+            })
+            .collect();
+        let barrier = Barrier::new(workers);
+        let failure: Mutex<Option<Box<dyn Any + Send>>> = Mutex::new(None);
+
+        let work = |worker: usize| {
+            for round in 0..(rounds.len() - 1) {
+                let (current, next) = (&rounds[round], &rounds[round + 1]);
+                // Each worker computes a contiguous block of pairs.
+                let pairs = current.len() / 2;
+                let block = (pairs - 1) / workers + 1;
+                for pair in (worker * block)..pairs.min((worker + 1) * block) {
+                    let left = take(&current[2 * pair]);
+                    let right = take(&current[2 * pair + 1]);
+                    // After a failure, some values are missing and the rest is skipped.
+                    if let (Some(left), Some(right)) = (left, right) {
+                        let result = catch_unwind(AssertUnwindSafe(|| op(&left, &right)));
+                        match result {
+                            Ok(result) => {
+                                *next[pair].lock().unwrap() = Some(FoldValue::Result(result))
+                            }
+                            Err(payload) => {
+                                failure.lock().unwrap().get_or_insert(payload);
+                            }
+                        }
+                    }
+                }
+                if worker == 0 && current.len() % 2 == 1 {
+                    *next[pairs].lock().unwrap() = take(&current[current.len() - 1]);
+                }
+                barrier.wait();
+            }
+        };
+        thread::scope(|scope| {
+            for worker in 1..workers {
+                let work = &work;
+                scope.spawn(move || work(worker));
+            }
+            work(0);
+        });
+
+        if let Some(payload) = failure.into_inner().unwrap() {
+            resume_unwind(payload);
+        }
+        match take(&rounds[rounds.len() - 1][0]) {
+            Some(FoldValue::Operand(bdd)) => bdd.clone(),
+            Some(FoldValue::Result(bdd)) => bdd,
+            None => unreachable!("The reduction has no result."),
+        }
+    }
+
+    /// Same as `Bdd::pairwise_and`, but the batch is split into contiguous blocks which are
+    /// processed by up to `threads` scoped threads (repeated pairs are only shared within one
+    /// block). The operands are borrowed, not copied.
+    ///
+    /// *Panics:* `left` and `right` must have the same length. As with all binary operations,
+    /// the operands must have the same number of variables.
+    pub fn par_pairwise_and(left: &[Bdd], right: &[Bdd], threads: usize) -> Vec<Bdd> {
+        if left.len() != right.len() {
+            panic!("Batch length mismatch: {} != {}.", left.len(), right.len());
+        }
+        if left.is_empty() {
+            return Vec::new();
+        }
+        // Check the operands here, so that the error is not hidden in a worker thread.
+        for (l, r) in left.iter().zip(right.iter()) {
+            left[0].check_compatible(l);
+            left[0].check_compatible(r);
+        }
+        let block = (left.len() - 1) / threads.max(1) + 1;
+        thread::scope(|scope| {
+            let handles: Vec<_> = left
+                .chunks(block)
+                .zip(right.chunks(block))
+                .map(|(left, right)| scope.spawn(move || Bdd::pairwise_and(left, right)))
+                .collect();
+            let mut results: Vec<Bdd> = Vec::with_capacity(left.len());
+            for handle in handles {
+                results.append(&mut handle.join().expect("Worker thread panicked."));
+            }
+            results
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::Bdd;
+
+    #[test]
+    fn par_fold_op_is_deterministic() {
+        let variables = mk_5_variable_set();
+        let operands: Vec<Bdd> = [
+            "v1 | v2",
+            "!v3 | v4",
+            "v2 ^ v5",
+            "v1 => v4",
+            "!v2 | !v5",
+            "v3 <=> v1",
+            "v4 | v5",
+        ]
+        .iter()
+        .map(|e| variables.eval_expression_string(e))
+        .collect();
+        let expected = operands
+            .iter()
+            .skip(1)
+            .fold(operands[0].clone(), |acc, b| acc.and(b));
+        for threads in 0..9 {
+            assert_eq!(expected, Bdd::par_fold_op(&operands, Bdd::and, threads));
+        }
+        // A non-associative operation is evaluated using the same tree for every thread count.
+        let sequential = Bdd::par_fold_op(&operands, Bdd::imp, 1);
+        for threads in 2..9 {
+            assert_eq!(sequential, Bdd::par_fold_op(&operands, Bdd::imp, threads));
+        }
+        assert_eq!(operands[3], Bdd::par_fold_op(&operands[3..4], Bdd::or, 4));
+    }
+
+    #[test]
+    fn par_pairwise_and_matches_sequential() {
+        let variables = mk_5_variable_set();
+        let left: Vec<Bdd> = [
+            "v1 | v2", "v3", "true", "v4 => v5", "v1 | v2", "v2 ^ v3", "v5",
+        ]
+        .iter()
+        .map(|e| variables.eval_expression_string(e))
+        .collect();
+        let right: Vec<Bdd> = ["!v2", "v1 | v4", "v1 ^ v5", "v4", "!v2", "v3", "false"]
+            .iter()
+            .map(|e| variables.eval_expression_string(e))
+            .collect();
+        let expected = Bdd::pairwise_and(&left, &right);
+        for threads in 0..9 {
+            assert_eq!(expected, Bdd::par_pairwise_and(&left, &right, threads));
+        }
+        assert!(Bdd::par_pairwise_and(&[], &[], 4).is_empty());
+    }
+
+    #[test]
+    fn par_fold_op_borrowed_op() {
+        // The operation can borrow local data, since the workers are scoped.
+        let variables = mk_5_variable_set();
+        let care = variables.eval_expression_string("v1 | v5");
+        let operands: Vec<Bdd> = ["v1", "v2", "!v3", "v4", "v5"]
+            .iter()
+            .map(|e| variables.eval_expression_string(e))
+            .collect();
+        let op = |a: &Bdd, b: &Bdd| a.or(b).and(&care);
+        let expected = Bdd::par_fold_op(&operands, op, 1);
+        assert_eq!(expected, Bdd::par_fold_op(&operands, op, 3));
+        assert!(expected.imp(&care).is_true());
+    }
+
+    #[test]
+    #[should_panic(expected = "Operation failed.")]
+    fn par_fold_op_panic_in_worker() {
+        let variables = mk_5_variable_set();
+        let operands = vec![variables.mk_true(); 9];
+        Bdd::par_fold_op(&operands, |_, _| panic!("Operation failed."), 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn par_fold_op_empty() {
+        Bdd::par_fold_op(&[], Bdd::and, 2);
+    }
+}
@@ -0,0 +1,318 @@
+use super::_impl_node_store::NodeStore;
+use crate::{Bdd, BddPartialValuation, BddPointer, BddVariable, ClusteringConfig};
+use fxhash::FxBuildHasher;
+use std::collections::{HashMap, HashSet};
+
+/// Quantification of multiple variables in a single pass.
+impl Bdd {
+    /// Existentially quantify all given `variables`: $\exists x_1, \ldots, x_k : B$.
+    ///
+    /// The result is the same as for `project`, but instead of eliminating the variables one
+    /// by one (creating a full intermediate `Bdd` for each variable), the source `Bdd` is
+    /// traversed only once: every node of a quantified variable is replaced with the
+    /// disjunction of its (already quantified) children. The disjunctions share one operation
+    /// cache during the whole traversal, so each pair of sub-results is only combined once.
+    ///
+    /// *Panics:* All variables must be valid in this `Bdd`.
+    pub fn exists(&self, variables: &[BddVariable]) -> Bdd {
+        quantify(self, variables, crate::op_function::or)
+    }
+
+    /// Existentially quantify all variables fixed by the given `cube` (the values of the
+    /// literals are ignored, only their variables matter). This is the same as `exists` with
+    /// the variables of the cube, so a quantified set kept as a `BddPartialValuation` (e.g.
+    /// the one used with `and_cube`) never has to be converted to a `Bdd`.
+    ///
+    /// *Panics:* All variables of the cube must be valid in this `Bdd`.
+    pub fn exists_cube(&self, cube: &BddPartialValuation) -> Bdd {
+        let variables: Vec<BddVariable> = cube.to_values().into_iter().map(|(v, _)| v).collect();
+        self.exists(&variables)
+    }
+
+    /// Universally quantify all given `variables`: $\forall x_1, \ldots, x_k : B$.
+    ///
+    /// This is the dual of `exists`, computed in the same single pass (quantified nodes are
+    /// replaced with the conjunction of their children), without negating the `Bdd` twice.
+    ///
+    /// *Panics:* All variables must be valid in this `Bdd`.
+    pub fn for_all(&self, variables: &[BddVariable]) -> Bdd {
+        quantify(self, variables, crate::op_function::and)
+    }
+
+    /// Existentially quantify all given `variables`, eliminating them in clusters with
+    /// an adaptive schedule. The result is the same as for `exists`.
+    ///
+    /// A cluster is a run of quantified variables which are adjacent in the variable ordering
+    /// when only the variables used by the `Bdd` are considered, since such variables can be
+    /// eliminated together without rebuilding the levels between them. The clusters are only
+    /// based on this ordering, not on the overlap of supports: within a single `Bdd`, the
+    /// sub-graph of every quantified variable contains the variables below it, so their
+    /// supports would put almost all variables into one cluster. The clusters are
+    /// processed bottom-up, and each cluster is eliminated in steps of at most
+    /// `cluster_size` variables using `exists`. After every step, the size of the intermediate
+    /// result is measured: if it grew more than `growth_limit` times, the following steps
+    /// eliminate half as many variables (down to one), and if it did not grow at all, twice
+    /// as many (up to `cluster_size`).
+    ///
+    /// *Panics:* All variables must be valid in this `Bdd`.
+    pub fn exists_clustered(&self, variables: &[BddVariable], config: &ClusteringConfig) -> Bdd {
+        let num_vars = self.num_vars();
+        if let Some(var) = variables.iter().find(|v| v.0 >= num_vars) {
+            panic!(
+                "Variable {} is not valid in a Bdd with {} variables.",
+                var, num_vars
+            );
+        }
+        let mut step = config.cluster_size;
+        let mut result = self.clone();
+        for cluster in adjacent_runs(self, variables).into_iter().rev() {
+            let mut remaining: &[BddVariable] = &cluster;
+            while !remaining.is_empty() {
+                // Eliminate the bottom-most variables of the cluster first.
+                let split = remaining.len() - step.min(remaining.len());
+                let (rest, eliminated) = remaining.split_at(split);
+                let next = result.exists(eliminated);
+                if next.size() as f64 > result.size() as f64 * config.growth_limit {
+                    step = (step / 2).max(1);
+                } else if next.size() <= result.size() {
+                    step = (step * 2).min(config.cluster_size);
+                }
+                result = next;
+                remaining = rest;
+            }
+        }
+        result
+    }
+}
+
+impl ClusteringConfig {
+    /// Create a configuration which eliminates at most `cluster_size` variables at once and
+    /// reduces the number of variables eliminated at once when an intermediate result grows
+    /// more than `growth_limit` times.
+    ///
+    /// *Panics:* `cluster_size` must be positive and `growth_limit` must be at least `1.0`.
+    pub fn new(cluster_size: usize, growth_limit: f64) -> ClusteringConfig {
+        if cluster_size == 0 {
+            panic!("Cluster size must be positive.");
+        }
+        if growth_limit.is_nan() || growth_limit < 1.0 {
+            panic!(
+                "Growth limit must be at least 1.0, but is {}.",
+                growth_limit
+            );
+        }
+        ClusteringConfig {
+            cluster_size,
+            growth_limit,
+        }
+    }
+}
+
+impl Default for ClusteringConfig {
+    /// Clusters of at most 8 variables and a growth limit of `1.5`.
+    fn default() -> Self {
+        ClusteringConfig::new(8, 1.5)
+    }
+}
+
+/// **(internal)** Split the quantified `variables` used in `bdd` into runs which are adjacent
+/// in the ordering of the variables used in `bdd` (sorted, top-most run first).
+fn adjacent_runs(bdd: &Bdd, variables: &[BddVariable]) -> Vec<Vec<BddVariable>> {
+    let quantified: HashSet<BddVariable> = variables.iter().cloned().collect();
+    let mut runs: Vec<Vec<BddVariable>> = Vec::new();
+    let mut current: Vec<BddVariable> = Vec::new();
+    for var in bdd.support_set() {
+        if quantified.contains(&var) {
+            current.push(var);
+        } else if !current.is_empty() {
+            runs.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        runs.push(current);
+    }
+    runs
+}
+
+/// **(internal)** Working state of a single quantification pass.
+struct Quantifier<'a, T>
+where
+    T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+{
+    source: &'a Bdd,
+    /// `quantified[v]` is true if the variable `v` should be eliminated.
+    quantified: Vec<bool>,
+    /// The operator which combines the two cofactors of a quantified node.
+    op: T,
+    store: NodeStore,
+    /// Result of quantifying each source node (if already known).
+    finished: Vec<Option<BddPointer>>,
+    /// Results of `op` applied to pairs of result nodes.
+    op_cache: HashMap<(BddPointer, BddPointer), BddPointer, FxBuildHasher>,
+    /// Work stacks of `combine` (empty between calls).
+    combine_stack: Vec<CombineTask>,
+    combine_results: Vec<BddPointer>,
+}
+
+/// **(internal)** Eliminate the given `variables` from `source`, combining the two cofactors
+/// of every eliminated variable using `op`, which must be commutative and idempotent
+/// (i.e. `or` for existential and `and` for universal quantification).
+fn quantify<T>(source: &Bdd, variables: &[BddVariable], op: T) -> Bdd
+where
+    T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+{
+    let num_vars = source.num_vars();
+    let mut quantified = vec![false; usize::from(num_vars)];
+    for var in variables {
+        if var.0 >= num_vars {
+            panic!(
+                "Variable {} is not valid in a Bdd with {} variables.",
+                var, num_vars
+            );
+        }
+        quantified[usize::from(var.0)] = true;
+    }
+    if source.is_true() || source.is_false() {
+        return source.clone();
+    }
+
+    let mut finished = vec![None; source.size()];
+    finished[0] = Some(BddPointer::zero());
+    finished[1] = Some(BddPointer::one());
+    let mut quantifier = Quantifier {
+        source,
+        quantified,
+        op,
+        store: NodeStore::new(num_vars, source.size()),
+        finished,
+        op_cache: HashMap::default(),
+        combine_stack: Vec::new(),
+        combine_results: Vec::new(),
+    };
+    let root = quantifier.quantify(source.root_pointer());
+    quantifier.store.into_bdd(root)
+}
+
+impl<'a, T> Quantifier<'a, T>
+where
+    T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+{
+    /// Compute the result for the given source node.
+    ///
+    /// The source nodes are explored using an explicit stack (a node stays on the stack until
+    /// the results of its children are known), so the depth of the `Bdd` is not limited by
+    /// the size of the call stack.
+    fn quantify(&mut self, root: BddPointer) -> BddPointer {
+        let mut stack: Vec<BddPointer> = vec![root];
+        while let Some(node) = stack.last().cloned() {
+            if self.finished[node.to_index()].is_some() {
+                stack.pop();
+                continue;
+            }
+            let var = self.source.var_of(node);
+            let is_quantified = self.quantified[usize::from(var.0)];
+            let low_link = self.source.low_link_of(node);
+            let low = match self.finished[low_link.to_index()] {
+                Some(low) => low,
+                None => {
+                    stack.push(low_link);
+                    continue;
+                }
+            };
+            // If the low cofactor decides the result, the high cofactor is not needed.
+            let decided = (self.op)(low.as_bool(), None).filter(|_| is_quantified);
+            let result = if let Some(value) = decided {
+                BddPointer::from_bool(value)
+            } else {
+                let high_link = self.source.high_link_of(node);
+                let high = match self.finished[high_link.to_index()] {
+                    Some(high) => high,
+                    None => {
+                        stack.push(high_link);
+                        continue;
+                    }
+                };
+                if is_quantified {
+                    self.combine(low, high)
+                } else {
+                    self.store.mk_node(var, low, high)
+                }
+            };
+            self.finished[node.to_index()] = Some(result);
+            stack.pop();
+        }
+        self.finished[root.to_index()].unwrap()
+    }
+
+    /// Apply the operator to two result nodes.
+    ///
+    /// Same as in `quantify`, the pairs of nodes are explored using an explicit stack. A pair
+    /// is first expanded into its two cofactor pairs, and once both are finished, their
+    /// results are taken from the `results` stack and joined into a node.
+    fn combine(&mut self, left: BddPointer, right: BddPointer) -> BddPointer {
+        if let Some(result) = self.combined(left, right) {
+            return result;
+        }
+        // The stacks are kept between calls, so that they are only allocated once.
+        let mut stack = std::mem::take(&mut self.combine_stack);
+        let mut results = std::mem::take(&mut self.combine_results);
+        stack.push(CombineTask::Expand(left, right));
+        while let Some(task) = stack.pop() {
+            match task {
+                CombineTask::Expand(left, right) => {
+                    if let Some(result) = self.combined(left, right) {
+                        results.push(result);
+                        continue;
+                    }
+                    let var = self.store.var_of(left).min(self.store.var_of(right));
+                    let (l_low, l_high) = self.store.cofactors(left, var);
+                    let (r_low, r_high) = self.store.cofactors(right, var);
+                    // The low pair is on top, so it is finished first.
+                    stack.push(CombineTask::Join(var, combine_key(left, right)));
+                    stack.push(CombineTask::Expand(l_high, r_high));
+                    stack.push(CombineTask::Expand(l_low, r_low));
+                }
+                CombineTask::Join(var, key) => {
+                    let high = results.pop().unwrap();
+                    let low = results.pop().unwrap();
+                    let result = self.store.mk_node(var, low, high);
+                    self.op_cache.insert(key, result);
+                    results.push(result);
+                }
+            }
+        }
+        let result = results.pop().unwrap();
+        self.combine_stack = stack;
+        self.combine_results = results;
+        result
+    }
+
+    /// The result of the operator for two result nodes, if it is trivial (a constant or
+    /// the same node on both sides) or already in the cache.
+    fn combined(&self, left: BddPointer, right: BddPointer) -> Option<BddPointer> {
+        if let Some(value) = (self.op)(left.as_bool(), right.as_bool()) {
+            Some(BddPointer::from_bool(value))
+        } else if left == right {
+            Some(left)
+        } else {
+            self.op_cache.get(&combine_key(left, right)).cloned()
+        }
+    }
+}
+
+/// **(internal)** A step of `Quantifier::combine`: either compute the result for a pair of
+/// nodes, or join the two (already computed) cofactor results of a pair into a node.
+enum CombineTask {
+    Expand(BddPointer, BddPointer),
+    Join(BddVariable, (BddPointer, BddPointer)),
+}
+
+/// **(internal)** The operator of a `Quantifier` is commutative, so each pair of nodes
+/// is stored in the cache in a fixed order.
+fn combine_key(left: BddPointer, right: BddPointer) -> (BddPointer, BddPointer) {
+    if left < right {
+        (left, right)
+    } else {
+        (right, left)
+    }
+}
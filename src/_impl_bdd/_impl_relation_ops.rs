@@ -1,4 +1,4 @@
-use crate::{Bdd, BddNode, BddPointer, BddVariable};
+use crate::{Bdd, BddNode, BddPartialValuation, BddPointer, BddVariable, QuantificationOrder};
 
 /// Advanced relation-like operations for `Bdd`s.
 impl Bdd {
@@ -29,6 +29,94 @@ impl Bdd {
             .fold(self.clone(), |result, v| result.var_project(v))
     }
 
+    /// Same as `project`, but the order in which the variables are eliminated is determined
+    /// by the given `QuantificationOrder`. The result does not depend on the order, only the
+    /// size of the intermediate results does.
+    ///
+    /// With `QuantificationOrder::FewestNodes`, the next eliminated variable is always
+    /// the one with the fewest decision nodes in the current intermediate result (ties are
+    /// broken in favour of the last variable). Variables that do not appear in the result
+    /// anymore are removed without any work.
+    pub fn project_ordered(&self, variables: &[BddVariable], order: QuantificationOrder) -> Bdd {
+        match order {
+            QuantificationOrder::BottomUp => self.project(variables),
+            QuantificationOrder::AsGiven => variables
+                .iter()
+                .fold(self.clone(), |result, v| result.var_project(*v)),
+            QuantificationOrder::FewestNodes => {
+                let mut remaining = sorted(variables);
+                remaining.dedup();
+                let mut result = self.clone();
+                while !remaining.is_empty() {
+                    let mut level_size = vec![0usize; usize::from(result.num_vars())];
+                    for node in result.nodes().skip(2) {
+                        level_size[usize::from(node.var.0)] += 1;
+                    }
+                    remaining.retain(|v| level_size[usize::from(v.0)] > 0);
+                    let best = remaining
+                        .iter()
+                        .enumerate()
+                        .rev()
+                        .min_by_key(|(_, v)| level_size[usize::from(v.0)])
+                        .map(|(i, _)| i);
+                    if let Some(best) = best {
+                        result = result.var_project(remaining.remove(best));
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    /// Detect variables which are equivalent (or anti-equivalent) in every satisfying valuation
+    /// of this `Bdd` and eliminate them.
+    ///
+    /// Every class of such variables is represented by its smallest variable. The result
+    /// contains the `Bdd` where all other variables of the class are projected away, together
+    /// with the list of eliminated variables: a triple `(var, representative, same)` says that
+    /// `var` is equal to `representative` if `same` is true, and to its negation otherwise.
+    /// The original `Bdd` is the conjunction of the result with these equivalences.
+    ///
+    /// If the `Bdd` is `false`, nothing is merged.
+    pub fn merge_equivalent_variables(&self) -> (Bdd, Vec<(BddVariable, BddVariable, bool)>) {
+        let witness = match self.sat_witness() {
+            Some(witness) => witness,
+            None => return (self.clone(), Vec::new()),
+        };
+        // Only variables which appear in the `Bdd` can be fixed by another variable.
+        let mut support: Vec<BddVariable> = self.nodes().skip(2).map(|node| node.var).collect();
+        support.sort();
+        support.dedup();
+
+        // Two values of `var` and `other` are compatible if the `Bdd` has a satisfying
+        // valuation with these values.
+        let compatible = |var: BddVariable, var_value: bool, other: BddVariable, value: bool| {
+            let cube = BddPartialValuation::from_values(&[(var, var_value), (other, value)]);
+            !self.and_cube(&cube).is_false()
+        };
+        let mut representatives: Vec<BddVariable> = Vec::new();
+        let mut merged: Vec<(BddVariable, BddVariable, bool)> = Vec::new();
+        for var in support {
+            // The witness determines the only possible polarity of the equivalence.
+            let found = representatives.iter().find_map(|r| {
+                let same = witness[*r] == witness[var];
+                let equivalent =
+                    !compatible(*r, true, var, !same) && !compatible(*r, false, var, same);
+                if equivalent {
+                    Some((*r, same))
+                } else {
+                    None
+                }
+            });
+            match found {
+                Some((r, same)) => merged.push((var, r, same)),
+                None => representatives.push(var),
+            }
+        }
+        let eliminated: Vec<BddVariable> = merged.iter().map(|(var, _, _)| *var).collect();
+        (self.project(&eliminated), merged)
+    }
+
     /// Picks one valuation for the given `BddVariable`.
     ///
     /// Essentially, what this means is that
@@ -0,0 +1,342 @@
+use crate::{Bdd, BddPartialValuation, BddValuation, BddVariable};
+#[cfg(feature = "sampling")]
+use rand::seq::SliceRandom;
+#[cfg(feature = "sampling")]
+use rand::Rng;
+
+/// **(internal)** Maximal number of valuations in a cell that is sampled explicitly
+/// by `Bdd::approx_sample`.
+#[cfg(feature = "sampling")]
+const SAMPLING_CELL_LIMIT: usize = 64;
+
+/// **(internal)** Number of times `Bdd::approx_sample` tries to find a non-empty cell
+/// before giving up on one sample.
+#[cfg(feature = "sampling")]
+const SAMPLING_ATTEMPTS: usize = 32;
+
+/// Methods for selecting representative sets of satisfying valuations of a `Bdd`.
+impl Bdd {
+    /// Compute a set of at most `limit` satisfying valuations which covers all valid t-tuples of
+    /// literals. That is, for every $t$ variables and every assignment of values to them which
+    /// can be extended to a satisfying valuation, one of the returned valuations extends it
+    /// (if `limit` is large enough). This is also known as a covering array.
+    ///
+    /// The valuations are constructed greedily: each new valuation starts from an uncovered
+    /// tuple and the remaining variables are assigned one by one, always picking the value
+    /// which covers more new tuples (ties are broken by picking the value with more satisfying
+    /// valuations). The result is not guaranteed to be minimal, but it is typically small.
+    ///
+    /// Be aware that the number of tuples grows as $\binom{n}{t} \cdot 2^t$, so this is only
+    /// practical for small `t`.
+    pub fn sample_t_wise(&self, t: usize, limit: usize) -> Vec<BddValuation> {
+        let num_vars = usize::from(self.num_vars());
+        if self.is_false() || limit == 0 || t > num_vars {
+            return Vec::new();
+        }
+
+        let mut uncovered = valid_tuples(self, t);
+        let mut samples: Vec<BddValuation> = Vec::new();
+        if uncovered.is_empty() {
+            // Only possible when `t == 0`: any valuation covers the empty tuple.
+            samples.extend(self.sat_witness());
+            return samples;
+        }
+        while !uncovered.is_empty() && samples.len() < limit {
+            let mut partial = BddPartialValuation::from_values(&uncovered[0]);
+            let mut remaining = self.restrict(&partial);
+            for i in 0..num_vars {
+                let var = BddVariable(i as u16);
+                if partial.has_value(var) {
+                    continue;
+                }
+                let options: Vec<(usize, f64, bool, Bdd)> = [true, false]
+                    .iter()
+                    .map(|value| {
+                        let restricted = remaining.var_restrict(var, *value);
+                        let gain = uncovered
+                            .iter()
+                            .filter(|tuple| {
+                                tuple.contains(&(var, *value))
+                                    && tuple.iter().all(|(v, b)| {
+                                        *v == var || partial.get_value(*v) == Some(*b)
+                                    })
+                            })
+                            .count();
+                        (gain, restricted.cardinality(), *value, restricted)
+                    })
+                    .filter(|(_, _, _, restricted)| !restricted.is_false())
+                    .collect();
+                // At least one option is valid, because `remaining` is satisfiable.
+                let best = options
+                    .into_iter()
+                    .max_by(|a, b| (a.0, a.1).partial_cmp(&(b.0, b.1)).unwrap())
+                    .unwrap();
+                partial.set_value(var, best.2);
+                remaining = best.3;
+            }
+            let sample = BddValuation::new(
+                (0..num_vars)
+                    .map(|i| partial.get_value(BddVariable(i as u16)) == Some(true))
+                    .collect(),
+            );
+            uncovered.retain(|tuple| tuple.iter().any(|(v, b)| sample[*v] != *b));
+            samples.push(sample);
+        }
+        samples
+    }
+
+    /// Pick `k` random satisfying valuations of this `Bdd` using XOR streamlining.
+    ///
+    /// For each sample, random parity (XOR) constraints over random subsets of variables are
+    /// added to the `Bdd` until the number of remaining satisfying valuations (the *cell*)
+    /// drops below a small threshold. The valuations of the cell are then enumerated and one
+    /// of them is picked uniformly at random. If the `Bdd` itself has only few satisfying
+    /// valuations, no parity constraints are added and the sampling is exactly uniform.
+    ///
+    /// Otherwise, the distribution is **not** uniform and its bias is not bounded: the
+    /// constraints are added one by one until the cell is small enough, so valuations which
+    /// tend to end up in smaller cells are picked more often. There is no stopping rule which
+    /// would bound the bias (as in the UniGen samplers), so treat the result as a cheap
+    /// heuristic for picking diverse valuations. For exactly uniform samples, use
+    /// `FrozenBdd::random_valuation` (see `Bdd::freeze`).
+    ///
+    /// The samples are independent and can repeat. If a cell ends up empty too many times in
+    /// a row (which is unlikely), fewer than `k` samples are returned. An unsatisfiable `Bdd`
+    /// gives no samples.
+    ///
+    /// Requires the `sampling` feature.
+    #[cfg(feature = "sampling")]
+    pub fn approx_sample<R: Rng>(&self, k: usize, rng: &mut R) -> Vec<BddValuation> {
+        let mut samples = Vec::with_capacity(k);
+        if self.is_false() {
+            return samples;
+        }
+        let cell_valuations = |cell: &Bdd| -> Vec<BddValuation> {
+            cell.sat_valuations()
+                .take(SAMPLING_CELL_LIMIT + 1)
+                .collect()
+        };
+        let initial = cell_valuations(self);
+        for _ in 0..k {
+            for _ in 0..SAMPLING_ATTEMPTS {
+                let mut cell = self.clone();
+                let mut valuations = initial.clone();
+                while valuations.len() > SAMPLING_CELL_LIMIT {
+                    cell = cell.and(&random_xor(self.num_vars(), rng));
+                    valuations = cell_valuations(&cell);
+                }
+                if let Some(sample) = valuations.choose(rng) {
+                    samples.push(sample.clone());
+                    break;
+                }
+            }
+        }
+        samples
+    }
+
+    /// Estimate the number of satisfying valuations of this `Bdd` using hashing with random
+    /// parity constraints (the ApproxMC algorithm by Chakraborty, Meel and Vardi).
+    ///
+    /// With probability at least $1 - \delta$, the result is within the factor $(1 + \epsilon)$
+    /// of the exact cardinality. Only cells with a small number of valuations (polynomial in
+    /// $1 / \epsilon$) are ever enumerated, and the number of repetitions is logarithmic in
+    /// $1 / \delta$.
+    ///
+    /// Requires the `sampling` feature.
+    ///
+    /// *Panics:* `epsilon` must be positive and `delta` must be in the open interval $(0, 1)$.
+    #[cfg(feature = "sampling")]
+    pub fn approx_cardinality_hashing<R: Rng>(&self, epsilon: f64, delta: f64, rng: &mut R) -> f64 {
+        if epsilon <= 0.0 || delta <= 0.0 || delta >= 1.0 {
+            panic!(
+                "Invalid approximation parameters: epsilon={}, delta={}.",
+                epsilon, delta
+            );
+        }
+        let threshold = (1.0
+            + 9.84 * (1.0 + epsilon / (1.0 + epsilon)) * (1.0 + 1.0 / epsilon).powi(2))
+        .ceil() as usize;
+        let repetitions = (17.0 * (3.0 / delta).log2()).ceil() as usize;
+        let count_up_to = |cell: &Bdd| cell.sat_valuations().take(threshold).count();
+
+        let initial = count_up_to(self);
+        if initial < threshold {
+            // The function is small enough to be counted exactly.
+            return initial as f64;
+        }
+        let mut estimates: Vec<f64> = Vec::with_capacity(repetitions);
+        for _ in 0..repetitions {
+            let mut cell = self.clone();
+            for constraints in 1..=self.num_vars() {
+                cell = cell.and(&random_xor(self.num_vars(), rng));
+                let count = count_up_to(&cell);
+                if count < threshold {
+                    // Empty cells are inconclusive and are not used as estimates.
+                    if count > 0 {
+                        estimates.push(count as f64 * 2.0_f64.powi(i32::from(constraints)));
+                    }
+                    break;
+                }
+            }
+        }
+        if estimates.is_empty() {
+            return 0.0;
+        }
+        estimates.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        estimates[estimates.len() / 2]
+    }
+
+    /// **(internal)** Restrict a single variable (see `Bdd::restrict`).
+    fn var_restrict(&self, variable: BddVariable, value: bool) -> Bdd {
+        self.restrict(&BddPartialValuation::from_values(&[(variable, value)]))
+    }
+}
+
+/// **(internal)** Create a random parity constraint: each variable is included with
+/// probability one half and the required parity is also random.
+#[cfg(feature = "sampling")]
+fn random_xor<R: Rng>(num_vars: u16, rng: &mut R) -> Bdd {
+    let parity = if rng.gen::<bool>() {
+        Bdd::mk_true(num_vars)
+    } else {
+        Bdd::mk_false(num_vars)
+    };
+    (0..num_vars)
+        .filter(|_| rng.gen::<bool>())
+        .fold(parity, |acc, var| {
+            acc.xor(&Bdd::mk_var(num_vars, BddVariable(var)))
+        })
+}
+
+/// **(internal)** All t-tuples of literals (sorted by variable) that are consistent with
+/// at least one satisfying valuation of the `Bdd`.
+fn valid_tuples(bdd: &Bdd, t: usize) -> Vec<Vec<(BddVariable, bool)>> {
+    let num_vars = usize::from(bdd.num_vars());
+    let mut tuples = Vec::new();
+    if t == 0 {
+        return tuples;
+    }
+    // Iterate over all increasing sequences of `t` variable indices.
+    let mut indices: Vec<usize> = (0..t).collect();
+    loop {
+        for values in 0..(1usize << t) {
+            let tuple: Vec<(BddVariable, bool)> = indices
+                .iter()
+                .enumerate()
+                .map(|(k, i)| (BddVariable(*i as u16), values & (1 << k) != 0))
+                .collect();
+            if !bdd
+                .and_cube(&BddPartialValuation::from_values(&tuple))
+                .is_false()
+            {
+                tuples.push(tuple);
+            }
+        }
+        // Advance to the next combination, if there is one.
+        let mut k = t;
+        while k > 0 && indices[k - 1] == num_vars - t + (k - 1) {
+            k -= 1;
+        }
+        if k == 0 {
+            return tuples;
+        }
+        indices[k - 1] += 1;
+        for j in k..t {
+            indices[j] = indices[j - 1] + 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{BddPartialValuation, BddVariable};
+    #[cfg(feature = "sampling")]
+    use crate::{BddValuation, BddVariableSet};
+    #[cfg(feature = "sampling")]
+    use rand::rngs::StdRng;
+    #[cfg(feature = "sampling")]
+    use rand::SeedableRng;
+    #[cfg(feature = "sampling")]
+    use std::collections::HashSet;
+
+    #[test]
+    fn sample_t_wise_covers_all_tuples() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("(v1 => v2) & (v3 | v4) & !(v4 & v5)");
+        for t in 1..4 {
+            let samples = bdd.sample_t_wise(t, usize::MAX);
+            assert!(!samples.is_empty());
+            assert!(samples.iter().all(|s| bdd.eval_in(s)));
+            // Every valid pair of literals must be covered.
+            for a in 0..5u16 {
+                for b in (a + 1)..5u16 {
+                    for &(x, y) in
+                        [(false, false), (false, true), (true, false), (true, true)].iter()
+                    {
+                        let tuple = [(BddVariable(a), x), (BddVariable(b), y)];
+                        let valid = !bdd
+                            .and_cube(&BddPartialValuation::from_values(&tuple))
+                            .is_false();
+                        let covered = samples
+                            .iter()
+                            .any(|s| s[tuple[0].0] == x && s[tuple[1].0] == y);
+                        assert!(valid || !covered);
+                        if t >= 2 {
+                            assert_eq!(valid, covered);
+                        }
+                    }
+                }
+            }
+        }
+        // Pairwise coverage of 5 free variables needs far fewer than 32 valuations.
+        assert!(variables.mk_true().sample_t_wise(2, usize::MAX).len() <= 10);
+        assert_eq!(1, bdd.sample_t_wise(2, 1).len());
+        assert_eq!(1, bdd.sample_t_wise(0, 10).len());
+        assert!(variables.mk_false().sample_t_wise(2, 10).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "sampling")]
+    fn approx_sample() {
+        let mut rng = StdRng::seed_from_u64(1234);
+        let variables = BddVariableSet::new_anonymous(16);
+        let bdd = variables.eval_expression_string("(x_0 | x_3) & (x_5 ^ x_9) & !(x_12 & x_15)");
+        let samples = bdd.approx_sample(50, &mut rng);
+        assert_eq!(50, samples.len());
+        assert!(samples.iter().all(|s| bdd.eval_in(s)));
+        let distinct: HashSet<BddValuation> = samples.into_iter().collect();
+        assert!(distinct.len() > 40);
+
+        // Small functions are sampled exactly.
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("v1 & (v2 | v3)");
+        let samples = bdd.approx_sample(300, &mut rng);
+        let distinct: HashSet<BddValuation> = samples.iter().cloned().collect();
+        assert_eq!(12, distinct.len());
+        assert!(samples.iter().all(|s| bdd.eval_in(s)));
+        assert!(variables.mk_false().approx_sample(10, &mut rng).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "sampling")]
+    fn approx_cardinality_hashing() {
+        let mut rng = StdRng::seed_from_u64(4321);
+        let variables = BddVariableSet::new_anonymous(20);
+        let bdd = variables
+            .eval_expression_string("(x_0 | x_3 | x_7) & (x_5 ^ x_9) & !(x_12 & x_15 & x_19)");
+        let exact = bdd.cardinality();
+        let estimate = bdd.approx_cardinality_hashing(1.0, 0.5, &mut rng);
+        assert!(estimate <= exact * 2.0 && estimate >= exact / 2.0);
+
+        // Small functions are counted exactly.
+        let bdd = variables.eval_expression_string("x_0 & x_1 & x_2 & x_3 & x_4 & x_5 & !x_6 & x_7 & x_8 & x_9 & x_10 & x_11 & x_12 & x_13 & x_14 & x_15 & (x_16 | x_17)");
+        assert_eq!(12.0, bdd.approx_cardinality_hashing(0.8, 0.2, &mut rng));
+        assert_eq!(
+            0.0,
+            variables
+                .mk_false()
+                .approx_cardinality_hashing(0.5, 0.5, &mut rng)
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use super::_impl_node_store::NodeStore;
+use crate::{Bdd, BddPointer, BddVariable};
+use fxhash::FxBuildHasher;
+use std::collections::HashMap;
+
+/// Logical operations with three operands.
+impl Bdd {
+    /// Create a `Bdd` corresponding to the if-then-else formula
+    /// $(\phi \land \psi) \lor (\neg \phi \land \chi)$, where $\phi$ is the `condition`, $\psi$
+    /// is `then` and $\chi$ is `otherwise`.
+    ///
+    /// All three operands are explored simultaneously (with one cache of triples), so unlike
+    /// the composition of binary operations, no intermediate results are created. Parts of
+    /// `then` (`otherwise`) that are only reachable where the condition is `false` (`true`)
+    /// are never explored.
+    ///
+    /// If one of the operands is constant, or two operands are equal, the result is
+    /// computed by a single binary operation (or directly), e.g. $ite(\phi, 1, 0) = \phi$,
+    /// $ite(\phi, \psi, \psi) = \psi$ or $ite(\phi, \psi, 0) = \phi \land \psi$. Similar
+    /// terminal cases (without the binary operations) are also used for the triples of nodes
+    /// during the traversal.
+    ///
+    /// *Panics:* All three `Bdd`s must have the same number of variables.
+    pub fn ite(condition: &Bdd, then: &Bdd, otherwise: &Bdd) -> Bdd {
+        condition.check_compatible(then);
+        condition.check_compatible(otherwise);
+        if condition.is_true() || then == otherwise {
+            return then.clone();
+        }
+        if condition.is_false() {
+            return otherwise.clone();
+        }
+        if then.is_true() && otherwise.is_false() {
+            return condition.clone();
+        }
+        if then.is_false() && otherwise.is_true() {
+            return condition.not();
+        }
+        if then.is_true() || condition == then {
+            return condition.or(otherwise);
+        }
+        if otherwise.is_false() || condition == otherwise {
+            return condition.and(then);
+        }
+        if then.is_false() {
+            return otherwise.and_not(condition);
+        }
+        if otherwise.is_true() {
+            return condition.imp(then);
+        }
+        let mut ite = Ite {
+            operands: [condition, then, otherwise],
+            store: NodeStore::new(condition.num_vars(), condition.size()),
+            imported: [
+                vec![None; then.size()],
+                vec![None; otherwise.size()],
+                vec![None; condition.size()],
+                vec![None; condition.size()],
+            ],
+            cache: HashMap::default(),
+            import_stack: Vec::new(),
+        };
+        let root = ite.ite(
+            condition.root_pointer(),
+            then.root_pointer(),
+            otherwise.root_pointer(),
+        );
+        ite.store.into_bdd(root)
+    }
+}
+
+/// **(internal)** Working state of one if-then-else operation.
+struct Ite<'a> {
+    operands: [&'a Bdd; 3],
+    store: NodeStore,
+    /// Position of the nodes copied into the store, for each `Source`.
+    imported: [Vec<Option<BddPointer>>; 4],
+    cache: HashMap<(BddPointer, BddPointer, BddPointer), BddPointer, FxBuildHasher>,
+    /// Work stack of `import` (empty between calls).
+    import_stack: Vec<BddPointer>,
+}
+
+/// **(internal)** A sub-graph which can be copied into the store of an `Ite`.
+#[derive(Clone, Copy)]
+enum Source {
+    Then = 0,
+    Otherwise = 1,
+    Condition = 2,
+    /// The condition with swapped terminals, i.e. its negation.
+    NotCondition = 3,
+}
+
+/// **(internal)** A step of `Ite::ite`: resolve a triple of nodes, split a triple which is
+/// not resolved into its cofactor triples, or join the two (already computed) cofactor
+/// results of a triple into a node.
+enum IteTask {
+    Expand(BddPointer, BddPointer, BddPointer),
+    Split(BddPointer, BddPointer, BddPointer),
+    Join(BddVariable, (BddPointer, BddPointer, BddPointer)),
+}
+
+impl<'a> Ite<'a> {
+    /// Compute the result for a triple of nodes.
+    ///
+    /// The triples are explored using an explicit stack: a triple is split into its two
+    /// cofactor triples, and once both are finished, their results are taken from the
+    /// `results` stack and joined into a node.
+    fn ite(
+        &mut self,
+        condition: BddPointer,
+        then: BddPointer,
+        otherwise: BddPointer,
+    ) -> BddPointer {
+        if let Some(result) = self.resolved(condition, then, otherwise) {
+            return result;
+        }
+        let mut stack: Vec<IteTask> = vec![IteTask::Split(condition, then, otherwise)];
+        let mut results: Vec<BddPointer> = Vec::new();
+        while let Some(task) = stack.pop() {
+            match task {
+                IteTask::Expand(condition, then, otherwise) => {
+                    match self.resolved(condition, then, otherwise) {
+                        Some(result) => results.push(result),
+                        None => stack.push(IteTask::Split(condition, then, otherwise)),
+                    }
+                }
+                IteTask::Split(condition, then, otherwise) => {
+                    let vars = [
+                        self.var_of(0, condition),
+                        self.var_of(1, then),
+                        self.var_of(2, otherwise),
+                    ];
+                    let var = vars[0].min(vars[1]).min(vars[2]);
+                    let split = |bdd: &Bdd, node: BddPointer, node_var: BddVariable| {
+                        if node_var == var {
+                            (bdd.low_link_of(node), bdd.high_link_of(node))
+                        } else {
+                            (node, node)
+                        }
+                    };
+                    let (c_low, c_high) = split(self.operands[0], condition, vars[0]);
+                    let (t_low, t_high) = split(self.operands[1], then, vars[1]);
+                    let (o_low, o_high) = split(self.operands[2], otherwise, vars[2]);
+                    let task = (condition, then, otherwise);
+                    // The high triple is only resolved once the low triple is finished, since
+                    // it can use the results computed for the low triple.
+                    match self.resolved(c_low, t_low, o_low) {
+                        Some(low) => match self.resolved(c_high, t_high, o_high) {
+                            Some(high) => {
+                                let result = self.store.mk_node(var, low, high);
+                                self.cache.insert(task, result);
+                                results.push(result);
+                            }
+                            None => {
+                                results.push(low);
+                                stack.push(IteTask::Join(var, task));
+                                stack.push(IteTask::Split(c_high, t_high, o_high));
+                            }
+                        },
+                        None => {
+                            stack.push(IteTask::Join(var, task));
+                            stack.push(IteTask::Expand(c_high, t_high, o_high));
+                            stack.push(IteTask::Split(c_low, t_low, o_low));
+                        }
+                    }
+                }
+                IteTask::Join(var, task) => {
+                    let high = results.pop().unwrap();
+                    let low = results.pop().unwrap();
+                    let result = self.store.mk_node(var, low, high);
+                    self.cache.insert(task, result);
+                    results.push(result);
+                }
+            }
+        }
+        results.pop().unwrap()
+    }
+
+    /// The result for a triple of nodes if it is a terminal case or already in the cache.
+    ///
+    /// The terminal cases are $ite(1, g, h) = g$, $ite(0, g, h) = h$, $ite(f, 1, 0) = f$,
+    /// $ite(f, 0, 1) = \neg f$ and $ite(f, g, g) = g$. The nodes of `then` and `otherwise`
+    /// come from different `Bdd`s, so the last case is only detected for terminals (equal
+    /// operands are resolved before the traversal starts).
+    fn resolved(
+        &mut self,
+        condition: BddPointer,
+        then: BddPointer,
+        otherwise: BddPointer,
+    ) -> Option<BddPointer> {
+        if condition.is_one() {
+            return Some(self.import(Source::Then, then));
+        }
+        if condition.is_zero() {
+            return Some(self.import(Source::Otherwise, otherwise));
+        }
+        if then.is_terminal() && otherwise.is_terminal() {
+            return Some(if then == otherwise {
+                then
+            } else if then.is_one() {
+                self.import(Source::Condition, condition)
+            } else {
+                self.import(Source::NotCondition, condition)
+            });
+        }
+        self.cache.get(&(condition, then, otherwise)).cloned()
+    }
+
+    /// The variable of a node in the given operand (terminals are below all variables).
+    fn var_of(&self, operand: usize, node: BddPointer) -> BddVariable {
+        if node.is_terminal() {
+            BddVariable(self.operands[operand].num_vars())
+        } else {
+            self.operands[operand].var_of(node)
+        }
+    }
+
+    /// Copy the sub-graph of `node` in the given `source` into the store, using an explicit
+    /// stack (the same way as `NodeStore::into_bdd`).
+    fn import(&mut self, source: Source, node: BddPointer) -> BddPointer {
+        let (bdd, negate) = match source {
+            Source::Then => (self.operands[1], false),
+            Source::Otherwise => (self.operands[2], false),
+            Source::Condition => (self.operands[0], false),
+            Source::NotCondition => (self.operands[0], true),
+        };
+        let imported = &mut self.imported[source as usize];
+        let position = |imported: &[Option<BddPointer>], node: BddPointer| {
+            if node.is_terminal() {
+                Some(BddPointer::from_bool(node.is_one() != negate))
+            } else {
+                imported[node.to_index()]
+            }
+        };
+        if let Some(result) = position(imported, node) {
+            return result;
+        }
+        let stack = &mut self.import_stack;
+        stack.push(node);
+        while let Some(top) = stack.last().cloned() {
+            if imported[top.to_index()].is_some() {
+                stack.pop();
+                continue;
+            }
+            let low = position(imported, bdd.low_link_of(top));
+            let high = position(imported, bdd.high_link_of(top));
+            if let (Some(low), Some(high)) = (low, high) {
+                imported[top.to_index()] = Some(self.store.mk_node(bdd.var_of(top), low, high));
+                stack.pop();
+            } else {
+                if low.is_none() {
+                    stack.push(bdd.low_link_of(top));
+                }
+                if high.is_none() {
+                    stack.push(bdd.high_link_of(top));
+                }
+            }
+        }
+        imported[node.to_index()].unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{Bdd, BddPartialValuation, BddVariable, BddVariableSet};
+
+    #[test]
+    fn ite_basic() {
+        let variables = mk_5_variable_set();
+        let functions: Vec<Bdd> = [
+            "v1 ^ v2",
+            "v1 & !v4",
+            "v2 => v5",
+            "(v1 & v2) | (v3 & v5)",
+            "!v3 | v4",
+            "v3 <=> (v4 ^ v5)",
+            "true",
+            "false",
+        ]
+        .iter()
+        .map(|e| variables.eval_expression_string(e))
+        .collect();
+        for c in &functions {
+            for t in &functions {
+                for e in &functions {
+                    let expected = c.and(t).or(&c.not().and(e));
+                    assert_eq!(expected, Bdd::ite(c, t, e));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ite_deep_chain() {
+        // The chains are much deeper than what a recursive traversal can handle.
+        let variables = BddVariableSet::new_anonymous(60_000);
+        let all = variables.variables();
+        let chain = |last: bool| {
+            let mut values: Vec<(BddVariable, bool)> =
+                all[1..].iter().map(|v| (*v, true)).collect();
+            values.last_mut().unwrap().1 = last;
+            variables.mk_conjunctive_clause(&BddPartialValuation::from_values(&values))
+        };
+        let (first, second) = (variables.mk_var(all[0]), variables.mk_var(all[1]));
+        let check = |c: &Bdd, t: &Bdd, e: &Bdd| {
+            let expected = c.and(t).or(&c.not().and(e));
+            assert_eq!(expected, Bdd::ite(c, t, e));
+        };
+        // The chains are only copied into the result.
+        check(&first, &chain(true), &chain(false));
+        // All three operands are explored together along the chains.
+        check(&chain(true), &chain(false), &second.not());
+        check(
+            &chain(true),
+            &first.and(&chain(false)),
+            &first.xor(&chain(true)),
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn ite_operand_mismatch() {
+        let variables = mk_5_variable_set();
+        let other = crate::BddVariableSet::new_anonymous(3);
+        Bdd::ite(
+            &variables.mk_true(),
+            &variables.mk_false(),
+            &other.mk_true(),
+        );
+    }
+}
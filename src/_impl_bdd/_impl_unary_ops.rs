@@ -0,0 +1,400 @@
+use super::_impl_block_relations::block_pairs;
+use super::_impl_node_store::NodeStore;
+use crate::{Bdd, BddNode, BddPartialValuation, BddPointer, BddVariable, NodeRewrite};
+use fxhash::FxBuildHasher;
+use std::collections::HashMap;
+
+/// Operations which rewrite the decision nodes of a single `Bdd`.
+impl Bdd {
+    /// Substitute the values fixed by the given partial valuation for the corresponding
+    /// variables, i.e. compute the restriction $B[x_i \mapsto b_i]$. The result does not depend
+    /// on the fixed variables anymore.
+    ///
+    /// Unlike `select`, which removes all valuations that do not match the partial valuation,
+    /// `restrict` keeps, for every valuation, the value of the function with the fixed
+    /// variables replaced. In other words, `B.restrict(x = b)` is equivalent to
+    /// `B.select(x = b).project(x)`, but is computed in a single pass which only visits nodes
+    /// reachable in the restricted `Bdd`.
+    pub fn restrict(&self, valuation: &BddPartialValuation) -> Bdd {
+        unary_rewrite(self, |var| match valuation.get_value(var) {
+            None => NodeChoice::Keep,
+            Some(false) => NodeChoice::Low,
+            Some(true) => NodeChoice::High,
+        })
+    }
+
+    /// Compute the cofactor of this `Bdd` with `var` fixed to `value`, i.e. the restriction
+    /// $B[var \mapsto value]$ (the same as `restrict` with a single fixed variable). The result
+    /// does not depend on `var`: every node of `var` is replaced by its low or high child.
+    ///
+    /// *Panics:* `var` must be a valid variable of this `Bdd`.
+    pub fn cofactor(&self, var: BddVariable, value: bool) -> Bdd {
+        if var.0 >= self.num_vars() {
+            panic!(
+                "Variable {} is not valid in a Bdd with {} variables.",
+                var,
+                self.num_vars()
+            );
+        }
+        let choice = if value {
+            NodeChoice::High
+        } else {
+            NodeChoice::Low
+        };
+        unary_rewrite(self, |node_var| {
+            if node_var == var {
+                choice
+            } else {
+                NodeChoice::Keep
+            }
+        })
+    }
+
+    /// Compute the Boolean derivative of this `Bdd` with respect to `var`, i.e.
+    /// $B[var \mapsto 1] \oplus B[var \mapsto 0]$: the valuations in which changing the value
+    /// of `var` changes the value of the function.
+    ///
+    /// *Panics:* `var` must be a valid variable of this `Bdd`.
+    pub fn derivative(&self, var: BddVariable) -> Bdd {
+        self.combine_cofactors(var, Bdd::xor)
+    }
+
+    /// Compute the smoothing of this `Bdd` with respect to `var`, i.e.
+    /// $B[var \mapsto 1] \lor B[var \mapsto 0]$ (the same function as `var_project`).
+    ///
+    /// *Panics:* `var` must be a valid variable of this `Bdd`.
+    pub fn smooth(&self, var: BddVariable) -> Bdd {
+        self.combine_cofactors(var, Bdd::or)
+    }
+
+    /// Compute the consensus of this `Bdd` with respect to `var`, i.e.
+    /// $B[var \mapsto 1] \land B[var \mapsto 0]$: the valuations in which the function is
+    /// true regardless of the value of `var`.
+    ///
+    /// *Panics:* `var` must be a valid variable of this `Bdd`.
+    pub fn consensus(&self, var: BddVariable) -> Bdd {
+        self.combine_cofactors(var, Bdd::and)
+    }
+
+    /// **(internal)** Combine the two cofactors of this `Bdd` with respect to `var` using `op`.
+    fn combine_cofactors<F>(&self, var: BddVariable, op: F) -> Bdd
+    where
+        F: Fn(&Bdd, &Bdd) -> Bdd,
+    {
+        op(&self.cofactor(var, true), &self.cofactor(var, false))
+    }
+
+    /// Rewrite every decision node of this `Bdd` as described by the `rewrite` function
+    /// (see `NodeRewrite`) and build the reduced result.
+    ///
+    /// The nodes are processed bottom-up (children before parents) and `rewrite` is called
+    /// exactly once for every decision node with the variable of the node. The rewrites are
+    /// local: `Low`, `High` and `Constant` drop the node (redirecting its parents), `Swap`
+    /// exchanges its links and `Rename` changes its variable. The rewritten nodes do not need
+    /// to respect the variable ordering, since the result is re-canonicalized: a node with
+    /// variable $x$ and rewritten children $L$ and $H$ always represents
+    /// $(x \land H) \lor (\neg x \land L)$, so for example renaming all nodes of $y$ to $x$
+    /// corresponds to substituting $x$ for $y$.
+    ///
+    /// *Panics:* Variables used in `Rename` must be valid in this `Bdd`.
+    pub fn map_nodes<F>(&self, rewrite: F) -> Bdd
+    where
+        F: FnMut(BddVariable) -> NodeRewrite,
+    {
+        self.map_nodes_into(self.num_vars(), rewrite)
+    }
+
+    /// **(internal)** Same as `map_nodes`, but the result is a `Bdd` with `num_vars`
+    /// variables. Every variable which remains in the result after the rewrite must be
+    /// valid in the result.
+    pub(crate) fn map_nodes_into<F>(&self, num_vars: u16, mut rewrite: F) -> Bdd
+    where
+        F: FnMut(BddVariable) -> NodeRewrite,
+    {
+        let mut store = NodeStore::new(num_vars, self.size());
+        // Result of every source node in the store (terminals are at the same positions).
+        let mut results: Vec<BddPointer> = Vec::with_capacity(self.size());
+        results.push(BddPointer::zero());
+        results.push(BddPointer::one());
+        for node in self.pointers().skip(2) {
+            let var = self.var_of(node);
+            let low = results[self.low_link_of(node).to_index()];
+            let high = results[self.high_link_of(node).to_index()];
+            let result = match rewrite(var) {
+                NodeRewrite::Keep => store.mk_select(var, low, high),
+                NodeRewrite::Low => low,
+                NodeRewrite::High => high,
+                NodeRewrite::Swap => store.mk_select(var, high, low),
+                NodeRewrite::Constant(value) => BddPointer::from_bool(value),
+                NodeRewrite::Rename(new_var) => {
+                    if new_var.0 >= num_vars {
+                        panic!(
+                            "Variable {} is not valid in a Bdd with {} variables.",
+                            new_var, num_vars
+                        );
+                    }
+                    store.mk_select(new_var, low, high)
+                }
+            };
+            results.push(result);
+        }
+        store.into_bdd(results[self.size() - 1])
+    }
+
+    /// Rename the variables of this `Bdd` as given by the `mapping` (variables which are not
+    /// in the mapping keep their names). All variables are renamed simultaneously, so for
+    /// example, swapping two blocks of variables is a single permutation.
+    ///
+    /// If the mapping preserves the order of the variables used in this `Bdd`, the nodes are
+    /// only relabeled. Otherwise, the result is re-canonicalized using `map_nodes`. If two
+    /// variables of the `Bdd` are mapped to the same variable, the result is the corresponding
+    /// substitution.
+    ///
+    /// *Panics:* All variables in the mapping must be valid in this `Bdd`.
+    pub fn permute_variables(&self, mapping: &HashMap<BddVariable, BddVariable>) -> Bdd {
+        let num_vars = self.num_vars();
+        for (from, to) in mapping {
+            for var in [from, to].iter() {
+                if var.0 >= num_vars {
+                    panic!(
+                        "Variable {} is not valid in a Bdd with {} variables.",
+                        var, num_vars
+                    );
+                }
+            }
+        }
+        let rename = |var: BddVariable| mapping.get(&var).cloned().unwrap_or(var);
+        let renamed: Vec<BddVariable> = self.support_set().into_iter().map(rename).collect();
+        if renamed.windows(2).all(|pair| pair[0] < pair[1]) {
+            // Order-preserving renaming keeps the node graph (and its canonical order) intact.
+            let mut result = self.clone();
+            for node in result.0.iter_mut().skip(2) {
+                node.var = rename(node.var);
+            }
+            result
+        } else {
+            self.map_nodes(|var| NodeRewrite::Rename(rename(var)))
+        }
+    }
+
+    /// Swap the variables of two blocks, i.e. rename every `block_a[i]` to `block_b[i]` and
+    /// vice versa. This is typically used to exchange the primed and unprimed copies of
+    /// the state variables in image computation.
+    ///
+    /// This is the same as `permute_variables` with the corresponding mapping, but the mapping
+    /// is a direct lookup table. When the blocks are interleaved in the variable ordering
+    /// (`block_a[i]` and `block_b[i]` are adjacent), every node is only exchanged with
+    /// the nodes of its pair variable, so the cost is linear in the size of the `Bdd`.
+    ///
+    /// *Panics:* The blocks must have the same length and contain unique valid variables
+    /// (no variable can appear in both blocks).
+    pub fn swap_variable_blocks(&self, block_a: &[BddVariable], block_b: &[BddVariable]) -> Bdd {
+        let pairs = block_pairs(self.num_vars(), block_a, block_b);
+        let mut rename: Vec<Option<BddVariable>> = vec![None; usize::from(self.num_vars())];
+        for (a, b) in pairs {
+            rename[usize::from(a.0)] = Some(b);
+            rename[usize::from(b.0)] = Some(a);
+        }
+        if self
+            .nodes()
+            .skip(2)
+            .all(|node| rename[usize::from(node.var.0)].is_none())
+        {
+            return self.clone();
+        }
+        self.map_nodes(|var| match rename[usize::from(var.0)] {
+            Some(new_var) => NodeRewrite::Rename(new_var),
+            None => NodeRewrite::Keep,
+        })
+    }
+
+    /// Same as `restrict`, but the result replaces this `Bdd` and reuses its node vector.
+    ///
+    /// If no fixed variable appears in the `Bdd`, it is left untouched. Otherwise, the nodes are
+    /// rewritten in place and no new node vector is allocated (only the internal index
+    /// remapping tables are). This is useful when restricting the same `Bdd` repeatedly, for
+    /// example in constraint propagation.
+    pub fn restrict_assign(&mut self, valuation: &BddPartialValuation) {
+        if self
+            .nodes()
+            .skip(2)
+            .all(|node| !valuation.has_value(node.var))
+        {
+            return;
+        }
+
+        // First, rewrite the nodes bottom-up (children always precede parents). Every source
+        // node produces at most one new node, so the output position never overtakes the node
+        // being read. `remap` stores the new location of every source node.
+        let size = self.size();
+        let mut remap: Vec<BddPointer> = Vec::with_capacity(size);
+        remap.push(BddPointer::zero());
+        remap.push(BddPointer::one());
+        let mut existing: HashMap<BddNode, BddPointer, FxBuildHasher> =
+            HashMap::with_capacity_and_hasher(size, FxBuildHasher::default());
+        let mut next = 2;
+        for i in 2..size {
+            let node = self.0[i];
+            let low = remap[node.low_link.to_index()];
+            let high = remap[node.high_link.to_index()];
+            let new_pointer = match valuation.get_value(node.var) {
+                Some(false) => low,
+                Some(true) => high,
+                None if low == high => low,
+                None => {
+                    let node = BddNode::mk_node(node.var, low, high);
+                    *existing.entry(node).or_insert_with(|| {
+                        self.0[next] = node;
+                        next += 1;
+                        BddPointer::from_index(next - 1)
+                    })
+                }
+            };
+            remap.push(new_pointer);
+        }
+        let root = remap[size - 1];
+        if root.is_terminal() {
+            self.0.truncate(if root.is_one() { 2 } else { 1 });
+            return;
+        }
+
+        // The rewritten nodes can contain unreachable nodes and are not in the canonical
+        // (DFS post-order, high link first) order. Compute the canonical position of every
+        // reachable node, then permute the nodes in place and drop the rest.
+        let mut position: Vec<Option<BddPointer>> = vec![None; next];
+        position[0] = Some(BddPointer::zero());
+        position[1] = Some(BddPointer::one());
+        let mut reachable = 2;
+        let mut stack: Vec<BddPointer> = vec![root];
+        while let Some(top) = stack.last().cloned() {
+            if position[top.to_index()].is_some() {
+                stack.pop();
+                continue;
+            }
+            let node = self.0[top.to_index()];
+            let low_done = position[node.low_link.to_index()].is_some();
+            let high_done = position[node.high_link.to_index()].is_some();
+            if low_done && high_done {
+                position[top.to_index()] = Some(BddPointer::from_index(reachable));
+                reachable += 1;
+                stack.pop();
+            } else {
+                if !low_done {
+                    stack.push(node.low_link);
+                }
+                if !high_done {
+                    stack.push(node.high_link);
+                }
+            }
+        }
+        // Unreachable nodes are moved behind the reachable ones.
+        let mut garbage = reachable;
+        let mut position: Vec<BddPointer> = position
+            .into_iter()
+            .map(|p| {
+                p.unwrap_or_else(|| {
+                    garbage += 1;
+                    BddPointer::from_index(garbage - 1)
+                })
+            })
+            .collect();
+        for node in self.0.iter_mut().take(next).skip(2) {
+            node.low_link = position[node.low_link.to_index()];
+            node.high_link = position[node.high_link.to_index()];
+        }
+        for i in 2..next {
+            while position[i].to_index() != i {
+                let target = position[i].to_index();
+                self.0.swap(i, target);
+                position.swap(i, target);
+            }
+        }
+        self.0.truncate(reachable);
+    }
+}
+
+/// **(internal)** Describes how a unary operation treats a decision node of the source `Bdd`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum NodeChoice {
+    /// Keep the decision, rewriting both of its children.
+    Keep,
+    /// Replace the node with its (rewritten) low child.
+    Low,
+    /// Replace the node with its (rewritten) high child.
+    High,
+}
+
+/// **(internal)** Universal function to implement unary operations which replace decision
+/// nodes of a `Bdd` by one of their children, based on the decision variable of the node.
+///
+/// The source `Bdd` is explored top-down using an explicit stack, so only nodes which are
+/// reachable in the result are visited (the children of a replaced node which are not selected
+/// are skipped). Each source node is processed at most once, because its result is memoized.
+/// The result nodes are created in the same order as in `apply` (DFS post-order, high link
+/// first), so the output is canonical and can be compared with results of other operations.
+fn unary_rewrite<F>(source: &Bdd, rewrite: F) -> Bdd
+where
+    F: Fn(BddVariable) -> NodeChoice,
+{
+    let num_vars = source.num_vars();
+    if source.is_true() || source.is_false() {
+        return source.clone();
+    }
+
+    let mut result: Bdd = Bdd::mk_true(num_vars);
+    let mut existing: HashMap<BddNode, BddPointer, FxBuildHasher> =
+        HashMap::with_capacity_and_hasher(source.size(), FxBuildHasher::default());
+
+    // `finished[i]` is the result of rewriting the source node `i`, if it is already known.
+    let mut finished: Vec<Option<BddPointer>> = vec![None; source.size()];
+    finished[0] = Some(BddPointer::zero());
+    finished[1] = Some(BddPointer::one());
+
+    let mut stack: Vec<BddPointer> = vec![source.root_pointer()];
+    while let Some(on_stack) = stack.last().cloned() {
+        if finished[on_stack.to_index()].is_some() {
+            stack.pop();
+            continue;
+        }
+        let (low, high) = (source.low_link_of(on_stack), source.high_link_of(on_stack));
+        let (new_low, new_high) = (finished[low.to_index()], finished[high.to_index()]);
+        let var = source.var_of(on_stack);
+        match rewrite(var) {
+            NodeChoice::Low => match new_low {
+                Some(new_low) => finished[on_stack.to_index()] = Some(new_low),
+                None => stack.push(low),
+            },
+            NodeChoice::High => match new_high {
+                Some(new_high) => finished[on_stack.to_index()] = Some(new_high),
+                None => stack.push(high),
+            },
+            NodeChoice::Keep => {
+                if let (Some(new_low), Some(new_high)) = (new_low, new_high) {
+                    let new_pointer = if new_low == new_high {
+                        new_low
+                    } else {
+                        let node = BddNode::mk_node(var, new_low, new_high);
+                        *existing.entry(node).or_insert_with(|| {
+                            result.push_node(node);
+                            result.root_pointer()
+                        })
+                    };
+                    finished[on_stack.to_index()] = Some(new_pointer);
+                } else {
+                    if new_low.is_none() {
+                        stack.push(low);
+                    }
+                    if new_high.is_none() {
+                        stack.push(high);
+                    }
+                }
+            }
+        }
+    }
+
+    match finished[source.root_pointer().to_index()] {
+        Some(root) if root.is_zero() => Bdd::mk_false(num_vars),
+        Some(root) if root.is_one() => Bdd::mk_true(num_vars),
+        _ => result,
+    }
+}
@@ -12,12 +12,41 @@ impl Bdd {
         self.0.len()
     }
 
+    /// The number of bytes of memory occupied by this `Bdd`, including the unused capacity
+    /// of its node vector.
+    ///
+    /// Note that `Bdd`s do not share any state: all caches used by the `Bdd` operations are
+    /// released once the operation finishes, so this is the whole memory footprint.
+    pub fn memory_bytes(&self) -> usize {
+        std::mem::size_of::<Bdd>() + self.0.capacity() * std::mem::size_of::<BddNode>()
+    }
+
     /// Number of variables in the corresponding `BddVariableSet`.
     pub fn num_vars(&self) -> u16 {
         // Assert: every BDD is not empty - it has at least the terminal zero node.
         self.0[0].var.0
     }
 
+    /// Increase the number of variables of this `Bdd` to `num_vars`, such that it is
+    /// compatible with `Bdd`s of a `BddVariableSet` extended with new variables (see
+    /// `BddVariableSet::add_variable`). The function itself does not change, since the new
+    /// variables are placed at the end of the variable ordering.
+    ///
+    /// *Panics:* `num_vars` cannot be smaller than the current number of variables.
+    pub fn extend_num_vars(&mut self, num_vars: u16) {
+        if num_vars < self.num_vars() {
+            panic!(
+                "Cannot reduce the number of variables from {} to {}.",
+                self.num_vars(),
+                num_vars
+            );
+        }
+        // Only the terminal nodes refer to the number of variables.
+        for node in self.0.iter_mut().take(2) {
+            node.var = BddVariable(num_vars);
+        }
+    }
+
     /// True if this `Bdd` is exactly the `true` formula.
     pub fn is_true(&self) -> bool {
         self.0.len() == 2
@@ -30,6 +59,9 @@ impl Bdd {
 
     /// Approximately computes the number of valuations satisfying the formula given
     /// by this `Bdd`.
+    ///
+    /// The result saturates: if the number of valuations exceeds the range of `f64`,
+    /// the result is `f64::INFINITY`. For an exact result, see `checked_cardinality`.
     pub fn cardinality(&self) -> f64 {
         if self.is_false() {
             return 0.0;
@@ -75,6 +107,48 @@ impl Bdd {
         }
     }
 
+    /// Computes the exact number of valuations satisfying the formula given by this `Bdd`,
+    /// or `None` if the number does not fit into `u128`.
+    pub fn checked_cardinality(&self) -> Option<u128> {
+        if self.is_false() {
+            return Some(0);
+        }
+        let counts = self.exact_node_counts()?;
+        Some(counts[self.size() - 1] << self.var_of(self.root_pointer()).0)
+    }
+
+    /// **(internal)** For every node, compute the number of valuations of the variables
+    /// *below* the node's variable (inclusive) which satisfy the node. Returns `None` if
+    /// the total number of satisfying valuations does not fit into `u128`.
+    pub(crate) fn exact_node_counts(&self) -> Option<Vec<u128>> {
+        let num_vars = self.num_vars();
+        // Children always precede their parents.
+        let mut counts: Vec<u128> = Vec::with_capacity(self.size());
+        counts.push(0);
+        counts.push(1);
+        for node in self.pointers().skip(2) {
+            let var = self.var_of(node).0;
+            let branch = |child: BddPointer| -> Option<u128> {
+                // Variables skipped between the node and its child can have any value.
+                let child_var = if child.is_terminal() {
+                    num_vars
+                } else {
+                    self.var_of(child).0
+                };
+                checked_shift(counts[child.to_index()], child_var - var - 1)
+            };
+            let count =
+                branch(self.low_link_of(node))?.checked_add(branch(self.high_link_of(node))?)?;
+            counts.push(count);
+        }
+        if self.size() > 1 {
+            checked_shift(counts[self.size() - 1], self.var_of(self.root_pointer()).0)?;
+        } else {
+            counts.truncate(1);
+        }
+        Some(counts)
+    }
+
     /// If the `Bdd` is satisfiable, return some `BddValuation` that satisfies the `Bdd`.
     pub fn sat_witness(&self) -> Option<BddValuation> {
         if self.is_false() {
@@ -103,6 +177,96 @@ impl Bdd {
         Some(BddValuation::new(valuation))
     }
 
+    /// The set of variables which appear in the decision nodes of this `Bdd`. The set is
+    /// ordered, so the variables are always iterated in increasing order.
+    ///
+    /// ```rust
+    /// use biodivine_lib_bdd::BddVariableSet;
+    ///
+    /// let variables = BddVariableSet::new_anonymous(4);
+    /// let bdd = variables.eval_expression_string("x_3 | (x_1 & x_3) | !x_0");
+    /// let support: Vec<String> = bdd
+    ///     .support_set()
+    ///     .into_iter()
+    ///     .map(|v| variables.name_of(v))
+    ///     .collect();
+    /// assert_eq!(vec!["x_0", "x_3"], support);
+    /// ```
+    pub fn support_set(&self) -> std::collections::BTreeSet<BddVariable> {
+        self.nodes().skip(2).map(|node| node.var).collect()
+    }
+
+    /// For every variable not fixed by the given partial valuation, determine which values
+    /// the variable can still take in some satisfying valuation of this `Bdd` that extends
+    /// the partial valuation. The result contains `Some(value)` if the variable is forced to
+    /// `value`, and `None` if both values are possible.
+    ///
+    /// The variables are listed in the increasing order. If the `Bdd` is not satisfiable
+    /// under the partial valuation, the result is empty.
+    ///
+    /// After restricting the `Bdd` to the partial valuation, all values are computed in a
+    /// single pass over its nodes: a value of a variable is possible if some edge of the
+    /// restricted `Bdd` leading to a non-zero node either tests the variable with that value,
+    /// or skips the variable completely.
+    pub fn valid_domains(&self, partial: &BddPartialValuation) -> Vec<(BddVariable, Option<bool>)> {
+        let restricted = self.restrict(partial);
+        if restricted.is_false() {
+            return Vec::new();
+        }
+        let num_vars = usize::from(self.num_vars());
+        let mut can_be_false = vec![false; num_vars];
+        let mut can_be_true = vec![false; num_vars];
+        // `skipped[i] - skipped[i - 1]` is the number of edges that start above variable `i`
+        // and end below it (difference encoding, so that each edge is processed in O(1)).
+        let mut skipped = vec![0i64; num_vars + 1];
+        let mut mark_skipped = |from: usize, to: usize| {
+            if from < to {
+                skipped[from] += 1;
+                skipped[to] -= 1;
+            }
+        };
+        // Variables above the root are not tested at all.
+        mark_skipped(
+            0,
+            usize::from(restricted.var_of(restricted.root_pointer()).0),
+        );
+        for node in restricted.pointers().skip(2) {
+            let var = usize::from(restricted.var_of(node).0);
+            for (child, value) in [
+                (restricted.low_link_of(node), false),
+                (restricted.high_link_of(node), true),
+            ]
+            .iter()
+            {
+                if child.is_zero() {
+                    continue;
+                }
+                if *value {
+                    can_be_true[var] = true;
+                } else {
+                    can_be_false[var] = true;
+                }
+                mark_skipped(var + 1, usize::from(restricted.var_of(*child).0));
+            }
+        }
+        let mut result = Vec::new();
+        let mut skipping_edges = 0;
+        for i in 0..num_vars {
+            skipping_edges += skipped[i];
+            let var = BddVariable(i as u16);
+            if partial.has_value(var) {
+                continue;
+            }
+            let value = if skipping_edges > 0 || (can_be_true[i] && can_be_false[i]) {
+                None
+            } else {
+                Some(can_be_true[i])
+            };
+            result.push((var, value));
+        }
+        result
+    }
+
     /// Convert this `Bdd` to a `BooleanExpression` (using the variable names from the given
     /// `BddVariableSet`).
     ///
@@ -184,6 +348,21 @@ impl Bdd {
         results.last().unwrap().clone()
     }
 
+    /// **(internal)** Panic if this `Bdd` and `other` do not have the same number of variables,
+    /// i.e. they cannot be operands of the same logical operation.
+    ///
+    /// Every public operation with multiple `Bdd` operands must call this before any shortcut
+    /// which could return a result without looking at all operands.
+    pub(crate) fn check_compatible(&self, other: &Bdd) {
+        if self.num_vars() != other.num_vars() {
+            panic!(
+                "Var count mismatch: BDDs are not compatible. {} != {}",
+                self.num_vars(),
+                other.num_vars()
+            );
+        }
+    }
+
     /// **(internal)** Pointer to the root of the decision diagram.
     pub(crate) fn root_pointer(&self) -> BddPointer {
         BddPointer::from_index(self.0.len() - 1)
@@ -248,14 +427,25 @@ impl Bdd {
     }
 
     /// **(internal)** Create an iterator over all nodes of the `Bdd` (including terminals).
-    pub(crate) fn nodes(&self) -> Iter<BddNode> {
+    pub(crate) fn nodes(&self) -> Iter<'_, BddNode> {
         self.0.iter()
     }
 }
 
+/// **(internal)** Multiply `value` by $2^{shift}$, or return `None` on overflow.
+fn checked_shift(value: u128, shift: u16) -> Option<u128> {
+    if value == 0 {
+        Some(0)
+    } else if u32::from(shift) > value.leading_zeros() {
+        None
+    } else {
+        Some(value << shift)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::_test_util::mk_small_test_bdd;
+    use crate::_test_util::{mk_5_variable_set, mk_small_test_bdd};
     use crate::boolean_expression::BooleanExpression;
     use crate::*;
     use std::convert::TryFrom;
@@ -292,6 +482,50 @@ mod tests {
         // 5 variables, v3 & !v4
         let bdd = mk_small_test_bdd();
         assert_eq!(8.0, bdd.cardinality());
+        assert_eq!(Some(8), bdd.checked_cardinality());
+    }
+
+    #[test]
+    fn bdd_checked_cardinality() {
+        let variables = mk_5_variable_set();
+        for expression in &["v1 ^ v2 ^ v3", "(v1 => v5) & (v2 | !v4)", "false", "true"] {
+            let bdd = variables.eval_expression_string(expression);
+            let count = bdd.checked_cardinality().unwrap();
+            assert_eq!(bdd.cardinality(), count as f64);
+        }
+        let variables = BddVariableSet::new_anonymous(127);
+        assert_eq!(
+            Some(1 << 126),
+            variables.mk_var(BddVariable(50)).checked_cardinality()
+        );
+        assert_eq!(Some(1 << 127), variables.mk_true().checked_cardinality());
+        let variables = BddVariableSet::new_anonymous(200);
+        assert_eq!(None, variables.mk_true().checked_cardinality());
+        assert_eq!(
+            None,
+            variables.mk_var(BddVariable(50)).checked_cardinality()
+        );
+        assert_eq!(Some(0), variables.mk_false().checked_cardinality());
+        let literals: Vec<(BddVariable, bool)> =
+            (0..80).map(|i| (BddVariable(2 * i), i % 3 == 0)).collect();
+        let bdd = variables.mk_conjunctive_clause(&BddPartialValuation::from_values(&literals));
+        assert_eq!(Some(1 << 120), bdd.checked_cardinality());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_extend_num_vars_shrink() {
+        let mut bdd = mk_5_variable_set().eval_expression_string("v1 & v2");
+        bdd.extend_num_vars(4);
+    }
+
+    #[test]
+    fn bdd_memory_bytes() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("v1 ^ v2 ^ v3");
+        let node_bytes = std::mem::size_of::<crate::BddNode>();
+        assert!(bdd.memory_bytes() >= bdd.size() * node_bytes);
+        assert!(variables.mk_true().memory_bytes() < bdd.memory_bytes());
     }
 
     #[test]
@@ -322,4 +556,46 @@ mod tests {
         assert_eq!(vars.eval_expression(&actual_expression), bdd);
         assert_eq!(bdd.to_boolean_expression(&vars), expected_expression);
     }
+
+    #[test]
+    fn valid_domains() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("(v1 => v2) & (v2 => !v4) & (v3 | v5)");
+        let (v1, v2, v3, v4, v5) = (
+            BddVariable(0),
+            BddVariable(1),
+            BddVariable(2),
+            BddVariable(3),
+            BddVariable(4),
+        );
+        assert_eq!(
+            vec![(v1, None), (v2, None), (v3, None), (v4, None), (v5, None)],
+            bdd.valid_domains(&BddPartialValuation::empty())
+        );
+        let partial = BddPartialValuation::from_values(&[(v1, true), (v5, false)]);
+        assert_eq!(
+            vec![(v2, Some(true)), (v3, Some(true)), (v4, Some(false))],
+            bdd.valid_domains(&partial)
+        );
+        let partial = BddPartialValuation::from_values(&[(v4, true)]);
+        assert_eq!(
+            vec![(v1, Some(false)), (v2, Some(false)), (v3, None), (v5, None)],
+            bdd.valid_domains(&partial)
+        );
+        let partial = BddPartialValuation::from_values(&[(v1, true), (v4, true)]);
+        assert!(bdd.valid_domains(&partial).is_empty());
+        // Compare with the naive approach which conditions on every value.
+        let partial = BddPartialValuation::from_values(&[(v3, false)]);
+        let restricted = bdd.restrict(&partial);
+        for (var, value) in bdd.valid_domains(&partial) {
+            let possible_true = !restricted.var_select(var, true).is_false();
+            let possible_false = !restricted.var_select(var, false).is_false();
+            let expected = match (possible_false, possible_true) {
+                (true, true) => None,
+                (_, true) => Some(true),
+                _ => Some(false),
+            };
+            assert_eq!(expected, value);
+        }
+    }
 }
@@ -0,0 +1,111 @@
+use crate::{Bdd, BddValuation, BddVariable};
+
+/// Spectral analysis of `Bdd`s using the Walsh-Hadamard transform.
+impl Bdd {
+    /// Compute the Walsh coefficient of this `Bdd` for the linear function given by `mask`:
+    /// $W_f(a) = \sum_x (-1)^{f(x) \oplus a \cdot x}$, where $a \cdot x$ is the XOR of all
+    /// variables which are `true` in `mask`.
+    ///
+    /// The coefficient is computed by counting the satisfying valuations of
+    /// $f \oplus (a \cdot x)$, i.e. $W_f(a) = 2^n - 2 \cdot |f \oplus (a \cdot x)|$. Same as
+    /// `cardinality`, the result is only exact up to $2^{53}$ (approx. 53 variables).
+    ///
+    /// *Panics:* `mask` must have the same number of variables as this `Bdd`.
+    pub fn walsh_coefficient(&self, mask: &BddValuation) -> f64 {
+        let num_vars = self.num_vars();
+        assert_eq!(
+            num_vars,
+            mask.num_vars(),
+            "Mask and Bdd have different number of variables."
+        );
+        let mut function = self.clone();
+        for var in (0..num_vars).map(BddVariable).filter(|v| mask[*v]) {
+            function = function.xor(&Bdd::mk_var(num_vars, var));
+        }
+        walsh_value(&function)
+    }
+
+    /// Compute all Walsh coefficients of this `Bdd`, or `None` if there are more than `limit`
+    /// coefficients ($2^n$ for $n$ variables).
+    ///
+    /// The coefficient of the mask with index `i` is stored at position `i`, where bit `k` of
+    /// the index is the value of the `k`-th variable in the mask (same as in `to_index_set`).
+    /// The masks are visited in the Gray code order, so each coefficient only needs one XOR
+    /// with a single variable (instead of the whole linear function).
+    ///
+    /// The spectrum can be used to compute, for example, the nonlinearity of the function:
+    /// $2^{n-1} - \max_a |W_f(a)| / 2$.
+    pub fn walsh_spectrum(&self, limit: usize) -> Option<Vec<f64>> {
+        let num_vars = self.num_vars();
+        if num_vars >= 64 || (1u64 << num_vars) > limit as u64 {
+            return None;
+        }
+        let mut spectrum = vec![0.0; 1 << num_vars];
+        let mut function = self.clone();
+        let mut mask: usize = 0;
+        spectrum[0] = walsh_value(&function);
+        for step in 1..spectrum.len() {
+            let flipped = step.trailing_zeros();
+            mask ^= 1 << flipped;
+            function = function.xor(&Bdd::mk_var(num_vars, BddVariable(flipped as u16)));
+            spectrum[mask] = walsh_value(&function);
+        }
+        Some(spectrum)
+    }
+}
+
+/// **(internal)** The sum of $(-1)^{g(x)}$ over all valuations, i.e. $2^n - 2 \cdot |g|$.
+fn walsh_value(function: &Bdd) -> f64 {
+    2.0f64.powi(i32::from(function.num_vars())) - 2.0 * function.cardinality()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::BddValuation;
+
+    #[test]
+    fn walsh_basic() {
+        let variables = mk_5_variable_set();
+        let mask = |values: &[bool]| BddValuation::new(values.to_vec());
+        let bdd = variables.eval_expression_string("v2 ^ v4");
+        assert_eq!(
+            32.0,
+            bdd.walsh_coefficient(&mask(&[false, true, false, true, false]))
+        );
+        assert_eq!(0.0, bdd.walsh_coefficient(&mask(&[false; 5])));
+        let bdd = variables.eval_expression_string("v1 & v2");
+        // f = v1 & v2 has 8 satisfying valuations out of 32.
+        assert_eq!(16.0, bdd.walsh_coefficient(&mask(&[false; 5])));
+        assert_eq!(
+            -16.0,
+            bdd.walsh_coefficient(&mask(&[true, true, false, false, false]))
+        );
+        assert_eq!(
+            0.0,
+            bdd.walsh_coefficient(&mask(&[true, false, true, false, false]))
+        );
+    }
+
+    #[test]
+    fn walsh_spectrum() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("(v1 & v2) ^ (v3 & v4) ^ (v2 & v5) ^ v3");
+        assert_eq!(None, bdd.walsh_spectrum(31));
+        let spectrum = bdd.walsh_spectrum(32).unwrap();
+        assert_eq!(32, spectrum.len());
+        for (index, coefficient) in spectrum.iter().enumerate() {
+            let mask: Vec<bool> = (0..5).map(|k| (index >> k) & 1 == 1).collect();
+            assert_eq!(
+                bdd.walsh_coefficient(&BddValuation::new(mask)),
+                *coefficient
+            );
+        }
+        // Parseval's identity.
+        let squares: f64 = spectrum.iter().map(|w| w * w).sum();
+        assert_eq!(1024.0, squares);
+        // The nonlinearity of this function is 12.
+        let max = spectrum.iter().fold(0.0f64, |max, w| max.max(w.abs()));
+        assert_eq!(12.0, 16.0 - max / 2.0);
+    }
+}
@@ -1,15 +1,66 @@
 /// **(internal)** Implementation of basic logical operators for `Bdd`s using the `apply` function.
 pub mod _impl_boolean_ops;
 
+/// **(internal)** Simplification of `Bdd`s with respect to a care set (constrain and restrict).
+pub mod _impl_care_ops;
+
+/// **(internal)** Implementation of the ternary if-then-else operator.
+pub mod _impl_ternary_ops;
+
 /// **(internal)** Implementation of extra operations which enable relation-like treatment of BDDs
 /// (quantification, selection, projection, partial element picking)
 pub mod _impl_relation_ops;
 
+/// **(internal)** Quantification of multiple variables in a single pass over a `Bdd`, and
+/// adaptive clustered quantification schedules.
+pub mod _impl_quantification;
+
+/// **(internal)** Implementation of unary operations which substitute constants for variables
+/// (restriction and cofactors), including the generic node rewriting procedure they are based
+/// on, operations which combine the two cofactors of a variable (derivative, smoothing and
+/// consensus), and general node-by-node rewriting of `Bdd`s.
+pub mod _impl_unary_ops;
+
+/// **(internal)** Operations which process whole slices of `Bdd`s, sharing work across
+/// the batch.
+pub mod _impl_batch_ops;
+
+/// **(internal)** Operations which process many `Bdd`s using multiple threads.
+pub mod _impl_parallel_ops;
+
+/// **(internal)** Selection of representative satisfying valuations of `Bdd`s.
+pub mod _impl_sampling;
+
+/// **(internal)** Conversion of `Bdd`s into the algebraic normal form and related analyses.
+pub mod _impl_anf;
+
+/// **(internal)** Walsh spectrum of `Bdd`s.
+pub mod _impl_walsh;
+
+/// **(internal)** Explanations of the value of a `Bdd` in a specific valuation.
+pub mod _impl_explain;
+
+/// **(internal)** Computation of optimal variable orderings for small `Bdd`s.
+pub mod _impl_optimal_order;
+
+/// **(internal)** Direct construction of relations between two blocks of variables.
+pub mod _impl_block_relations;
+
+/// **(internal)** Conversions between `Bdd`s and explicit sets of valuation indices.
+pub mod _impl_index_set;
+
 /// **(internal)** Simple export functions for printing `Bdd`s as `.dot` files.
 pub mod _impl_export_dot;
 
+/// **(internal)** Export and import of `Bdd`s in the `.nnf` (d-DNNF) format.
+pub mod _impl_export_nnf;
+
 /// **(internal)** Implementation of the string and byte serialisation procedures for `Bdd`s.
 pub mod _impl_serialisation;
 
+/// **(internal)** A set of unique decision nodes for operations which cannot create their
+/// result in the canonical node order directly.
+pub mod _impl_node_store;
+
 /// **(internal)** Implementation of some basic internal utility methods for `Bdd`s.
 pub mod _impl_util;
@@ -0,0 +1,203 @@
+use crate::{Bdd, Bdd3, BddValuation};
+
+impl Bdd3 {
+    /// Create a three-valued function from its `under` and `over` approximation: the function
+    /// is `true` in `under`, `false` outside of `over` and unknown in the remaining valuations.
+    ///
+    /// *Panics:* `under` must be a subset of `over` and both must have the same number
+    /// of variables.
+    pub fn from_approximations(under: Bdd, over: Bdd) -> Bdd3 {
+        if !under.and_not(&over).is_false() {
+            panic!("Under-approximation is not a subset of the over-approximation.");
+        }
+        Bdd3 { under, over }
+    }
+
+    /// The `Bdd` of valuations where this function is definitely `true`.
+    pub fn under_approximation(&self) -> &Bdd {
+        &self.under
+    }
+
+    /// The `Bdd` of valuations where this function is `true` or unknown.
+    pub fn over_approximation(&self) -> &Bdd {
+        &self.over
+    }
+
+    /// Convert this function into a pair of under- and over-approximating `Bdd`s.
+    pub fn into_approximations(self) -> (Bdd, Bdd) {
+        (self.under, self.over)
+    }
+
+    /// The `Bdd` of valuations where the value of this function is unknown.
+    pub fn unknown_set(&self) -> Bdd {
+        self.over.and_not(&self.under)
+    }
+
+    /// Number of variables in the corresponding `BddVariableSet`.
+    pub fn num_vars(&self) -> u16 {
+        self.under.num_vars()
+    }
+
+    /// True if this function has no unknown values, i.e. it is a standard Boolean function.
+    pub fn is_exact(&self) -> bool {
+        self.under == self.over
+    }
+
+    /// Convert this function to a standard `Bdd`, assuming it has no unknown values.
+    pub fn to_exact(&self) -> Option<Bdd> {
+        if self.is_exact() {
+            Some(self.under.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Evaluate this function in the given `BddValuation`. Returns `None` if the value
+    /// is unknown.
+    pub fn eval_in(&self, valuation: &BddValuation) -> Option<bool> {
+        if self.under.eval_in(valuation) {
+            Some(true)
+        } else if !self.over.eval_in(valuation) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Three-valued negation: $\neg \phi$ is unknown exactly when $\phi$ is unknown.
+    pub fn not(&self) -> Bdd3 {
+        Bdd3 {
+            under: self.over.not(),
+            over: self.under.not(),
+        }
+    }
+
+    /// Three-valued $\phi \land \psi$: `false` if one of the operands is `false`, `true` if
+    /// both operands are `true`, unknown otherwise.
+    pub fn and(&self, right: &Bdd3) -> Bdd3 {
+        Bdd3 {
+            under: self.under.and(&right.under),
+            over: self.over.and(&right.over),
+        }
+    }
+
+    /// Three-valued $\phi \lor \psi$: `true` if one of the operands is `true`, `false` if
+    /// both operands are `false`, unknown otherwise.
+    pub fn or(&self, right: &Bdd3) -> Bdd3 {
+        Bdd3 {
+            under: self.under.or(&right.under),
+            over: self.over.or(&right.over),
+        }
+    }
+
+    /// Three-valued $\phi \Rightarrow \psi$, i.e. $\neg \phi \lor \psi$.
+    pub fn imp(&self, right: &Bdd3) -> Bdd3 {
+        Bdd3 {
+            under: self.over.imp(&right.under),
+            over: self.under.imp(&right.over),
+        }
+    }
+
+    /// Three-valued $\phi \oplus \psi$: known only if both operands are known.
+    pub fn xor(&self, right: &Bdd3) -> Bdd3 {
+        let under = self
+            .under
+            .and_not(&right.over)
+            .or(&right.under.and_not(&self.over));
+        let over = self
+            .over
+            .and_not(&right.under)
+            .or(&right.over.and_not(&self.under));
+        Bdd3 { under, over }
+    }
+
+    /// Three-valued $\phi \Leftrightarrow \psi$: known only if both operands are known.
+    pub fn iff(&self, right: &Bdd3) -> Bdd3 {
+        self.xor(right).not()
+    }
+}
+
+/// Every standard `Bdd` is a three-valued function without unknown values.
+impl From<Bdd> for Bdd3 {
+    fn from(bdd: Bdd) -> Self {
+        Bdd3 {
+            under: bdd.clone(),
+            over: bdd,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{Bdd3, BddValuationIterator};
+
+    /// Kleene semantics of the binary operators, evaluated explicitly.
+    fn kleene(op: &str, l: Option<bool>, r: Option<bool>) -> Option<bool> {
+        let not = |x: Option<bool>| x.map(|x| !x);
+        let and = |l: Option<bool>, r: Option<bool>| match (l, r) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        };
+        let or = |l: Option<bool>, r: Option<bool>| not(and(not(l), not(r)));
+        let xor = |l: Option<bool>, r: Option<bool>| match (l, r) {
+            (Some(l), Some(r)) => Some(l ^ r),
+            _ => None,
+        };
+        match op {
+            "and" => and(l, r),
+            "or" => or(l, r),
+            "imp" => or(not(l), r),
+            "xor" => xor(l, r),
+            _ => not(xor(l, r)),
+        }
+    }
+
+    #[test]
+    fn bdd3_kleene_semantics() {
+        let variables = mk_5_variable_set();
+        let a = Bdd3::from_approximations(
+            variables.eval_expression_string("v1 & v2"),
+            variables.eval_expression_string("v1 | v3"),
+        );
+        let b = Bdd3::from_approximations(
+            variables.eval_expression_string("v4"),
+            variables.eval_expression_string("v4 | (v2 & v5)"),
+        );
+        assert!(!a.is_exact());
+        assert_eq!(None, a.to_exact());
+        let results = [
+            ("and", a.and(&b)),
+            ("or", a.or(&b)),
+            ("imp", a.imp(&b)),
+            ("xor", a.xor(&b)),
+            ("iff", a.iff(&b)),
+        ];
+        for valuation in BddValuationIterator::new(5) {
+            let (l, r) = (a.eval_in(&valuation), b.eval_in(&valuation));
+            assert_eq!(l.map(|l| !l), a.not().eval_in(&valuation));
+            for (op, result) in results.iter() {
+                assert_eq!(kleene(op, l, r), result.eval_in(&valuation), "{}", op);
+            }
+        }
+    }
+
+    #[test]
+    fn bdd3_exact_conversion() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("v1 => (v2 ^ v3)");
+        let exact = Bdd3::from(bdd.clone());
+        assert!(exact.is_exact());
+        assert!(exact.unknown_set().is_false());
+        assert_eq!(Some(bdd.not()), exact.not().to_exact());
+        assert_eq!((bdd.clone(), bdd), exact.into_approximations());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd3_invalid_approximations() {
+        let variables = mk_5_variable_set();
+        Bdd3::from_approximations(variables.mk_true(), variables.mk_var_by_name("v1"));
+    }
+}
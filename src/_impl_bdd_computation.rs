@@ -0,0 +1,243 @@
+use crate::op_function::BooleanOp;
+use crate::{Bdd, BddComputation, BddVariable, ComputationId};
+
+/// **(internal)** A node of a `BddComputation`. Operations only refer to nodes which were
+/// added before them, so the nodes are always sorted topologically.
+#[derive(Clone, Debug)]
+pub(crate) enum ComputationNode {
+    Input(Bdd),
+    Operation(Operation, Option<Bdd>),
+}
+
+/// **(internal)** An operation of a `BddComputation` together with its operands.
+#[derive(Clone, Debug)]
+pub(crate) enum Operation {
+    Binary(BooleanOp, usize, usize),
+    Exists(usize, Vec<BddVariable>),
+}
+
+impl Operation {
+    fn operands(&self) -> Vec<usize> {
+        match self {
+            Operation::Binary(_, left, right) => vec![*left, *right],
+            Operation::Exists(operand, _) => vec![*operand],
+        }
+    }
+}
+
+impl BddComputation {
+    /// Create an empty computation.
+    pub fn new() -> BddComputation {
+        BddComputation { nodes: Vec::new() }
+    }
+
+    /// Add a new input with the given initial value.
+    pub fn add_input(&mut self, value: Bdd) -> ComputationId {
+        self.nodes.push(ComputationNode::Input(value));
+        ComputationId(self.nodes.len() - 1)
+    }
+
+    /// Add a binary operation `op` applied to the results of `left` and `right`. The operation
+    /// is not evaluated until its value is requested.
+    ///
+    /// Unary operations can be expressed using `BooleanOp::NotLeft` or `BooleanOp::Left` with
+    /// the same operand on both sides.
+    ///
+    /// *Panics:* Both operands must be valid in this computation.
+    pub fn add_op(
+        &mut self,
+        op: BooleanOp,
+        left: ComputationId,
+        right: ComputationId,
+    ) -> ComputationId {
+        self.check_id(left);
+        self.check_id(right);
+        self.push_operation(Operation::Binary(op, left.0, right.0))
+    }
+
+    /// Add an existential quantification of the given `variables` in the result of `operand`
+    /// (see `Bdd::exists`). The operation is not evaluated until its value is requested.
+    ///
+    /// *Panics:* The operand must be valid in this computation.
+    pub fn add_exists(
+        &mut self,
+        operand: ComputationId,
+        variables: &[BddVariable],
+    ) -> ComputationId {
+        self.check_id(operand);
+        self.push_operation(Operation::Exists(operand.0, variables.to_vec()))
+    }
+
+    /// Replace the value of an input. All operations which depend on this input are
+    /// invalidated (unless the value did not change).
+    ///
+    /// *Panics:* The `id` must be an input of this computation and the new value must have
+    /// the same number of variables as the old value.
+    pub fn set_input(&mut self, id: ComputationId, value: Bdd) {
+        self.check_id(id);
+        match &mut self.nodes[id.0] {
+            ComputationNode::Input(old) => {
+                old.check_compatible(&value);
+                if *old == value {
+                    return;
+                }
+                *old = value;
+            }
+            ComputationNode::Operation(_, _) => {
+                panic!("Computation node {} is not an input.", id.0);
+            }
+        }
+        let mut changed = vec![false; self.nodes.len()];
+        changed[id.0] = true;
+        for i in (id.0 + 1)..self.nodes.len() {
+            if let ComputationNode::Operation(operation, value) = &mut self.nodes[i] {
+                if operation.operands().into_iter().any(|j| changed[j]) {
+                    changed[i] = true;
+                    *value = None;
+                }
+            }
+        }
+    }
+
+    /// True if the value of `id` is known without any recomputation.
+    ///
+    /// *Panics:* The `id` must be valid in this computation.
+    pub fn is_computed(&self, id: ComputationId) -> bool {
+        self.check_id(id);
+        match &self.nodes[id.0] {
+            ComputationNode::Input(_) => true,
+            ComputationNode::Operation(_, value) => value.is_some(),
+        }
+    }
+
+    /// Obtain the value of `id`, recomputing the invalidated operations it depends on (and only
+    /// those).
+    ///
+    /// *Panics:* The `id` must be valid in this computation. The operands of every operation
+    /// must have the same number of variables.
+    pub fn value(&mut self, id: ComputationId) -> &Bdd {
+        self.check_id(id);
+        // Explore the missing values depth-first; a node is evaluated once its operands are.
+        let mut stack: Vec<usize> = vec![id.0];
+        while let Some(top) = stack.last().cloned() {
+            let missing: Vec<usize> = match &self.nodes[top] {
+                ComputationNode::Operation(operation, None) => operation
+                    .operands()
+                    .into_iter()
+                    .filter(|i| !self.is_computed(ComputationId(*i)))
+                    .collect(),
+                _ => {
+                    stack.pop();
+                    continue;
+                }
+            };
+            if !missing.is_empty() {
+                stack.extend(missing);
+                continue;
+            }
+            let result = match &self.nodes[top] {
+                ComputationNode::Operation(Operation::Binary(op, left, right), _) => {
+                    Bdd::binary_op(self.known(*left), self.known(*right), op.function())
+                }
+                ComputationNode::Operation(Operation::Exists(operand, variables), _) => {
+                    self.known(*operand).exists(variables)
+                }
+                ComputationNode::Input(_) => unreachable!("Inputs are always computed."),
+            };
+            if let ComputationNode::Operation(_, value) = &mut self.nodes[top] {
+                *value = Some(result);
+            }
+            stack.pop();
+        }
+        self.known(id.0)
+    }
+
+    /// **(internal)** The value of a node which is already computed.
+    fn known(&self, index: usize) -> &Bdd {
+        match &self.nodes[index] {
+            ComputationNode::Input(value) => value,
+            ComputationNode::Operation(_, Some(value)) => value,
+            ComputationNode::Operation(_, None) => unreachable!("Node {} is not computed.", index),
+        }
+    }
+
+    /// **(internal)** Append a new (not yet evaluated) operation.
+    fn push_operation(&mut self, operation: Operation) -> ComputationId {
+        self.nodes.push(ComputationNode::Operation(operation, None));
+        ComputationId(self.nodes.len() - 1)
+    }
+
+    /// **(internal)** Panic if the `id` does not belong to this computation.
+    fn check_id(&self, id: ComputationId) {
+        if id.0 >= self.nodes.len() {
+            panic!(
+                "Computation node {} is not valid in a computation with {} nodes.",
+                id.0,
+                self.nodes.len()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::op_function::BooleanOp;
+    use crate::{BddComputation, BddVariable, ComputationId};
+
+    #[test]
+    fn bdd_computation_incremental() {
+        let variables = mk_5_variable_set();
+        let mut computation = BddComputation::new();
+        let x = computation.add_input(variables.eval_expression_string("v1 => v2"));
+        let y = computation.add_input(variables.eval_expression_string("v2 => v3"));
+        let z = computation.add_input(variables.eval_expression_string("v4 | v5"));
+        let xy = computation.add_op(BooleanOp::And, x, y);
+        let not_z = computation.add_op(BooleanOp::NotLeft, z, z);
+        let all = computation.add_op(BooleanOp::AndNot, xy, not_z);
+        let projected = computation.add_exists(all, &[BddVariable(1)]);
+        assert!(!computation.is_computed(projected));
+        assert_eq!(
+            &variables.eval_expression_string("(v1 => v3) & (v4 | v5)"),
+            computation.value(projected)
+        );
+        assert!(computation.is_computed(all));
+
+        computation.set_input(z, variables.eval_expression_string("v4"));
+        assert!(computation.is_computed(xy));
+        assert!(!computation.is_computed(not_z));
+        assert!(!computation.is_computed(all));
+        assert!(!computation.is_computed(projected));
+        assert_eq!(
+            &variables.eval_expression_string("(v1 => v2) & (v2 => v3) & v4"),
+            computation.value(all)
+        );
+        // Only the requested part of the graph is recomputed.
+        assert!(!computation.is_computed(projected));
+
+        // Setting the same value does not invalidate anything.
+        computation.set_input(x, variables.eval_expression_string("v1 => v2"));
+        assert!(computation.is_computed(all));
+        assert_eq!(
+            &variables.eval_expression_string("(v1 => v3) & v4"),
+            computation.value(projected)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_computation_set_operation() {
+        let variables = mk_5_variable_set();
+        let mut computation = BddComputation::new();
+        let x = computation.add_input(variables.mk_true());
+        let y = computation.add_op(BooleanOp::Or, x, x);
+        computation.set_input(y, variables.mk_false());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_computation_invalid_id() {
+        let mut computation = BddComputation::new();
+        computation.value(ComputationId(0));
+    }
+}
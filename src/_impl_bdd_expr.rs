@@ -0,0 +1,430 @@
+use crate::{Bdd, BddExpr, BddVariable};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+
+/// **(internal)** A node of the recorded operation tree of a `BddExpr`.
+///
+/// Chains of associative operators are stored as n-ary nodes so that the evaluation
+/// order of their operands can be chosen freely when building the result.
+#[derive(Clone, Debug)]
+pub(crate) enum ExprNode {
+    Leaf(Bdd),
+    Not(Box<ExprNode>),
+    And(Vec<ExprNode>),
+    Or(Vec<ExprNode>),
+    Xor(Vec<ExprNode>),
+    Imp(Box<ExprNode>, Box<ExprNode>),
+    Iff(Box<ExprNode>, Box<ExprNode>),
+    Exists(Vec<BddVariable>, Box<ExprNode>),
+}
+
+impl BddExpr {
+    /// Create a conjunction of the two expressions.
+    pub fn and<T: Into<BddExpr>>(self, other: T) -> BddExpr {
+        BddExpr(merge(self.0, other.into().0, ExprNode::And, |n| match n {
+            ExprNode::And(operands) => Ok(operands),
+            n => Err(n),
+        }))
+    }
+
+    /// Create a disjunction of the two expressions.
+    pub fn or<T: Into<BddExpr>>(self, other: T) -> BddExpr {
+        BddExpr(merge(self.0, other.into().0, ExprNode::Or, |n| match n {
+            ExprNode::Or(operands) => Ok(operands),
+            n => Err(n),
+        }))
+    }
+
+    /// Create an exclusive disjunction of the two expressions.
+    pub fn xor<T: Into<BddExpr>>(self, other: T) -> BddExpr {
+        BddExpr(merge(self.0, other.into().0, ExprNode::Xor, |n| match n {
+            ExprNode::Xor(operands) => Ok(operands),
+            n => Err(n),
+        }))
+    }
+
+    /// Create an implication between the two expressions.
+    pub fn imp<T: Into<BddExpr>>(self, other: T) -> BddExpr {
+        BddExpr(ExprNode::Imp(Box::new(self.0), Box::new(other.into().0)))
+    }
+
+    /// Create an equivalence between the two expressions.
+    pub fn iff<T: Into<BddExpr>>(self, other: T) -> BddExpr {
+        BddExpr(ExprNode::Iff(Box::new(self.0), Box::new(other.into().0)))
+    }
+
+    /// Create a conjunction of this expression and the negation of the `other` expression.
+    pub fn and_not<T: Into<BddExpr>>(self, other: T) -> BddExpr {
+        self.and(!other.into())
+    }
+
+    /// Existentially quantify the given `variables` in this expression (see `Bdd::project`).
+    ///
+    /// If this expression is a conjunction, the variables are eliminated as soon as possible
+    /// while the conjunction is evaluated (early quantification), so the full conjunction
+    /// is typically never computed.
+    pub fn exists(self, variables: &[BddVariable]) -> BddExpr {
+        BddExpr(ExprNode::Exists(variables.to_vec(), Box::new(self.0)))
+    }
+
+    /// Universally quantify the given `variables` in this expression.
+    pub fn for_all(self, variables: &[BddVariable]) -> BddExpr {
+        !(!self).exists(variables)
+    }
+
+    /// Evaluate the expression into a `Bdd`.
+    ///
+    /// Operands of `and`, `or` and `xor` chains are combined starting with the smallest
+    /// intermediate results (which balances the evaluation tree regardless of how the
+    /// expression was written), conjunctions stop as soon as the result is `false`
+    /// (and disjunctions when it is `true`), and quantified variables are eliminated
+    /// early where possible.
+    ///
+    /// *Panics:* All `Bdd`s in the expression must have the same number of variables.
+    pub fn build(&self) -> Bdd {
+        // Evaluation can stop early, so the operands are checked before it starts.
+        let mut first: Option<&Bdd> = None;
+        check_leaves(&self.0, &mut first);
+        eval(&self.0).into_owned()
+    }
+}
+
+/// **(internal)** Check that all leaves of the expression are compatible with the `first` leaf.
+fn check_leaves<'a>(node: &'a ExprNode, first: &mut Option<&'a Bdd>) {
+    match node {
+        ExprNode::Leaf(bdd) => match first {
+            Some(first) => first.check_compatible(bdd),
+            None => *first = Some(bdd),
+        },
+        ExprNode::Not(inner) | ExprNode::Exists(_, inner) => check_leaves(inner, first),
+        ExprNode::And(operands) | ExprNode::Or(operands) | ExprNode::Xor(operands) => {
+            for operand in operands {
+                check_leaves(operand, first);
+            }
+        }
+        ExprNode::Imp(l, r) | ExprNode::Iff(l, r) => {
+            check_leaves(l, first);
+            check_leaves(r, first);
+        }
+    }
+}
+
+impl From<Bdd> for BddExpr {
+    fn from(bdd: Bdd) -> Self {
+        BddExpr(ExprNode::Leaf(bdd))
+    }
+}
+
+impl From<&Bdd> for BddExpr {
+    fn from(bdd: &Bdd) -> Self {
+        BddExpr(ExprNode::Leaf(bdd.clone()))
+    }
+}
+
+impl Not for BddExpr {
+    type Output = BddExpr;
+
+    fn not(self) -> BddExpr {
+        match self.0 {
+            ExprNode::Not(inner) => BddExpr(*inner),
+            node => BddExpr(ExprNode::Not(Box::new(node))),
+        }
+    }
+}
+
+impl Not for &Bdd {
+    type Output = BddExpr;
+
+    fn not(self) -> BddExpr {
+        !BddExpr::from(self)
+    }
+}
+
+impl<T: Into<BddExpr>> BitAnd<T> for BddExpr {
+    type Output = BddExpr;
+
+    fn bitand(self, rhs: T) -> BddExpr {
+        self.and(rhs)
+    }
+}
+
+impl<T: Into<BddExpr>> BitAnd<T> for &Bdd {
+    type Output = BddExpr;
+
+    fn bitand(self, rhs: T) -> BddExpr {
+        BddExpr::from(self).and(rhs)
+    }
+}
+
+impl<T: Into<BddExpr>> BitOr<T> for BddExpr {
+    type Output = BddExpr;
+
+    fn bitor(self, rhs: T) -> BddExpr {
+        self.or(rhs)
+    }
+}
+
+impl<T: Into<BddExpr>> BitOr<T> for &Bdd {
+    type Output = BddExpr;
+
+    fn bitor(self, rhs: T) -> BddExpr {
+        BddExpr::from(self).or(rhs)
+    }
+}
+
+impl<T: Into<BddExpr>> BitXor<T> for BddExpr {
+    type Output = BddExpr;
+
+    fn bitxor(self, rhs: T) -> BddExpr {
+        self.xor(rhs)
+    }
+}
+
+impl<T: Into<BddExpr>> BitXor<T> for &Bdd {
+    type Output = BddExpr;
+
+    fn bitxor(self, rhs: T) -> BddExpr {
+        BddExpr::from(self).xor(rhs)
+    }
+}
+
+/// **(internal)** Join two nodes using an n-ary operator, flattening operands which
+/// already use the same operator (`unpack` returns their operands).
+fn merge<C, U>(left: ExprNode, right: ExprNode, create: C, unpack: U) -> ExprNode
+where
+    C: Fn(Vec<ExprNode>) -> ExprNode,
+    U: Fn(ExprNode) -> Result<Vec<ExprNode>, ExprNode>,
+{
+    let mut operands = unpack(left).unwrap_or_else(|node| vec![node]);
+    match unpack(right) {
+        Ok(mut right) => operands.append(&mut right),
+        Err(node) => operands.push(node),
+    }
+    create(operands)
+}
+
+/// **(internal)** Evaluate an expression node. Leaves are returned without copying.
+fn eval(node: &ExprNode) -> Cow<'_, Bdd> {
+    match node {
+        ExprNode::Leaf(bdd) => Cow::Borrowed(bdd),
+        ExprNode::Not(inner) => Cow::Owned(eval(inner).not()),
+        ExprNode::And(operands) => eval_chain(operands, Bdd::and, Bdd::is_false),
+        ExprNode::Or(operands) => eval_chain(operands, Bdd::or, Bdd::is_true),
+        ExprNode::Xor(operands) => eval_chain(operands, Bdd::xor, |_| false),
+        ExprNode::Imp(l, r) => Cow::Owned(eval(l).imp(&eval(r))),
+        ExprNode::Iff(l, r) => Cow::Owned(eval(l).iff(&eval(r))),
+        ExprNode::Exists(variables, inner) => Cow::Owned(eval_exists(inner, variables)),
+    }
+}
+
+/// **(internal)** Evaluate an expression node with the given `variables` existentially
+/// quantified.
+fn eval_exists(node: &ExprNode, variables: &[BddVariable]) -> Bdd {
+    match node {
+        ExprNode::And(operands) => eval_and_exists(operands, variables),
+        // Existential quantification distributes over disjunction.
+        ExprNode::Or(operands) => {
+            let mut result = eval_exists(&operands[0], variables);
+            for operand in &operands[1..] {
+                if result.is_true() {
+                    break;
+                }
+                result = result.or(&eval_exists(operand, variables));
+            }
+            result
+        }
+        node => eval(node).project(variables),
+    }
+}
+
+/// **(internal)** Evaluate an n-ary associative operator by always combining the two
+/// smallest intermediate results. Evaluation stops once an `absorbing` result is found.
+fn eval_chain<'a, O, A>(operands: &'a [ExprNode], op: O, absorbing: A) -> Cow<'a, Bdd>
+where
+    O: Fn(&Bdd, &Bdd) -> Bdd,
+    A: Fn(&Bdd) -> bool,
+{
+    let mut queue = BinaryHeap::new();
+    for (i, operand) in operands.iter().enumerate() {
+        let bdd = eval(operand);
+        if absorbing(&bdd) {
+            return bdd;
+        }
+        queue.push(SizeOrdered(Reverse(bdd.size()), i, bdd));
+    }
+    let mut next_id = operands.len();
+    while queue.len() > 1 {
+        let SizeOrdered(_, _, left) = queue.pop().unwrap();
+        let SizeOrdered(_, _, right) = queue.pop().unwrap();
+        let result = op(&left, &right);
+        if absorbing(&result) {
+            return Cow::Owned(result);
+        }
+        queue.push(SizeOrdered(
+            Reverse(result.size()),
+            next_id,
+            Cow::Owned(result),
+        ));
+        next_id += 1;
+    }
+    queue.pop().unwrap().2
+}
+
+/// **(internal)** Evaluate $\exists variables: \bigwedge operands$, eliminating every
+/// variable as soon as it only appears in one of the intermediate results.
+fn eval_and_exists(operands: &[ExprNode], variables: &[BddVariable]) -> Bdd {
+    let variables: HashSet<BddVariable> = variables.iter().cloned().collect();
+    let mut remaining: Vec<(Cow<Bdd>, HashSet<BddVariable>)> = Vec::new();
+    for operand in operands {
+        let bdd = eval(operand);
+        if bdd.is_false() {
+            return bdd.into_owned();
+        }
+        let support = support_set(&bdd);
+        remaining.push((bdd, support));
+    }
+    // Quantify variables which appear only in a single operand right away.
+    for i in 0..remaining.len() {
+        let local = private_variables(&remaining, i, &variables);
+        if !local.is_empty() {
+            let bdd = remaining[i].0.project(&local);
+            let support = support_set(&bdd);
+            remaining[i] = (Cow::Owned(bdd), support);
+        }
+    }
+    while remaining.len() > 1 {
+        remaining.sort_by_key(|(bdd, _)| Reverse(bdd.size()));
+        let (left, _) = remaining.pop().unwrap();
+        let (right, _) = remaining.pop().unwrap();
+        let result = left.and(&right);
+        if result.is_false() {
+            return result;
+        }
+        let support = support_set(&result);
+        remaining.push((Cow::Owned(result), support));
+        let last = remaining.len() - 1;
+        let local = private_variables(&remaining, last, &variables);
+        if !local.is_empty() {
+            let bdd = remaining[last].0.project(&local);
+            let support = support_set(&bdd);
+            remaining[last] = (Cow::Owned(bdd), support);
+        }
+    }
+    remaining.pop().unwrap().0.into_owned()
+}
+
+/// **(internal)** Quantified variables that appear only in the support of the `i`-th operand.
+fn private_variables(
+    operands: &[(Cow<Bdd>, HashSet<BddVariable>)],
+    i: usize,
+    variables: &HashSet<BddVariable>,
+) -> Vec<BddVariable> {
+    operands[i]
+        .1
+        .iter()
+        .filter(|v| variables.contains(v))
+        .filter(|v| {
+            operands
+                .iter()
+                .enumerate()
+                .all(|(j, (_, support))| i == j || !support.contains(v))
+        })
+        .cloned()
+        .collect()
+}
+
+/// **(internal)** Set of all variables which appear in the decision nodes of the `Bdd`.
+fn support_set(bdd: &Bdd) -> HashSet<BddVariable> {
+    bdd.nodes().skip(2).map(|node| node.var).collect()
+}
+
+/// **(internal)** An item of the evaluation queue: the first component defines the priority,
+/// the second (unique) component makes the order total and deterministic.
+pub(crate) struct SizeOrdered<'a>(
+    pub(crate) Reverse<usize>,
+    pub(crate) usize,
+    pub(crate) Cow<'a, Bdd>,
+);
+
+impl PartialEq for SizeOrdered<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.0, self.1) == (other.0, other.1)
+    }
+}
+
+impl Eq for SizeOrdered<'_> {}
+
+impl PartialOrd for SizeOrdered<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SizeOrdered<'_> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.0, Reverse(self.1)).cmp(&(other.0, Reverse(other.1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BddExpr, BddVariableSet};
+
+    #[test]
+    fn bdd_expr_operators() {
+        let vars = BddVariableSet::new_anonymous(4);
+        let v = vars.variables();
+        let (a, b, c, d) = (
+            vars.mk_var(v[0]),
+            vars.mk_var(v[1]),
+            vars.mk_var(v[2]),
+            vars.mk_var(v[3]),
+        );
+        let expr = (&a & &b) | !(&c ^ &d);
+        assert_eq!(
+            vars.eval_expression_string("(x_0 & x_1) | !(x_2 ^ x_3)"),
+            expr.build()
+        );
+        let expr = (&a & &b & &c & &d).imp(!BddExpr::from(&a)).iff(&b);
+        assert_eq!(
+            vars.eval_expression_string("((x_0 & x_1 & x_2 & x_3) => !x_0) <=> x_1"),
+            expr.build()
+        );
+        assert_eq!(a.and_not(&b), BddExpr::from(&a).and_not(&b).build());
+        assert!((&a & !&a & &b).build().is_false());
+        assert!((&a | !&a | &b).build().is_true());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_expr_operand_mismatch() {
+        // The conjunction is `false` before the last operand is evaluated.
+        let vars = BddVariableSet::new_anonymous(5);
+        let other = BddVariableSet::new_anonymous(3);
+        (&vars.mk_false() & &vars.mk_var_by_name("x_0") & &other.mk_true()).build();
+    }
+
+    #[test]
+    fn bdd_expr_quantification() {
+        let vars = BddVariableSet::new_anonymous(5);
+        let v = vars.variables();
+        let r1 = vars.eval_expression_string("x_0 <=> x_1");
+        let r2 = vars.eval_expression_string("x_1 <=> x_2");
+        let r3 = vars.eval_expression_string("x_2 => (x_3 | x_4)");
+        let quantified = [v[1], v[2], v[3]];
+        let expected = r1.and(&r2).and(&r3).project(&quantified);
+        let expr = (&r1 & &r2 & &r3).exists(&quantified);
+        assert_eq!(expected, expr.build());
+        let expected = r1.or(&r3).project(&quantified);
+        assert_eq!(expected, (&r1 | &r3).exists(&quantified).build());
+        let expected = r1.not().project(&quantified);
+        assert_eq!(expected, (!&r1).exists(&quantified).build());
+        let expected = r1.imp(&r3).not().project(&quantified).not();
+        assert_eq!(
+            expected,
+            BddExpr::from(&r1).imp(&r3).for_all(&quantified).build()
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use crate::{Bdd, Bdd3, BddInterval, BddVariable};
+
+impl BddInterval {
+    /// Create a new interval from its `lower` and `upper` bound.
+    ///
+    /// *Panics:* `lower` must be a subset of `upper` and both must have the same number
+    /// of variables.
+    pub fn new(lower: Bdd, upper: Bdd) -> BddInterval {
+        if !lower.and_not(&upper).is_false() {
+            panic!("Lower bound of an interval is not a subset of its upper bound.");
+        }
+        BddInterval { lower, upper }
+    }
+
+    /// The under-approximation of the represented set.
+    pub fn lower(&self) -> &Bdd {
+        &self.lower
+    }
+
+    /// The over-approximation of the represented set.
+    pub fn upper(&self) -> &Bdd {
+        &self.upper
+    }
+
+    /// Convert this interval into its `(lower, upper)` bounds.
+    pub fn into_bounds(self) -> (Bdd, Bdd) {
+        (self.lower, self.upper)
+    }
+
+    /// The valuations for which it is not known whether they belong to the represented set.
+    pub fn uncertainty(&self) -> Bdd {
+        self.upper.and_not(&self.lower)
+    }
+
+    /// True if the interval represents exactly one set.
+    pub fn is_exact(&self) -> bool {
+        self.lower == self.upper
+    }
+
+    /// True if the given set lies within this interval.
+    pub fn contains(&self, set: &Bdd) -> bool {
+        self.lower.and_not(set).is_false() && set.and_not(&self.upper).is_false()
+    }
+
+    /// Sound complement of the interval: $[\neg \texttt{upper}, \neg \texttt{lower}]$.
+    pub fn not(&self) -> BddInterval {
+        BddInterval {
+            lower: self.upper.not(),
+            upper: self.lower.not(),
+        }
+    }
+
+    /// Sound intersection of two intervals.
+    pub fn and(&self, right: &BddInterval) -> BddInterval {
+        BddInterval {
+            lower: self.lower.and(&right.lower),
+            upper: self.upper.and(&right.upper),
+        }
+    }
+
+    /// Sound union of two intervals.
+    pub fn or(&self, right: &BddInterval) -> BddInterval {
+        BddInterval {
+            lower: self.lower.or(&right.lower),
+            upper: self.upper.or(&right.upper),
+        }
+    }
+
+    /// Sound difference of two intervals.
+    pub fn and_not(&self, right: &BddInterval) -> BddInterval {
+        BddInterval {
+            lower: self.lower.and_not(&right.upper),
+            upper: self.upper.and_not(&right.lower),
+        }
+    }
+
+    /// Sound existential quantification (see `Bdd::project`) of the given `variables`.
+    pub fn project(&self, variables: &[BddVariable]) -> BddInterval {
+        BddInterval {
+            lower: self.lower.project(variables),
+            upper: self.upper.project(variables),
+        }
+    }
+
+    /// The smallest interval which contains both `self` and `other`.
+    ///
+    /// Since the lattice of intervals has a finite height, this also serves as a widening
+    /// operator for fixed-point computations.
+    pub fn join(&self, other: &BddInterval) -> BddInterval {
+        BddInterval {
+            lower: self.lower.and(&other.lower),
+            upper: self.upper.or(&other.upper),
+        }
+    }
+
+    /// Widen the interval such that its bounds no longer depend on the given `variables`:
+    /// the lower bound is universally and the upper bound existentially quantified.
+    ///
+    /// The result is coarser, but its bounds are usually much smaller `Bdd`s.
+    pub fn relax(&self, variables: &[BddVariable]) -> BddInterval {
+        BddInterval {
+            lower: self.lower.not().project(variables).not(),
+            upper: self.upper.project(variables),
+        }
+    }
+}
+
+/// Every `Bdd` is an exact interval containing only itself.
+impl From<Bdd> for BddInterval {
+    fn from(bdd: Bdd) -> Self {
+        BddInterval {
+            lower: bdd.clone(),
+            upper: bdd,
+        }
+    }
+}
+
+/// Three-valued functions correspond to intervals of their `true` sets.
+impl From<Bdd3> for BddInterval {
+    fn from(function: Bdd3) -> Self {
+        let (lower, upper) = function.into_approximations();
+        BddInterval { lower, upper }
+    }
+}
+
+impl From<BddInterval> for Bdd3 {
+    fn from(interval: BddInterval) -> Self {
+        let (lower, upper) = interval.into_bounds();
+        Bdd3::from_approximations(lower, upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{Bdd, Bdd3, BddInterval, BddVariable};
+
+    #[test]
+    fn bdd_interval_operations_are_sound() {
+        let variables = mk_5_variable_set();
+        let x_sets: Vec<Bdd> = vec!["v1 & v2", "v1 & (v2 | v4)", "v1", "v1 & (v2 | !v3)"]
+            .into_iter()
+            .map(|e| variables.eval_expression_string(e))
+            .collect();
+        let y_sets: Vec<Bdd> = vec!["v3", "v3 | (v4 & v5)", "v3 | v5"]
+            .into_iter()
+            .map(|e| variables.eval_expression_string(e))
+            .collect();
+        let x = BddInterval::new(x_sets[0].clone(), x_sets[2].clone());
+        let y = BddInterval::new(y_sets[0].clone(), y_sets[2].clone());
+        let quantified = [BddVariable(1), BddVariable(4)];
+        for a in x_sets.iter().filter(|s| x.contains(s)) {
+            assert!(x.not().contains(&a.not()));
+            assert!(x.project(&quantified).contains(&a.project(&quantified)));
+            assert!(x.relax(&quantified).contains(a));
+            assert!(x.join(&y).contains(a));
+            for b in y_sets.iter().filter(|s| y.contains(s)) {
+                assert!(x.and(&y).contains(&a.and(b)));
+                assert!(x.or(&y).contains(&a.or(b)));
+                assert!(x.and_not(&y).contains(&a.and_not(b)));
+                assert!(x.join(&y).contains(b));
+            }
+        }
+        assert!(!x.contains(&y_sets[0]));
+        assert!(!x.is_exact());
+        assert_eq!(
+            variables.eval_expression_string("v1 & !v2"),
+            x.uncertainty()
+        );
+    }
+
+    #[test]
+    fn bdd_interval_conversions() {
+        let variables = mk_5_variable_set();
+        let bdd = variables.eval_expression_string("v1 => v5");
+        let exact = BddInterval::from(bdd.clone());
+        assert!(exact.is_exact());
+        assert!(exact.contains(&bdd));
+        let interval = BddInterval::new(variables.mk_false(), bdd.clone());
+        let function = Bdd3::from(interval.clone());
+        assert_eq!(bdd, function.unknown_set());
+        assert_eq!(interval, BddInterval::from(function));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_interval_invalid_bounds() {
+        let variables = mk_5_variable_set();
+        BddInterval::new(
+            variables.mk_var_by_name("v1"),
+            variables.mk_var_by_name("v2"),
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::{Bdd, BddNode, BddPartialValuation, BddPointer, BddVariable};
+use std::fmt::{Display, Error, Formatter};
+
+impl BddPartialValuation {
+    /// Create a partial valuation with no fixed variables.
+    pub fn empty() -> BddPartialValuation {
+        BddPartialValuation(Vec::new())
+    }
+
+    /// Create a partial valuation with the given variables fixed to the given values.
+    ///
+    /// If a variable appears multiple times, the last value is used.
+    pub fn from_values(values: &[(BddVariable, bool)]) -> BddPartialValuation {
+        let mut valuation = BddPartialValuation::empty();
+        for (var, value) in values {
+            valuation.set_value(*var, *value);
+        }
+        valuation
+    }
+
+    /// Get the value of the given variable, or `None` if the variable is not fixed.
+    pub fn get_value(&self, variable: BddVariable) -> Option<bool> {
+        self.0.get(variable.0 as usize).cloned().flatten()
+    }
+
+    /// Check whether the given variable has a fixed value.
+    pub fn has_value(&self, variable: BddVariable) -> bool {
+        self.get_value(variable).is_some()
+    }
+
+    /// Fix the value of the given variable.
+    pub fn set_value(&mut self, variable: BddVariable, value: bool) {
+        let i = variable.0 as usize;
+        if self.0.len() <= i {
+            self.0.resize(i + 1, None);
+        }
+        self.0[i] = Some(value);
+    }
+
+    /// Remove the value of the given variable (if it was fixed).
+    pub fn unset_value(&mut self, variable: BddVariable) {
+        let i = variable.0 as usize;
+        if i < self.0.len() {
+            self.0[i] = None;
+            // Keep the representation canonical so that equality works as expected.
+            while let Some(None) = self.0.last() {
+                self.0.pop();
+            }
+        }
+    }
+
+    /// True if no variable is fixed by this valuation.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Number of fixed variables.
+    pub fn cardinality(&self) -> usize {
+        self.0.iter().filter(|v| v.is_some()).count()
+    }
+
+    /// List of all fixed variables and their values, sorted by variable.
+    pub fn to_values(&self) -> Vec<(BddVariable, bool)> {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(i, value)| value.map(|value| (BddVariable(i as u16), value)))
+            .collect()
+    }
+}
+
+impl Display for BddPartialValuation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "[")?;
+        for (i, value) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match value {
+                Some(true) => write!(f, "1")?,
+                Some(false) => write!(f, "0")?,
+                None => write!(f, "-")?,
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+impl Bdd {
+    /// **(internal)** Create a `Bdd` of the conjunctive clause described by a partial valuation.
+    ///
+    /// The nodes are created directly, from the last variable to the first one.
+    pub(crate) fn mk_cube(num_vars: u16, valuation: &BddPartialValuation) -> Bdd {
+        let mut bdd = Bdd::mk_true(num_vars);
+        for (var, value) in valuation.to_values().into_iter().rev() {
+            assert!(var.0 < num_vars, "Invalid variable id.");
+            let child = bdd.root_pointer();
+            bdd.push_node(if value {
+                BddNode::mk_node(var, BddPointer::zero(), child)
+            } else {
+                BddNode::mk_node(var, child, BddPointer::zero())
+            });
+        }
+        bdd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BddPartialValuation, BddVariable};
+
+    #[test]
+    fn partial_valuation_basic() {
+        let (a, b, c) = (BddVariable(0), BddVariable(1), BddVariable(4));
+        let mut valuation = BddPartialValuation::from_values(&[(c, true), (a, false)]);
+        assert_eq!(Some(false), valuation.get_value(a));
+        assert_eq!(None, valuation.get_value(b));
+        assert_eq!(Some(true), valuation.get_value(c));
+        assert_eq!(None, valuation.get_value(BddVariable(10)));
+        assert_eq!(2, valuation.cardinality());
+        assert_eq!(vec![(a, false), (c, true)], valuation.to_values());
+        assert_eq!("[0,-,-,-,1]", valuation.to_string());
+        valuation.unset_value(c);
+        assert_eq!(BddPartialValuation::from_values(&[(a, false)]), valuation);
+        valuation.unset_value(a);
+        assert!(valuation.is_empty());
+        assert_eq!(BddPartialValuation::empty(), valuation);
+    }
+}
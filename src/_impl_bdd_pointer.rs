@@ -40,7 +40,15 @@ impl BddPointer {
     }
 
     /// Create a pointer from an usize index.
+    ///
+    /// *Panics:* `index` must fit into 32 bits, i.e. a `Bdd` can have at most $2^{32}$ nodes.
     pub fn from_index(index: usize) -> BddPointer {
+        if index > u32::MAX as usize {
+            panic!(
+                "Bdd is too large: node index {} does not fit into 32 bits.",
+                index
+            );
+        }
         BddPointer(index as u32)
     }
 
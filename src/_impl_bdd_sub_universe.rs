@@ -0,0 +1,241 @@
+use crate::{Bdd, BddPartialValuation, BddPointer, BddSubUniverse, BddVariable, BddVariableSet};
+
+impl BddVariableSet {
+    /// Create a view of this variable set which only allows the given variables
+    /// (see `BddSubUniverse`).
+    ///
+    /// *Panics:* All variables must be valid variables in this set.
+    pub fn scoped(&self, vars: &[BddVariable]) -> BddSubUniverse<'_> {
+        let mut variables = vars.to_vec();
+        variables.sort();
+        variables.dedup();
+        if let Some(var) = variables.iter().find(|v| v.0 >= self.num_vars) {
+            panic!("Variable {} is not known in this set.", var);
+        }
+        BddSubUniverse {
+            parent: self,
+            variables,
+        }
+    }
+}
+
+impl BddSubUniverse<'_> {
+    /// The `BddVariableSet` in which the created `Bdd`s are valid.
+    pub fn parent(&self) -> &BddVariableSet {
+        self.parent
+    }
+
+    /// The variables of this sub-universe, in increasing order.
+    pub fn variables(&self) -> &[BddVariable] {
+        &self.variables
+    }
+
+    /// True if the given variable belongs to this sub-universe.
+    pub fn contains(&self, var: BddVariable) -> bool {
+        self.variables.binary_search(&var).is_ok()
+    }
+
+    /// True if the given `Bdd` only depends on the variables of this sub-universe.
+    pub fn is_in_scope(&self, bdd: &Bdd) -> bool {
+        bdd.nodes().skip(2).all(|node| self.contains(node.var))
+    }
+
+    /// Create a `Bdd` corresponding to the `true` formula.
+    pub fn mk_true(&self) -> Bdd {
+        self.parent.mk_true()
+    }
+
+    /// Create a `Bdd` corresponding to the `false` formula.
+    pub fn mk_false(&self) -> Bdd {
+        self.parent.mk_false()
+    }
+
+    /// Create a `Bdd` corresponding to the $v$ formula where `v` is a variable of this
+    /// sub-universe.
+    ///
+    /// *Panics:* `var` must be a variable of this sub-universe.
+    pub fn mk_var(&self, var: BddVariable) -> Bdd {
+        self.mk_literal(var, true)
+    }
+
+    /// Create a `Bdd` corresponding to the $\neg v$ formula where `v` is a variable of this
+    /// sub-universe.
+    ///
+    /// *Panics:* `var` must be a variable of this sub-universe.
+    pub fn mk_not_var(&self, var: BddVariable) -> Bdd {
+        self.mk_literal(var, false)
+    }
+
+    /// Create a `Bdd` corresponding to the literal $v$ or $\neg v$ (depending on `value`).
+    ///
+    /// *Panics:* `var` must be a variable of this sub-universe.
+    pub fn mk_literal(&self, var: BddVariable, value: bool) -> Bdd {
+        self.check_var(var);
+        self.parent.mk_literal(var, value)
+    }
+
+    /// Create a `Bdd` corresponding to the $v$ formula where `v` is a variable with the given
+    /// name.
+    ///
+    /// *Panics:* `var` must be a name of a variable of this sub-universe.
+    pub fn mk_var_by_name(&self, var: &str) -> Bdd {
+        self.parent
+            .var_by_name(var)
+            .map(|var| self.mk_var(var))
+            .unwrap_or_else(|| panic!("Variable {} is not known in this set.", var))
+    }
+
+    /// Create a `Bdd` corresponding to the conjunction of literals fixed by the given
+    /// partial valuation (an empty valuation gives `true`).
+    ///
+    /// *Panics:* All variables in the valuation must be variables of this sub-universe.
+    pub fn mk_conjunctive_clause(&self, valuation: &BddPartialValuation) -> Bdd {
+        for (var, _) in valuation.to_values() {
+            self.check_var(var);
+        }
+        self.parent.mk_conjunctive_clause(valuation)
+    }
+
+    /// Evaluate the given expression into a `Bdd` which is valid in the parent variable set.
+    ///
+    /// *Panics:* The expression must be valid in the parent variable set and the result can
+    /// only depend on the variables of this sub-universe.
+    pub fn eval_expression_string(&self, expression: &str) -> Bdd {
+        let result = self.parent.eval_expression_string(expression);
+        if !self.is_in_scope(&result) {
+            panic!(
+                "Expression `{}` depends on variables outside of the scope.",
+                expression
+            );
+        }
+        result
+    }
+
+    /// List the valuations of the variables of this sub-universe which can be extended to
+    /// a satisfying valuation of the given `Bdd` (i.e. the satisfying valuations of the `Bdd`
+    /// projected to this sub-universe). Every returned partial valuation fixes exactly the
+    /// variables of this sub-universe. The valuations are listed in lexicographic order with
+    /// respect to the variable ordering (`false` before `true`).
+    ///
+    /// Be aware of the potential exponential number of valuations!
+    pub fn valuations(&self, bdd: &Bdd) -> Vec<BddPartialValuation> {
+        let outside: Vec<BddVariable> = self
+            .parent
+            .variables()
+            .into_iter()
+            .filter(|v| !self.contains(*v))
+            .collect();
+        let projected = bdd.project(&outside);
+        let mut result = Vec::new();
+        let mut valuation = BddPartialValuation::empty();
+        self.collect_valuations(
+            &projected,
+            projected.root_pointer(),
+            0,
+            &mut valuation,
+            &mut result,
+        );
+        result
+    }
+
+    /// **(internal)** Append all valuations of `variables[level..]` accepted by `node`,
+    /// extending the given `valuation`.
+    fn collect_valuations(
+        &self,
+        bdd: &Bdd,
+        node: BddPointer,
+        level: usize,
+        valuation: &mut BddPartialValuation,
+        result: &mut Vec<BddPartialValuation>,
+    ) {
+        if node.is_zero() {
+            return;
+        }
+        if level == self.variables.len() {
+            result.push(valuation.clone());
+            return;
+        }
+        let var = self.variables[level];
+        let (low, high) = if !node.is_terminal() && bdd.var_of(node) == var {
+            (bdd.low_link_of(node), bdd.high_link_of(node))
+        } else {
+            (node, node)
+        };
+        valuation.set_value(var, false);
+        self.collect_valuations(bdd, low, level + 1, valuation, result);
+        valuation.set_value(var, true);
+        self.collect_valuations(bdd, high, level + 1, valuation, result);
+        valuation.unset_value(var);
+    }
+
+    /// **(internal)** Panic if the variable is not a part of this sub-universe.
+    fn check_var(&self, var: BddVariable) {
+        if !self.contains(var) {
+            panic!("Variable {} is not in the scope.", var);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{BddPartialValuation, BddVariable};
+
+    #[test]
+    fn sub_universe_basic() {
+        let variables = mk_5_variable_set();
+        let (v1, v2, v4) = (BddVariable(0), BddVariable(1), BddVariable(3));
+        let scope = variables.scoped(&[v4, v1, v4]);
+        assert_eq!(&[v1, v4], scope.variables());
+        assert!(scope.contains(v1) && !scope.contains(v2));
+
+        assert_eq!(variables.mk_var(v1), scope.mk_var(v1));
+        assert_eq!(variables.mk_not_var(v4), scope.mk_not_var(v4));
+        assert_eq!(variables.mk_var(v4), scope.mk_var_by_name("v4"));
+        let cube = BddPartialValuation::from_values(&[(v1, true), (v4, false)]);
+        assert_eq!(
+            variables.mk_conjunctive_clause(&cube),
+            scope.mk_conjunctive_clause(&cube)
+        );
+        let bdd = scope.eval_expression_string("v1 => v4");
+        assert!(scope.is_in_scope(&bdd));
+        assert!(!scope.is_in_scope(&variables.mk_var(v2)));
+        // Out of scope variables are allowed as long as the result does not depend on them.
+        assert!(scope.eval_expression_string("v2 | !v2").is_true());
+    }
+
+    #[test]
+    fn sub_universe_valuations() {
+        let variables = mk_5_variable_set();
+        let (v1, v2, v3) = (BddVariable(0), BddVariable(1), BddVariable(2));
+        let scope = variables.scoped(&[v1, v3]);
+        let bdd = variables.eval_expression_string("(v1 & v2 & !v3) | (!v1 & v5)");
+        assert_eq!(
+            vec![
+                BddPartialValuation::from_values(&[(v1, false), (v3, false)]),
+                BddPartialValuation::from_values(&[(v1, false), (v3, true)]),
+                BddPartialValuation::from_values(&[(v1, true), (v3, false)]),
+            ],
+            scope.valuations(&bdd)
+        );
+        assert!(scope.valuations(&variables.mk_false()).is_empty());
+        assert_eq!(4, scope.valuations(&variables.mk_var(v2)).len());
+        assert_eq!(1, variables.scoped(&[]).valuations(&bdd).len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn sub_universe_var_out_of_scope() {
+        let variables = mk_5_variable_set();
+        variables.scoped(&[BddVariable(0)]).mk_var(BddVariable(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn sub_universe_expression_out_of_scope() {
+        let variables = mk_5_variable_set();
+        variables
+            .scoped(&[BddVariable(0)])
+            .eval_expression_string("v1 & v2");
+    }
+}
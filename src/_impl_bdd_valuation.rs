@@ -26,11 +26,11 @@ impl BddValuation {
     }
 
     pub fn clear(&mut self, variable: BddVariable) {
-        self.0[(variable.0 as usize)] = false;
+        self.0[variable.0 as usize] = false;
     }
 
     pub fn set(&mut self, variable: BddVariable) {
-        self.0[(variable.0 as usize)] = true;
+        self.0[variable.0 as usize] = true;
     }
 
     /// Convert the valuation to its underlying vector.
@@ -141,6 +141,14 @@ impl From<BddValuation> for Bdd {
 
 impl BddValuationIterator {
     /// Create a new iterator with a specified number of variables.
+    ///
+    /// ```rust
+    /// use biodivine_lib_bdd::{BddValuation, BddValuationIterator};
+    ///
+    /// let valuations: Vec<BddValuation> = BddValuationIterator::new(2).collect();
+    /// assert_eq!(BddValuation::new(vec![true, false]), valuations[1]);
+    /// assert_eq!(BddValuation::new(vec![false, true]), valuations[2]);
+    /// ```
     pub fn new(num_vars: u16) -> BddValuationIterator {
         BddValuationIterator(Some(BddValuation(vec![false; num_vars as usize])))
     }
@@ -172,10 +180,10 @@ mod tests {
         let v1 = universe.mk_var_by_name("x_0");
         let v2 = universe.mk_var_by_name("x_1");
         let bdd = bdd!(v1 & (!v2));
-        assert_eq!(true, bdd.eval_in(&BddValuation::new(vec![true, false])));
-        assert_eq!(false, bdd.eval_in(&BddValuation::new(vec![true, true])));
-        assert_eq!(false, bdd.eval_in(&BddValuation::new(vec![false, false])));
-        assert_eq!(false, bdd.eval_in(&BddValuation::new(vec![false, false])));
+        assert!(bdd.eval_in(&BddValuation::new(vec![true, false])));
+        assert!(!bdd.eval_in(&BddValuation::new(vec![true, true])));
+        assert!(!bdd.eval_in(&BddValuation::new(vec![false, false])));
+        assert!(!bdd.eval_in(&BddValuation::new(vec![false, false])));
     }
 
     #[test]
@@ -1,13 +1,16 @@
 use super::*;
+use crate::boolean_expression::BooleanExpression;
+use crate::op_function::BooleanOp;
+use std::convert::TryFrom;
 
 impl BddVariableSet {
     /// Create a new `BddVariableSet` with anonymous variables $(x_1, \ldots, x_n)$ where $n$ is
     /// the `num_vars` parameter.
     pub fn new_anonymous(num_vars: u16) -> BddVariableSet {
-        if num_vars >= (std::u16::MAX - 1) {
+        if num_vars >= (u16::MAX - 1) {
             panic!(
                 "Too many BDD variables. There can be at most {} variables.",
-                std::u16::MAX - 1
+                u16::MAX - 1
             )
         }
         BddVariableSet {
@@ -27,11 +30,68 @@ impl BddVariableSet {
         builder.build()
     }
 
+    /// Same as `BddVariableSet::new`, but returns an error instead of panicking when the
+    /// names are not valid. Each name must be unique and it must be parsed as a variable
+    /// by `BooleanExpression::try_from` (so, for example, `true` or `a b` are rejected).
+    pub fn try_new(vars: Vec<&str>) -> Result<BddVariableSet, String> {
+        check_new_names(&HashMap::new(), &vars)?;
+        Ok(BddVariableSet::new(vars))
+    }
+
+    /// Add a new variable with the given `name` at the end of the variable ordering.
+    ///
+    /// Existing `Bdd`s do not depend on the new variable, but they still use the original
+    /// number of variables. Use `Bdd::extend_num_vars` to make them compatible with the `Bdd`s
+    /// created by the extended set.
+    ///
+    /// Returns an error (and leaves the set unchanged) if the variable already exists or its
+    /// name is not valid. The names are validated the same way as in `try_new`.
+    pub fn add_variable(&mut self, name: &str) -> Result<BddVariable, String> {
+        check_new_names(&self.var_index_mapping, &[name])?;
+        Ok(self.push_variable(name))
+    }
+
+    /// Similar to `add_variable`, but allows adding multiple variables at the same time.
+    ///
+    /// All names are validated before any variable is added, so the set is left unchanged
+    /// if one of them is not valid.
+    pub fn add_variables(&mut self, names: Vec<&str>) -> Result<Vec<BddVariable>, String> {
+        check_new_names(&self.var_index_mapping, &names)?;
+        Ok(names
+            .into_iter()
+            .map(|name| self.push_variable(name))
+            .collect())
+    }
+
+    /// **(internal)** Add a variable with an already validated `name` to this set.
+    fn push_variable(&mut self, name: &str) -> BddVariable {
+        let new_variable_id = self.num_vars;
+        self.var_index_mapping
+            .insert(name.to_string(), new_variable_id);
+        self.var_names.push(name.to_string());
+        self.num_vars += 1;
+        BddVariable(new_variable_id)
+    }
+
     /// Return the number of variables in this set.
     pub fn num_vars(&self) -> u16 {
         self.num_vars
     }
 
+    /// Estimate the number of bytes of memory occupied by this variable set (the variable
+    /// names and the index of names, including unused capacity).
+    ///
+    /// The result is only an estimate, since the exact layout of the hash map is not known.
+    pub fn estimated_overhead(&self) -> usize {
+        let names: usize = self.var_names.iter().map(|name| name.capacity()).sum();
+        // Every map entry stores a copy of the name, the index and one control byte.
+        let entry = std::mem::size_of::<String>() + std::mem::size_of::<u16>() + 1;
+        std::mem::size_of::<BddVariableSet>()
+            + self.var_names.capacity() * std::mem::size_of::<String>()
+            + self.var_index_mapping.capacity() * entry
+            + 2 * names
+    }
+
     /// Create a `BddVariable` based on a variable name. If the name does not appear
     /// in this set, return `None`.
     pub fn var_by_name(&self, name: &str) -> Option<BddVariable> {
@@ -101,6 +161,257 @@ impl BddVariableSet {
             .map(|var| self.mk_not_var(var))
             .unwrap_or_else(|| panic!("Variable {} is not known in this set.", var))
     }
+
+    /// Create a BDD corresponding to the conjunction of literals fixed by the given
+    /// partial valuation (an empty valuation gives `true`).
+    ///
+    /// *Panics:* All variables in the valuation must be valid variables in this set.
+    pub fn mk_conjunctive_clause(&self, valuation: &BddPartialValuation) -> Bdd {
+        Bdd::mk_cube(self.num_vars, valuation)
+    }
+
+    /// Create a BDD corresponding to the disjunction of literals fixed by the given
+    /// partial valuation (an empty valuation gives `false`).
+    ///
+    /// *Panics:* All variables in the valuation must be valid variables in this set.
+    pub fn mk_disjunctive_clause(&self, valuation: &BddPartialValuation) -> Bdd {
+        // A clause is the negation of the cube of the negated literals.
+        let negated: Vec<(BddVariable, bool)> = valuation
+            .to_values()
+            .into_iter()
+            .map(|(var, value)| (var, !value))
+            .collect();
+        Bdd::mk_cube(self.num_vars, &BddPartialValuation::from_values(&negated)).not()
+    }
+
+    /// Compile an ordered list of rules into a `Bdd`. A valuation is in the result if the
+    /// first rule that matches it (i.e. its partial valuation agrees with the valuation) has
+    /// the value `true`. Valuations not matched by any rule are not in the result.
+    ///
+    /// Instead of nesting one if-then-else per rule (which repeatedly rebuilds an ever
+    /// growing intermediate result), the list is split recursively and the halves are
+    /// combined, so most of the work is done on small `Bdd`s.
+    ///
+    /// *Panics:* All variables in the rules must be valid variables in this set.
+    pub fn mk_decision_list(&self, rules: &[(BddPartialValuation, bool)]) -> Bdd {
+        self.compile_decision_list(rules).1
+    }
+
+    /// Create a `Bdd` from an explicit set of valuations of the given `vars`, encoded as integer
+    /// indices where bit `i` is the value of `vars[i]` (the same encoding as produced by
+    /// `Bdd::to_index_set`). The remaining variables are unconstrained.
+    ///
+    /// *Panics:* `vars` must contain at most 64 unique valid variables and every index must
+    /// fit into `vars.len()` bits.
+    pub fn mk_from_index_set(&self, vars: &[BddVariable], indices: &[u64]) -> Bdd {
+        Bdd::from_index_set(self.num_vars, vars, indices)
+    }
+
+    /// Create a `Bdd` of the equality relation between two blocks of variables, i.e.
+    /// $\bigwedge_i (a_i \Leftrightarrow b_i)$.
+    ///
+    /// The relation is built directly (see `mk_block_hamming_at_most`). If the blocks
+    /// are interleaved in the variable ordering, the result has three nodes per pair.
+    ///
+    /// *Panics:* The blocks must have the same length and contain unique valid variables
+    /// (no variable can appear in both blocks).
+    pub fn mk_block_equality(&self, block_a: &[BddVariable], block_b: &[BddVariable]) -> Bdd {
+        Bdd::mk_block_hamming_at_most(self.num_vars, block_a, block_b, 0)
+    }
+
+    /// Create a `Bdd` of all valuations where the two blocks of variables differ in at most
+    /// `k` positions (the Hamming distance of `block_a` and `block_b` is at most `k`).
+    ///
+    /// The relation is built directly, pair by pair, without intermediate `Bdd`s. If the blocks
+    /// are interleaved in the variable ordering, the result has $O(n \cdot k)$ nodes.
+    ///
+    /// *Panics:* The blocks must have the same length and contain unique valid variables
+    /// (no variable can appear in both blocks).
+    pub fn mk_block_hamming_at_most(
+        &self,
+        block_a: &[BddVariable],
+        block_b: &[BddVariable],
+        k: usize,
+    ) -> Bdd {
+        Bdd::mk_block_hamming_at_most(self.num_vars, block_a, block_b, k)
+    }
+
+    /// Create a `Bdd` of all valuations where the unsigned value of `block` is between
+    /// `lo` and `hi` (both inclusive). The first variable of the block is the most
+    /// significant bit. If `lo > hi`, the result is `false`.
+    ///
+    /// The relation is built directly, bit by bit, without intermediate `Bdd`s. If the block
+    /// is ordered from the most significant bit in the variable ordering, the result has
+    /// $O(n)$ nodes.
+    ///
+    /// *Panics:* The block must contain unique valid variables.
+    pub fn mk_block_range(&self, block: &[BddVariable], lo: u64, hi: u64) -> Bdd {
+        Bdd::mk_block_range(self.num_vars, block, lo, hi)
+    }
+
+    /// Create a `Bdd` of all valuations where the unsigned value of `block` is in the cyclic
+    /// interval from `lo` to `hi` (inclusive) modulo $2^n$, where $n$ is the size of the block.
+    /// The bounds are also taken modulo $2^n$, and if `lo > hi`, the interval wraps around
+    /// (it contains the values from `lo` to $2^n - 1$ and from `0` to `hi`). The first
+    /// variable of the block is the most significant bit.
+    ///
+    /// The result has the same size as for `mk_block_range`.
+    ///
+    /// *Panics:* The block must contain unique valid variables.
+    pub fn mk_block_cyclic_range(&self, block: &[BddVariable], lo: u64, hi: u64) -> Bdd {
+        Bdd::mk_block_cyclic_range(self.num_vars, block, lo, hi)
+    }
+
+    /// Create a `Bdd` of all valuations where the modular difference $(b - a) \bmod 2^n$
+    /// of the unsigned values of `block_b` and `block_a` is in the cyclic interval from `lo`
+    /// to `hi` (see `mk_block_cyclic_range`). The first variable of each block is the most
+    /// significant bit.
+    ///
+    /// This covers the usual wrap-around comparisons. For example, `a + k = b (mod 2^n)` is
+    /// the interval `[k, k]`, and the serial number comparison "`a` precedes `b`" is
+    /// the interval $[1, 2^{n-1} - 1]$.
+    ///
+    /// The relation is built directly, pair by pair, without adders or intermediate `Bdd`s.
+    /// If the blocks are interleaved in the variable ordering, the result has $O(n)$ nodes.
+    ///
+    /// *Panics:* The blocks must have the same length and contain unique valid variables
+    /// (no variable can appear in both blocks).
+    pub fn mk_block_difference_range(
+        &self,
+        block_a: &[BddVariable],
+        block_b: &[BddVariable],
+        lo: u64,
+        hi: u64,
+    ) -> Bdd {
+        Bdd::mk_block_difference_range(self.num_vars, block_a, block_b, lo, hi)
+    }
+
+    /// Create a `Bdd` of all valuations where the unsigned value of `block_a` is strictly
+    /// smaller than the unsigned value of `block_b`. The first variable of each block is
+    /// the most significant bit.
+    ///
+    /// The relation is built directly, pair by pair, without intermediate `Bdd`s. If the blocks
+    /// are interleaved in the variable ordering from the most significant bit, the result
+    /// has at most three nodes per pair.
+    ///
+    /// *Panics:* The blocks must have the same length and contain unique valid variables
+    /// (no variable can appear in both blocks).
+    pub fn mk_block_less_than(&self, block_a: &[BddVariable], block_b: &[BddVariable]) -> Bdd {
+        Bdd::mk_block_less_than(self.num_vars, block_a, block_b)
+    }
+
+    /// Convert a `Bdd` created in the `source` variable set into a `Bdd` of this variable
+    /// set, matching the variables by name. The variables can be ordered differently in the
+    /// two sets (the result is re-canonicalized in the order of this set).
+    ///
+    /// Returns `None` if some variable used by the `Bdd` does not exist in this set.
+    ///
+    /// *Panics:* The `bdd` must have the same number of variables as the `source` set.
+    pub fn transfer_from(&self, bdd: &Bdd, source: &BddVariableSet) -> Option<Bdd> {
+        if bdd.num_vars() != source.num_vars {
+            panic!(
+                "Var count mismatch: Bdd is not from the source set. {} != {}",
+                bdd.num_vars(),
+                source.num_vars
+            );
+        }
+        let mut mapping: Vec<Option<BddVariable>> = vec![None; usize::from(source.num_vars)];
+        for var in bdd.support_set() {
+            let name = &source.var_names[usize::from(var.0)];
+            mapping[usize::from(var.0)] = Some(self.var_by_name(name)?);
+        }
+        Some(
+            bdd.map_nodes_into(self.num_vars, |var| match mapping[usize::from(var.0)] {
+                Some(new_var) => NodeRewrite::Rename(new_var),
+                None => unreachable!("Variable {} is not used.", var),
+            }),
+        )
+    }
+
+    /// Compute the conjunction of all `bdds`.
+    ///
+    /// Instead of a linear fold, the operands are kept in a heap ordered by size and the two
+    /// smallest intermediate results are always combined first. This keeps the intermediate
+    /// results small for typical inputs (e.g. clauses of a CNF formula). Duplicate and `true`
+    /// operands are skipped, and the computation stops as soon as the result is `false`.
+    /// The conjunction of no operands is `true`.
+    ///
+    /// *Panics:* All `bdds` must have the same number of variables as this set.
+    pub fn and_all<I: IntoIterator<Item = Bdd>>(&self, bdds: I) -> Bdd {
+        Bdd::fold_smallest_first(self.num_vars, bdds, BooleanOp::And)
+    }
+
+    /// Compute the disjunction of all `bdds`, using the same strategy as `and_all` (duplicate
+    /// and `false` operands are skipped, and the computation stops once the result is `true`).
+    /// The disjunction of no operands is `false`.
+    ///
+    /// *Panics:* All `bdds` must have the same number of variables as this set.
+    pub fn or_all<I: IntoIterator<Item = Bdd>>(&self, bdds: I) -> Bdd {
+        Bdd::fold_smallest_first(self.num_vars, bdds, BooleanOp::Or)
+    }
+
+    /// Compute the exclusive disjunction (parity) of all `bdds`, using the same strategy as
+    /// `and_all`. Duplicate operands cancel out in pairs, `true` operands only negate
+    /// the result, and the parity of no operands is `false`.
+    ///
+    /// *Panics:* All `bdds` must have the same number of variables as this set.
+    pub fn xor_all<I: IntoIterator<Item = Bdd>>(&self, bdds: I) -> Bdd {
+        Bdd::fold_smallest_first(self.num_vars, bdds, BooleanOp::Xor)
+    }
+
+    /// **(internal)** Compile the given rules, returning the set of valuations matched by
+    /// some rule together with the result of the decision list.
+    fn compile_decision_list(&self, rules: &[(BddPartialValuation, bool)]) -> (Bdd, Bdd) {
+        match rules {
+            [] => (self.mk_false(), self.mk_false()),
+            [(cube, value)] => {
+                let matched = self.mk_conjunctive_clause(cube);
+                let result = if *value {
+                    matched.clone()
+                } else {
+                    self.mk_false()
+                };
+                (matched, result)
+            }
+            _ => {
+                let (first, second) = rules.split_at(rules.len() / 2);
+                let (first_matched, first_result) = self.compile_decision_list(first);
+                if first_matched.is_true() {
+                    // The remaining rules are unreachable.
+                    return (first_matched, first_result);
+                }
+                let (second_matched, second_result) = self.compile_decision_list(second);
+                (
+                    first_matched.or(&second_matched),
+                    first_result.or(&second_result.and_not(&first_matched)),
+                )
+            }
+        }
+    }
+}
+
+/// **(internal)** Check that the new variable `names` can be added to a set which already
+/// contains the `existing` variables: the total number of variables must stay within the
+/// limit, each name must be unique and it must be parsed as a variable by
+/// `BooleanExpression::try_from` (so, for example, `true` or `a b` are rejected).
+fn check_new_names(existing: &HashMap<String, u16>, names: &[&str]) -> Result<(), String> {
+    if existing.len() + names.len() >= usize::from(u16::MAX - 1) {
+        return Err(format!(
+            "Too many BDD variables. There can be at most {} variables.",
+            u16::MAX - 1
+        ));
+    }
+    let mut unique: HashSet<&str> = HashSet::new();
+    for name in names {
+        if existing.contains_key(*name) || !unique.insert(name) {
+            return Err(format!("BDD variable `{}` already exists.", name));
+        }
+        let expected = BooleanExpression::Variable(name.to_string());
+        if BooleanExpression::try_from(*name).as_ref() != Ok(&expected) {
+            return Err(format!("Variable name `{}` is invalid.", name));
+        }
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -118,6 +429,57 @@ mod tests {
         assert_eq!(Some(BddVariable(4)), universe.var_by_name("x_4"));
     }
 
+    #[test]
+    fn bdd_universe_add_variables() {
+        let mut variables = BddVariableSet::new(vec!["a", "b"]);
+        let a_and_b = variables.eval_expression_string("a & b");
+        let new = variables.add_variables(vec!["c", "d"]).unwrap();
+        assert_eq!(vec![BddVariable(2), BddVariable(3)], new);
+        assert_eq!(4, variables.num_vars());
+        assert_eq!(Some(BddVariable(3)), variables.var_by_name("d"));
+        assert_eq!("c", variables.name_of(BddVariable(2)));
+        let mut extended = a_and_b;
+        extended.extend_num_vars(variables.num_vars());
+        assert_eq!(variables.eval_expression_string("a & b"), extended);
+        assert_eq!(
+            variables.eval_expression_string("a & b & !c"),
+            extended.and(&variables.mk_not_var_by_name("c"))
+        );
+    }
+
+    #[test]
+    fn bdd_universe_try_new() {
+        let variables = BddVariableSet::try_new(vec!["a", "b_1", "c.d"]).unwrap();
+        assert_eq!(3, variables.num_vars());
+        assert_eq!(Some(BddVariable(1)), variables.var_by_name("b_1"));
+        assert!(BddVariableSet::try_new(Vec::new()).is_ok());
+        for names in [
+            vec!["a", "b", "a"],
+            vec!["a", "b&c"],
+            vec!["a", "b c"],
+            vec![""],
+            vec!["true"],
+        ]
+        .iter()
+        {
+            assert!(BddVariableSet::try_new(names.clone()).is_err());
+        }
+    }
+
+    #[test]
+    fn bdd_universe_add_invalid_variable() {
+        let mut variables = BddVariableSet::new(vec!["a", "b"]);
+        for name in ["a", "b&c", "b c", "", "true"].iter() {
+            assert!(variables.add_variable(name).is_err());
+        }
+        // Nothing is added when one of the names is not valid.
+        assert!(variables.add_variables(vec!["c", "d", "c"]).is_err());
+        assert!(variables.add_variables(vec!["c", "d e"]).is_err());
+        assert_eq!(2, variables.num_vars());
+        assert_eq!(None, variables.var_by_name("c"));
+        assert_eq!(Ok(BddVariable(2)), variables.add_variable("c"));
+    }
+
     #[test]
     fn bdd_universe_mk_const() {
         let variables = mk_5_variable_set();
@@ -154,4 +516,112 @@ mod tests {
     fn bdd_universe_mk_not_var_by_name_invalid_name() {
         mk_5_variable_set().mk_not_var_by_name("abc");
     }
+
+    #[test]
+    fn bdd_universe_mk_conjunctive_clause() {
+        let variables = mk_5_variable_set();
+        let valuation =
+            BddPartialValuation::from_values(&[(BddVariable(3), false), (BddVariable(1), true)]);
+        assert_eq!(
+            variables.eval_expression_string("v2 & !v4"),
+            variables.mk_conjunctive_clause(&valuation)
+        );
+        assert!(variables
+            .mk_conjunctive_clause(&BddPartialValuation::empty())
+            .is_true());
+    }
+
+    #[test]
+    fn bdd_universe_mk_disjunctive_clause() {
+        let variables = mk_5_variable_set();
+        let valuation =
+            BddPartialValuation::from_values(&[(BddVariable(3), false), (BddVariable(1), true)]);
+        assert_eq!(
+            variables.eval_expression_string("v2 | !v4"),
+            variables.mk_disjunctive_clause(&valuation)
+        );
+        assert!(variables
+            .mk_disjunctive_clause(&BddPartialValuation::empty())
+            .is_false());
+    }
+
+    #[test]
+    fn bdd_universe_mk_decision_list() {
+        let variables = mk_5_variable_set();
+        let v = variables.variables();
+        let rule = |values: &[(BddVariable, bool)], result: bool| {
+            (BddPartialValuation::from_values(values), result)
+        };
+        let rules = vec![
+            rule(&[(v[0], true), (v[1], true)], false),
+            rule(&[(v[0], true)], true),
+            rule(&[(v[2], false), (v[3], true)], true),
+            rule(&[(v[4], true)], false),
+            rule(&[(v[3], true)], true),
+            rule(&[], false),
+            rule(&[(v[1], true)], true),
+        ];
+        // Compare with nested if-then-else (built from the last rule) for every prefix.
+        for i in 0..=rules.len() {
+            let mut expected = variables.mk_false();
+            for (cube, value) in rules[..i].iter().rev() {
+                let cube = variables.mk_conjunctive_clause(cube);
+                expected = if *value {
+                    cube.or(&expected)
+                } else {
+                    expected.and_not(&cube)
+                };
+            }
+            assert_eq!(expected, variables.mk_decision_list(&rules[..i]));
+        }
+        assert_eq!(
+            variables.eval_expression_string("(v1 & !v2) | (!v1 & !v3 & v4) | (!v1 & !v5 & v4)"),
+            variables.mk_decision_list(&rules)
+        );
+    }
+
+    #[test]
+    fn bdd_universe_estimated_overhead() {
+        let small = BddVariableSet::new_anonymous(2);
+        let large = BddVariableSet::new_anonymous(200);
+        assert!(small.estimated_overhead() > 0);
+        assert!(small.estimated_overhead() < large.estimated_overhead());
+        // At least the names are stored twice.
+        assert!(large.estimated_overhead() > 2 * 200 * "x_0".len());
+    }
+
+    #[test]
+    fn bdd_universe_transfer_from() {
+        let source = BddVariableSet::new(vec!["a", "b", "c"]);
+        let target = BddVariableSet::new(vec!["d", "c", "x", "a", "b"]);
+        let bdd = source.eval_expression_string("(a => b) & (b ^ c)");
+        let transferred = target.transfer_from(&bdd, &source).unwrap();
+        assert_eq!(
+            target.eval_expression_string("(a => b) & (b ^ c)"),
+            transferred
+        );
+        assert_eq!(
+            Some(bdd.clone()),
+            source.transfer_from(&transferred, &target)
+        );
+        assert!(target
+            .transfer_from(&source.mk_false(), &source)
+            .unwrap()
+            .is_false());
+        // The variable `x` does not exist in the source set.
+        let missing = target.eval_expression_string("x | a");
+        assert_eq!(None, source.transfer_from(&missing, &target));
+        assert_eq!(
+            Some(source.mk_true()),
+            source.transfer_from(&target.mk_true(), &target)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn bdd_universe_transfer_from_wrong_source() {
+        let source = BddVariableSet::new(vec!["a", "b", "c"]);
+        let target = BddVariableSet::new(vec!["a", "b"]);
+        target.transfer_from(&target.mk_true(), &source);
+    }
 }
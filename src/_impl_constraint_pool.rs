@@ -0,0 +1,184 @@
+use crate::{Bdd, BddPartialValuation, BddVariable, ConstraintPool};
+use std::collections::HashSet;
+
+/// **(internal)** Default maximal size of a `Bdd` created by merging constraints.
+const DEFAULT_MERGE_LIMIT: usize = 10_000;
+
+impl ConstraintPool {
+    /// Create an empty pool (representing the `true` constraint).
+    pub fn new() -> ConstraintPool {
+        ConstraintPool::with_merge_limit(DEFAULT_MERGE_LIMIT)
+    }
+
+    /// Create an empty pool where constraints are only merged if the result has at most
+    /// `merge_limit` nodes. With limit `0`, constraints are never merged.
+    pub fn with_merge_limit(merge_limit: usize) -> ConstraintPool {
+        ConstraintPool {
+            constraints: Vec::new(),
+            merge_limit,
+        }
+    }
+
+    /// The constraints currently stored in this pool (their conjunction is the represented
+    /// constraint).
+    pub fn constraints(&self) -> &[Bdd] {
+        &self.constraints
+    }
+
+    /// Add a new constraint to this pool.
+    ///
+    /// The constraint is conjoined with the stored constraint that shares the most variables
+    /// with it, if the result is not larger than both of them together and not larger than
+    /// the merge limit. Otherwise, it is stored separately.
+    pub fn add(&mut self, constraint: Bdd) {
+        if constraint.is_true() || self.is_trivially_false() {
+            return;
+        }
+        if constraint.is_false() {
+            self.constraints = vec![constraint];
+            return;
+        }
+        let support = support_set(&constraint);
+        let candidate = self
+            .constraints
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (support_set(c).intersection(&support).count(), i))
+            .filter(|(shared, _)| *shared > 0)
+            .max_by_key(|(shared, i)| (*shared, std::cmp::Reverse(*i)));
+        if let Some((_, i)) = candidate {
+            let merged = self.constraints[i].and(&constraint);
+            if merged.is_false() {
+                self.constraints = vec![merged];
+                return;
+            }
+            let size_limit = self
+                .merge_limit
+                .min(self.constraints[i].size() + constraint.size());
+            if merged.size() <= size_limit {
+                self.constraints[i] = merged;
+                return;
+            }
+        }
+        self.constraints.push(constraint);
+    }
+
+    /// Check whether the constraints are satisfiable when the variables fixed in `assumptions`
+    /// have the given values.
+    pub fn is_sat_under(&self, assumptions: &BddPartialValuation) -> bool {
+        let restricted: Vec<Bdd> = self
+            .constraints
+            .iter()
+            .map(|c| c.restrict(assumptions))
+            .collect();
+        if restricted.iter().any(|c| c.is_false()) {
+            return false;
+        }
+        match conjoin_until_false(restricted.iter().collect(), None) {
+            Some(result) => !result.is_false(),
+            None => true,
+        }
+    }
+
+    /// Check whether the constraints imply the given `property`, i.e. whether every valuation
+    /// satisfying all the constraints also satisfies the `property`.
+    pub fn implied(&self, property: &Bdd) -> bool {
+        let counterexamples =
+            conjoin_until_false(self.constraints.iter().collect(), Some(property.not()));
+        matches!(counterexamples, Some(bdd) if bdd.is_false())
+    }
+
+    /// Compute the conjunction of all constraints in this pool.
+    ///
+    /// *Panics:* The pool cannot be empty, since the number of variables is not known.
+    pub fn to_bdd(&self) -> Bdd {
+        conjoin_until_false(self.constraints.iter().collect(), None)
+            .expect("Cannot build a Bdd from an empty constraint pool.")
+    }
+
+    /// **(internal)** True if the pool contains only the `false` constraint.
+    fn is_trivially_false(&self) -> bool {
+        self.constraints.len() == 1 && self.constraints[0].is_false()
+    }
+}
+
+impl Default for ConstraintPool {
+    fn default() -> Self {
+        ConstraintPool::new()
+    }
+}
+
+/// **(internal)** Conjoin the `initial` value (if given) with all `constraints`, starting
+/// with the smallest ones and stopping once the result is `false`. Returns `None` if there
+/// is nothing to conjoin.
+fn conjoin_until_false(mut constraints: Vec<&Bdd>, initial: Option<Bdd>) -> Option<Bdd> {
+    constraints.sort_by_key(|c| c.size());
+    let mut result = initial;
+    for constraint in constraints {
+        let next = match result {
+            None => constraint.clone(),
+            Some(result) => result.and(constraint),
+        };
+        if next.is_false() {
+            return Some(next);
+        }
+        result = Some(next);
+    }
+    result
+}
+
+/// **(internal)** Set of all variables which appear in the decision nodes of the `Bdd`.
+fn support_set(bdd: &Bdd) -> HashSet<BddVariable> {
+    bdd.nodes().skip(2).map(|node| node.var).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{BddPartialValuation, BddVariable, ConstraintPool};
+
+    #[test]
+    fn constraint_pool_queries() {
+        let variables = mk_5_variable_set();
+        let constraints = ["v1 => v2", "v2 => v3", "v4 | v5", "!v4 | !v5"];
+        let mut pool = ConstraintPool::with_merge_limit(0);
+        let mut merged_pool = ConstraintPool::new();
+        for c in constraints.iter() {
+            pool.add(variables.eval_expression_string(c));
+            merged_pool.add(variables.eval_expression_string(c));
+        }
+        assert_eq!(4, pool.constraints().len());
+        assert_eq!(2, merged_pool.constraints().len());
+        let expected =
+            variables.eval_expression_string("(v1 => v2) & (v2 => v3) & (v4 | v5) & (!v4 | !v5)");
+        for pool in [pool, merged_pool].iter() {
+            assert_eq!(expected, pool.to_bdd());
+            assert!(pool.implied(&variables.eval_expression_string("v1 => v3")));
+            assert!(pool.implied(&variables.eval_expression_string("v4 ^ v5")));
+            assert!(!pool.implied(&variables.eval_expression_string("v3 => v1")));
+            let (v1, v3, v4) = (BddVariable(0), BddVariable(2), BddVariable(3));
+            let valuation = BddPartialValuation::from_values(&[(v1, true), (v4, true)]);
+            assert!(pool.is_sat_under(&valuation));
+            let valuation = BddPartialValuation::from_values(&[(v1, true), (v3, false)]);
+            assert!(!pool.is_sat_under(&valuation));
+            assert!(pool.is_sat_under(&BddPartialValuation::empty()));
+        }
+    }
+
+    #[test]
+    fn constraint_pool_trivial() {
+        let variables = mk_5_variable_set();
+        let mut pool = ConstraintPool::new();
+        assert!(pool.is_sat_under(&BddPartialValuation::empty()));
+        assert!(pool.implied(&variables.mk_true()));
+        assert!(!pool.implied(&variables.mk_var(BddVariable(0))));
+        pool.add(variables.mk_true());
+        assert!(pool.constraints().is_empty());
+        pool.add(variables.eval_expression_string("v1 & v2"));
+        pool.add(variables.eval_expression_string("v3"));
+        pool.add(variables.eval_expression_string("!v1"));
+        assert!(pool.to_bdd().is_false());
+        assert_eq!(1, pool.constraints().len());
+        assert!(pool.implied(&variables.mk_false()));
+    }
+}
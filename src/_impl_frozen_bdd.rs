@@ -0,0 +1,239 @@
+use crate::{Bdd, BddValuation, BddVariable, FrozenBdd};
+#[cfg(feature = "sampling")]
+use rand::Rng;
+
+impl Bdd {
+    /// Convert this `Bdd` into a `FrozenBdd`, which precomputes the (relative and exact)
+    /// number of satisfying valuations of every node and stores the nodes in a layout which
+    /// is faster to evaluate.
+    ///
+    /// The preprocessing takes linear time and roughly quadruples the memory of the `Bdd`, but
+    /// afterwards, counting is constant-time and indexing or sampling a satisfying valuation
+    /// only takes one pass from the root to a terminal.
+    pub fn freeze(self) -> FrozenBdd {
+        let mut vars: Vec<u16> = Vec::with_capacity(self.size());
+        let mut links: Vec<[u32; 2]> = Vec::with_capacity(self.size());
+        let mut densities: Vec<f64> = Vec::with_capacity(self.size());
+        for node in self.nodes() {
+            vars.push(node.var.0);
+            links.push([node.low_link.0, node.high_link.0]);
+        }
+        densities.push(0.0);
+        if self.size() > 1 {
+            densities.push(1.0);
+        }
+        for node in self.nodes().skip(2) {
+            // Skipped variables do not change the fraction of satisfying valuations.
+            let low = densities[node.low_link.to_index()];
+            let high = densities[node.high_link.to_index()];
+            densities.push((low + high) / 2.0);
+        }
+        let counts = self.exact_node_counts();
+        FrozenBdd {
+            bdd: self,
+            vars,
+            links,
+            densities,
+            counts,
+        }
+    }
+}
+
+impl FrozenBdd {
+    /// The underlying `Bdd`.
+    pub fn as_bdd(&self) -> &Bdd {
+        &self.bdd
+    }
+
+    /// Drop the precomputed data and return the underlying `Bdd`.
+    pub fn into_bdd(self) -> Bdd {
+        self.bdd
+    }
+
+    /// Evaluate the function in the given valuation (same as `Bdd::eval_in`).
+    pub fn eval_in(&self, valuation: &BddValuation) -> bool {
+        let mut node = self.links.len() - 1;
+        while node > 1 {
+            let value = valuation[BddVariable(self.vars[node])];
+            node = self.links[node][usize::from(value)] as usize;
+        }
+        node == 1
+    }
+
+    /// The number of satisfying valuations (same as `Bdd::cardinality`, including
+    /// the saturation to `f64::INFINITY`), computed in constant time.
+    pub fn cardinality(&self) -> f64 {
+        let root = self.densities[self.densities.len() - 1];
+        if root == 0.0 {
+            0.0
+        } else {
+            root * 2.0_f64.powi(i32::from(self.bdd.num_vars()))
+        }
+    }
+
+    /// The exact number of satisfying valuations, or `None` if it does not fit into `u128`
+    /// (same as `Bdd::checked_cardinality`).
+    pub fn checked_cardinality(&self) -> Option<u128> {
+        if self.bdd.is_false() {
+            return Some(0);
+        }
+        let counts = self.counts.as_ref()?;
+        Some(counts[counts.len() - 1] << self.vars[self.vars.len() - 1])
+    }
+
+    /// Return the satisfying valuation at position `index` in the order of
+    /// `Bdd::sat_valuations` (lexicographic), or `None` if there are not enough satisfying
+    /// valuations (or their number does not fit into `u128`).
+    pub fn sat_valuation_at(&self, index: u128) -> Option<BddValuation> {
+        let counts = self.counts.as_ref()?;
+        if index >= self.checked_cardinality()? {
+            return None;
+        }
+        let num_vars = self.bdd.num_vars();
+        // Number of satisfying valuations of `node` for variables starting at `var`.
+        let count_from = |node: usize, var: u16| {
+            if node == 0 {
+                0
+            } else {
+                counts[node] << (self.vars[node] - var)
+            }
+        };
+        let mut valuation = BddValuation::all_false(num_vars);
+        let mut remaining = index;
+        let mut node = self.links.len() - 1;
+        for var in 0..num_vars {
+            let (low, high) = if self.vars[node] == var {
+                (self.links[node][0] as usize, self.links[node][1] as usize)
+            } else {
+                (node, node)
+            };
+            let low_count = count_from(low, var + 1);
+            if remaining < low_count {
+                node = low;
+            } else {
+                remaining -= low_count;
+                valuation.set(BddVariable(var));
+                node = high;
+            }
+        }
+        Some(valuation)
+    }
+
+    /// Pick a satisfying valuation uniformly at random, or `None` if the function is `false`.
+    ///
+    /// Requires the `sampling` feature.
+    #[cfg(feature = "sampling")]
+    pub fn random_valuation<R: Rng>(&self, rng: &mut R) -> Option<BddValuation> {
+        if self.bdd.is_false() {
+            return None;
+        }
+        let num_vars = self.bdd.num_vars();
+        let mut valuation = BddValuation::all_false(num_vars);
+        let mut node = self.links.len() - 1;
+        for var in 0..num_vars {
+            let value = if self.vars[node] == var {
+                let [low, high] = self.links[node];
+                let (low_density, high_density) =
+                    (self.densities[low as usize], self.densities[high as usize]);
+                let value = if low_density + high_density > 0.0 {
+                    rng.gen_bool(high_density / (low_density + high_density))
+                } else {
+                    // Both densities underflow, so just avoid the zero terminal.
+                    low == 0
+                };
+                node = self.links[node][usize::from(value)] as usize;
+                value
+            } else {
+                rng.gen_bool(0.5)
+            };
+            if value {
+                valuation.set(BddVariable(var));
+            }
+        }
+        Some(valuation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{BddValuation, BddValuationIterator, BddVariableSet};
+    #[cfg(feature = "sampling")]
+    use rand::rngs::StdRng;
+    #[cfg(feature = "sampling")]
+    use rand::SeedableRng;
+    #[cfg(feature = "sampling")]
+    use std::collections::HashMap;
+
+    #[test]
+    fn frozen_bdd_queries() {
+        let variables = mk_5_variable_set();
+        for expression in &[
+            "(v1 => v5) & (v2 | !v4)",
+            "v3 ^ v5",
+            "v2 & !v4",
+            "true",
+            "false",
+        ] {
+            let bdd = variables.eval_expression_string(expression);
+            let frozen = bdd.clone().freeze();
+            assert_eq!(bdd.cardinality(), frozen.cardinality());
+            assert_eq!(bdd.checked_cardinality(), frozen.checked_cardinality());
+            for valuation in BddValuationIterator::new(5) {
+                assert_eq!(bdd.eval_in(&valuation), frozen.eval_in(&valuation));
+            }
+            let indexed: Vec<BddValuation> = (0..)
+                .map(|i| frozen.sat_valuation_at(i))
+                .take_while(|v| v.is_some())
+                .map(|v| v.unwrap())
+                .collect();
+            let expected: Vec<BddValuation> = bdd.sat_valuations().collect();
+            assert_eq!(expected, indexed);
+            assert_eq!(bdd, frozen.into_bdd());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "sampling")]
+    fn frozen_bdd_random_valuation() {
+        let variables = mk_5_variable_set();
+        let frozen = variables
+            .eval_expression_string("(v1 & v2 & v3) | !v4")
+            .freeze();
+        assert!(variables
+            .mk_false()
+            .freeze()
+            .random_valuation(&mut StdRng::seed_from_u64(1))
+            .is_none());
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut histogram: HashMap<BddValuation, usize> = HashMap::new();
+        for _ in 0..4000 {
+            let valuation = frozen.random_valuation(&mut rng).unwrap();
+            assert!(frozen.eval_in(&valuation));
+            *histogram.entry(valuation).or_insert(0) += 1;
+        }
+        // 18 valuations, each should be sampled roughly 222 times.
+        assert_eq!(18, histogram.len());
+        assert!(histogram.values().all(|count| *count > 150 && *count < 300));
+    }
+
+    #[test]
+    fn frozen_bdd_large() {
+        let variables = BddVariableSet::new_anonymous(200);
+        let frozen = variables.mk_true().freeze();
+        assert_eq!(None, frozen.checked_cardinality());
+        assert_eq!(None, frozen.sat_valuation_at(0));
+        assert_eq!(2.0_f64.powi(200), frozen.cardinality());
+    }
+
+    #[test]
+    #[cfg(feature = "sampling")]
+    fn frozen_bdd_large_random_valuation() {
+        let variables = BddVariableSet::new_anonymous(200);
+        let frozen = variables.eval_expression_string("x_10 & !x_150").freeze();
+        let valuation = frozen
+            .random_valuation(&mut StdRng::seed_from_u64(3))
+            .unwrap();
+        assert!(frozen.eval_in(&valuation));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{Bdd, TypedBdd};
+use std::fmt::{Debug, Display, Error, Formatter};
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+impl<U> TypedBdd<U> {
+    /// Tag the given `Bdd` with the universe `U`.
+    ///
+    /// It is up to the caller to make sure the `Bdd` was actually created in the
+    /// `BddVariableSet` represented by `U`.
+    pub fn from_bdd(bdd: Bdd) -> TypedBdd<U> {
+        TypedBdd {
+            bdd,
+            universe: PhantomData,
+        }
+    }
+
+    /// Reference to the underlying untyped `Bdd`.
+    pub fn as_bdd(&self) -> &Bdd {
+        &self.bdd
+    }
+
+    /// Remove the universe tag, returning the underlying `Bdd`.
+    pub fn into_bdd(self) -> Bdd {
+        self.bdd
+    }
+
+    /// Apply a function that transforms the underlying `Bdd` within the same universe.
+    pub fn map<F>(&self, function: F) -> TypedBdd<U>
+    where
+        F: FnOnce(&Bdd) -> Bdd,
+    {
+        TypedBdd::from_bdd(function(&self.bdd))
+    }
+
+    /// Same as `Bdd::not`.
+    pub fn not(&self) -> TypedBdd<U> {
+        self.map(Bdd::not)
+    }
+
+    /// Same as `Bdd::and`, but only for `Bdd`s from the same universe.
+    pub fn and(&self, right: &TypedBdd<U>) -> TypedBdd<U> {
+        self.map(|bdd| bdd.and(&right.bdd))
+    }
+
+    /// Same as `Bdd::or`, but only for `Bdd`s from the same universe.
+    pub fn or(&self, right: &TypedBdd<U>) -> TypedBdd<U> {
+        self.map(|bdd| bdd.or(&right.bdd))
+    }
+
+    /// Same as `Bdd::imp`, but only for `Bdd`s from the same universe.
+    pub fn imp(&self, right: &TypedBdd<U>) -> TypedBdd<U> {
+        self.map(|bdd| bdd.imp(&right.bdd))
+    }
+
+    /// Same as `Bdd::iff`, but only for `Bdd`s from the same universe.
+    pub fn iff(&self, right: &TypedBdd<U>) -> TypedBdd<U> {
+        self.map(|bdd| bdd.iff(&right.bdd))
+    }
+
+    /// Same as `Bdd::xor`, but only for `Bdd`s from the same universe.
+    pub fn xor(&self, right: &TypedBdd<U>) -> TypedBdd<U> {
+        self.map(|bdd| bdd.xor(&right.bdd))
+    }
+
+    /// Same as `Bdd::and_not`, but only for `Bdd`s from the same universe.
+    pub fn and_not(&self, right: &TypedBdd<U>) -> TypedBdd<U> {
+        self.map(|bdd| bdd.and_not(&right.bdd))
+    }
+
+    /// Same as `Bdd::binary_op`, but only for `Bdd`s from the same universe.
+    pub fn binary_op<T>(left: &TypedBdd<U>, right: &TypedBdd<U>, op_function: T) -> TypedBdd<U>
+    where
+        T: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        left.map(|bdd| Bdd::binary_op(bdd, &right.bdd, op_function))
+    }
+}
+
+/// Typed `Bdd`s can be always converted back to untyped `Bdd`s.
+impl<U> From<TypedBdd<U>> for Bdd {
+    fn from(bdd: TypedBdd<U>) -> Self {
+        bdd.bdd
+    }
+}
+
+impl<U> AsRef<Bdd> for TypedBdd<U> {
+    fn as_ref(&self) -> &Bdd {
+        &self.bdd
+    }
+}
+
+/*
+   Standard traits are implemented manually, because derive would require the marker
+   type `U` to implement them as well.
+*/
+
+impl<U> Clone for TypedBdd<U> {
+    fn clone(&self) -> Self {
+        TypedBdd::from_bdd(self.bdd.clone())
+    }
+}
+
+impl<U> PartialEq for TypedBdd<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bdd == other.bdd
+    }
+}
+
+impl<U> Eq for TypedBdd<U> {}
+
+impl<U> Hash for TypedBdd<U> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bdd.hash(state)
+    }
+}
+
+impl<U> Debug for TypedBdd<U> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.debug_tuple("TypedBdd").field(&self.bdd).finish()
+    }
+}
+
+impl<U> Display for TypedBdd<U> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Display::fmt(&self.bdd, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::_test_util::mk_5_variable_set;
+    use crate::{Bdd, TypedBdd};
+
+    struct Universe;
+
+    #[test]
+    fn typed_bdd_operations() {
+        let variables = mk_5_variable_set();
+        let v1 = variables.mk_var_by_name("v1");
+        let v2 = variables.mk_var_by_name("v2");
+        let t1: TypedBdd<Universe> = TypedBdd::from_bdd(v1.clone());
+        let t2: TypedBdd<Universe> = TypedBdd::from_bdd(v2.clone());
+
+        assert_eq!(&v1.not(), t1.not().as_bdd());
+        assert_eq!(v1.and(&v2), t1.and(&t2).into_bdd());
+        assert_eq!(v1.or(&v2), t1.or(&t2).into_bdd());
+        assert_eq!(v1.imp(&v2), t1.imp(&t2).into_bdd());
+        assert_eq!(v1.iff(&v2), t1.iff(&t2).into_bdd());
+        assert_eq!(v1.xor(&v2), t1.xor(&t2).into_bdd());
+        assert_eq!(v1.and_not(&v2), Bdd::from(t1.and_not(&t2)));
+        assert_eq!(
+            v1.xor(&v2),
+            TypedBdd::binary_op(&t1, &t2, crate::op_function::xor).into_bdd()
+        );
+        assert_eq!(t1.clone(), t1);
+        assert_eq!(v1.to_string(), t1.to_string());
+        assert_eq!(
+            std::mem::size_of::<Bdd>(),
+            std::mem::size_of::<TypedBdd<Universe>>()
+        );
+    }
+}
@@ -2,16 +2,16 @@ use crate::_test_util::{mk_5_variable_set, mk_small_test_bdd};
 use crate::*;
 
 fn v1() -> BddVariable {
-    return BddVariable(0);
+    BddVariable(0)
 }
 fn v2() -> BddVariable {
-    return BddVariable(1);
+    BddVariable(1)
 }
 fn v3() -> BddVariable {
-    return BddVariable(2);
+    BddVariable(2)
 }
 fn v4() -> BddVariable {
-    return BddVariable(3);
+    BddVariable(3)
 }
 
 #[test]
@@ -280,3 +280,304 @@ fn invert_input() {
     )
     .is_true());
 }
+
+#[test]
+fn same_operand_shortcuts() {
+    let variables = mk_5_variable_set();
+    let tt = variables.mk_true();
+    let bdd = variables.eval_expression_string("(v1 => (v2 <=> v3)) & (!v1 => !(v2 <=> v5))");
+    let flip = |bdd: &Bdd, var: BddVariable| {
+        Bdd::fused_binary_flip_op((bdd, Some(var)), (&tt, None), None, crate::op_function::and)
+    };
+
+    assert_eq!(bdd.not(), bdd.xor(&tt).iff(&bdd.xor(&tt)).and_not(&bdd));
+    assert_eq!(bdd, bdd.iff(&bdd).and(&bdd));
+    assert_eq!(
+        flip(&bdd, v2()),
+        Bdd::fused_binary_flip_op(
+            (&bdd, None),
+            (&bdd, None),
+            Some(v2()),
+            crate::op_function::or
+        )
+    );
+    for var in [v1(), v2(), v3()].iter() {
+        let flipped = flip(&bdd, *var);
+        assert_eq!(
+            bdd.and_not(&flipped),
+            Bdd::fused_binary_flip_op(
+                (&bdd, None),
+                (&bdd, Some(*var)),
+                None,
+                crate::op_function::and_not
+            )
+        );
+        assert_eq!(
+            bdd.xor(&flipped),
+            Bdd::fused_binary_flip_op(
+                (&bdd, None),
+                (&bdd, Some(*var)),
+                None,
+                crate::op_function::xor
+            )
+        );
+        assert!(Bdd::fused_binary_flip_op(
+            (&bdd, Some(*var)),
+            (&bdd, Some(*var)),
+            None,
+            crate::op_function::iff
+        )
+        .is_true());
+    }
+}
+
+#[test]
+fn constant_operand_shortcuts() {
+    let variables = mk_5_variable_set();
+    let tt = variables.mk_true();
+    let ff = variables.mk_false();
+    let bdd = variables.eval_expression_string("(v1 => (v2 <=> v3)) & (!v1 => !(v2 <=> v5))");
+
+    // Neutral constants.
+    assert_eq!(bdd, tt.and(&bdd));
+    assert_eq!(bdd, bdd.or(&ff));
+    assert_eq!(bdd, tt.imp(&bdd));
+    assert_eq!(bdd, bdd.iff(&tt));
+    assert_eq!(bdd, bdd.and_not(&ff));
+    // Negating constants.
+    assert_eq!(bdd.not(), bdd.xor(&tt));
+    assert_eq!(bdd.not(), ff.iff(&bdd));
+    assert_eq!(bdd.not(), bdd.imp(&ff));
+    assert_eq!(bdd.not(), tt.and_not(&bdd));
+    // Absorbing constants.
+    assert!(ff.and(&bdd).is_false());
+    assert!(bdd.or(&tt).is_true());
+    assert!(ff.imp(&bdd).is_true());
+    // The node limit still applies.
+    assert_eq!(None, tt.checked_and(&bdd, bdd.size() - 1));
+    assert_eq!(Some(bdd.clone()), tt.checked_and(&bdd, bdd.size()));
+    // Flipped operands are not covered by the shortcut.
+    let flipped = Bdd::fused_binary_flip_op(
+        (&tt, None),
+        (&bdd, Some(v2())),
+        None,
+        crate::op_function::and,
+    );
+    assert_ne!(bdd, flipped);
+    assert_eq!(
+        flipped,
+        Bdd::fused_binary_flip_op(
+            (&bdd, Some(v2())),
+            (&ff, None),
+            None,
+            crate::op_function::or
+        )
+    );
+}
+
+#[test]
+fn bdd_assign_operations() {
+    let variables = mk_5_variable_set();
+    let a = variables.eval_expression_string("v1 & (v2 | !v3)");
+    let b = variables.eval_expression_string("v3 ^ v4");
+    let tt = variables.mk_true();
+    let ff = variables.mk_false();
+    type BinaryOp = fn(&Bdd, &Bdd) -> Bdd;
+    type AssignOp = fn(&mut Bdd, &Bdd);
+    let ops: [(BinaryOp, AssignOp); 6] = [
+        (Bdd::and, Bdd::and_assign),
+        (Bdd::or, Bdd::or_assign),
+        (Bdd::imp, Bdd::imp_assign),
+        (Bdd::iff, Bdd::iff_assign),
+        (Bdd::xor, Bdd::xor_assign),
+        (Bdd::and_not, Bdd::and_not_assign),
+    ];
+    for (op, op_assign) in ops.iter() {
+        for left in [&a, &b, &tt, &ff].iter() {
+            for right in [&a, &b, &tt, &ff].iter() {
+                let mut result = (*left).clone();
+                op_assign(&mut result, right);
+                assert_eq!(op(left, right), result);
+            }
+        }
+    }
+    for bdd in [&a, &tt, &ff].iter() {
+        let mut result = (*bdd).clone();
+        result.not_assign();
+        assert_eq!(bdd.not(), result);
+    }
+}
+
+#[test]
+#[should_panic]
+fn bdd_and_cube_operand_mismatch() {
+    // The right operand is a cube, which is handled without the general `apply`.
+    let variables = mk_5_variable_set();
+    let larger = BddVariableSet::new_anonymous(10);
+    variables.mk_var(v1()).and(&larger.mk_var(v2()));
+}
+
+#[test]
+fn bdd_and_cube() {
+    let variables = mk_5_variable_set();
+    let functions = [
+        "v1 & (v2 | !v3)",
+        "(v3 ^ v4) | (v1 <=> v5)",
+        "v2 & !v5",
+        "v1 | v2 | v3 | v4 | v5",
+        "true",
+        "false",
+    ];
+    let cubes = ["v3", "!v1 & v2", "v1 & !v3 & v5", "v2 & v3 & v4", "!v5"];
+    for f in functions.iter() {
+        let f = variables.eval_expression_string(f);
+        for c in cubes.iter() {
+            let c = variables.eval_expression_string(c);
+            let expected = Bdd::binary_op(&f, &c, op_function::and);
+            assert_eq!(expected, f.and(&c));
+            assert_eq!(expected, c.and(&f));
+        }
+    }
+}
+
+#[test]
+fn bdd_and_cube_valuation() {
+    let variables = mk_5_variable_set();
+    let f = variables.eval_expression_string("(v3 ^ v4) | (v1 <=> v5)");
+    let mut cube = BddPartialValuation::empty();
+    assert_eq!(f, f.and_cube(&cube));
+    cube.set_value(v4(), true);
+    cube.set_value(v1(), false);
+    assert_eq!(
+        variables.eval_expression_string("!v1 & v4 & (!v3 | !v5)"),
+        f.and_cube(&cube)
+    );
+    cube.set_value(v3(), true);
+    cube.set_value(v2(), true);
+    assert!(variables.mk_false().and_cube(&cube).is_false());
+    assert_eq!(
+        variables.mk_conjunctive_clause(&cube),
+        variables.mk_true().and_cube(&cube)
+    );
+}
+
+#[test]
+fn bdd_equivalent_under() {
+    let variables = mk_5_variable_set();
+    let a = variables.eval_expression_string("(v1 & v2) | v3");
+    let b = variables.eval_expression_string("(v1 | v3) & (v2 | v3)");
+    let c = variables.eval_expression_string("v1 | v3");
+    let care = variables.eval_expression_string("v2 | v4");
+    assert!(a.equivalent_under(&b, &variables.mk_true()));
+    assert!(!a.equivalent_under(&c, &variables.mk_true()));
+    assert!(!a.equivalent_under(&c, &care));
+    assert!(a.equivalent_under(&c, &variables.mk_var(v2())));
+    assert!(a.equivalent_under(&c, &variables.mk_false()));
+    assert!(variables
+        .mk_true()
+        .equivalent_under(&variables.mk_false(), &variables.mk_false()));
+    assert!(!variables
+        .mk_true()
+        .equivalent_under(&variables.mk_false(), &care));
+
+    // Compare with the explicit definition on all pairs of a few functions.
+    let functions: Vec<Bdd> = [
+        "v1 ^ v2",
+        "v1 & !v4",
+        "v2 => v5",
+        "(v1 & v2) | (v3 & v5)",
+        "!v3 | v4",
+        "true",
+        "false",
+    ]
+    .iter()
+    .map(|e| variables.eval_expression_string(e))
+    .collect();
+    for x in &functions {
+        for y in &functions {
+            for care in &functions {
+                let expected = x.xor(y).and(care).is_false();
+                assert_eq!(expected, x.equivalent_under(y, care));
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn bdd_and_assign_constant_mismatch() {
+    let variables = mk_5_variable_set();
+    let mut bdd = variables.mk_var(v1());
+    bdd.and_assign(&BddVariableSet::new_anonymous(10).mk_true());
+}
+
+#[test]
+fn bdd_checked_ops_limit() {
+    let variables = mk_5_variable_set();
+    let functions: Vec<Bdd> = [
+        "v1 ^ v3",
+        "v2 ^ v4 ^ v5",
+        "(v1 & v2) | (v3 & v5)",
+        "v4",
+        "true",
+        "false",
+    ]
+    .iter()
+    .map(|e| variables.eval_expression_string(e))
+    .collect();
+    for x in &functions {
+        for y in &functions {
+            let expected = x.xor(y);
+            assert_eq!(Some(expected.clone()), x.checked_xor(y, expected.size()));
+            assert_eq!(None, x.checked_xor(y, expected.size() - 1));
+            let expected = x.and(y);
+            assert_eq!(Some(expected.clone()), x.checked_and(y, expected.size()));
+            assert_eq!(None, x.checked_and(y, expected.size() - 1));
+            let expected = x.imp(y);
+            assert_eq!(Some(expected.clone()), x.checked_imp(y, expected.size()));
+            assert_eq!(None, x.checked_imp(y, expected.size() - 1));
+        }
+    }
+    let a = variables.eval_expression_string("v1 | v2");
+    assert_eq!(Some(a.clone()), a.checked_or(&a, a.size()));
+    assert_eq!(None, a.checked_iff(&a, 1));
+    assert_eq!(Some(variables.mk_false()), a.checked_and_not(&a, 1));
+    assert_eq!(
+        Some(a.and(&a.not())),
+        Bdd::checked_binary_op(&a, &a.not(), op_function::and, 1)
+    );
+}
+
+#[test]
+fn bdd_apply_progress_reports() {
+    struct Reports(Vec<(usize, usize, usize)>);
+    impl ApplyProgress for Reports {
+        fn interval(&self) -> usize {
+            10
+        }
+        fn report(&mut self, processed_tasks: usize, pending_tasks: usize, result_size: usize) {
+            self.0.push((processed_tasks, pending_tasks, result_size));
+        }
+    }
+
+    let variables = BddVariableSet::new_anonymous(12);
+    let v = variables.variables();
+    let a = variables.mk_block_equality(&v[..6], &v[6..]);
+    let b = variables.mk_block_hamming_at_most(&v[..6], &v[6..], 1);
+    let mut reports = Reports(Vec::new());
+    assert_eq!(a.or(&b), a.or_with_progress(&b, &mut reports));
+    assert!(reports.0.len() > 5);
+    for (i, (processed, _, _)) in reports.0.iter().enumerate() {
+        assert_eq!(10 * (i + 1), *processed);
+    }
+    for pair in reports.0.windows(2) {
+        assert!(pair[0].2 <= pair[1].2);
+    }
+    assert!(reports.0.last().unwrap().2 <= a.or(&b).size());
+
+    let mut reports = Reports(Vec::new());
+    let result = Bdd::binary_op_with_progress(&a, &b, op_function::and, &mut reports);
+    assert_eq!(a.and(&b), result);
+    let mut reports = Reports(Vec::new());
+    assert_eq!(a.and(&b), a.and_with_progress(&b, &mut reports));
+}
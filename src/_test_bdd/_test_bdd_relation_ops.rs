@@ -1,5 +1,9 @@
 use crate::_test_util::{mk_5_variable_set, mk_small_test_bdd};
-use crate::{Bdd, BddVariable};
+use crate::{
+    Bdd, BddPartialValuation, BddVariable, BddVariableSet, ClusteringConfig, NodeRewrite,
+    QuantificationOrder,
+};
+use std::collections::HashMap;
 
 fn vars() -> (
     BddVariable,
@@ -8,13 +12,13 @@ fn vars() -> (
     BddVariable,
     BddVariable,
 ) {
-    return (
+    (
         BddVariable(0),
         BddVariable(1),
         BddVariable(2),
         BddVariable(3),
         BddVariable(4),
-    );
+    )
 }
 
 #[test]
@@ -79,20 +83,157 @@ fn bdd_projection_simple() {
     {
         let bdd = variables.eval_expression_string("(v1 <=> v2) & (v4 <=> v5)");
         let projected = variables.eval_expression_string("(v1 <=> v2)");
-        assert_eq!(projected, bdd.project(&vec![v4, v5]));
-        assert_eq!(bdd.project(&vec![v3, v4, v5]), bdd.project(&vec![v4, v5]));
+        assert_eq!(projected, bdd.project(&[v4, v5]));
+        assert_eq!(bdd.project(&[v3, v4, v5]), bdd.project(&[v4, v5]));
     }
     {
         let bdd = variables.eval_expression_string("(v4 => (v1 & v2)) & (!v4 => (!v1 & v3))");
         let projected_3 = variables.eval_expression_string("(v1 & v2) | (!v1 & v3)");
         let projected_2 = variables.eval_expression_string("(v1 & v2) | !v1");
-        assert_eq!(bdd, bdd.project(&vec![v5]));
-        assert_eq!(projected_3, bdd.project(&vec![v4]));
-        assert_eq!(projected_2, bdd.project(&vec![v3, v4]));
-        assert_eq!(variables.mk_true(), bdd.project(&vec![v2, v3, v4]));
+        assert_eq!(bdd, bdd.project(&[v5]));
+        assert_eq!(projected_3, bdd.project(&[v4]));
+        assert_eq!(projected_2, bdd.project(&[v3, v4]));
+        assert_eq!(variables.mk_true(), bdd.project(&[v2, v3, v4]));
+    }
+}
+
+#[test]
+fn bdd_exists() {
+    let variables = mk_5_variable_set();
+    let (v1, v2, v3, v4, v5) = vars();
+    let all = [v1, v2, v3, v4, v5];
+    for expression in &[
+        "(v1 <=> v2) & (v4 <=> v5)",
+        "(v4 => (v1 & v2)) & (!v4 => (!v1 & v3))",
+        "(v1 ^ v3 ^ v5) | (v2 & !v4)",
+        "v1 & v2 & v3 & v4 & v5",
+        "true",
+        "false",
+    ] {
+        let bdd = variables.eval_expression_string(expression);
+        for k in 0..all.len() {
+            assert_eq!(bdd.project(&all[k..]), bdd.exists(&all[k..]));
+            assert_eq!(bdd.project(&all[..k]), bdd.exists(&all[..k]));
+        }
+        assert_eq!(bdd.project(&[v2, v4]), bdd.exists(&[v4, v2, v4]));
+    }
+}
+
+#[test]
+fn bdd_exists_cube() {
+    let variables = mk_5_variable_set();
+    let (v1, v2, v3, v4, v5) = vars();
+    let bdd = variables.eval_expression_string("(v4 => (v1 & v2)) & (!v4 => (!v1 & v3))");
+    let cube = BddPartialValuation::from_values(&[(v4, true), (v2, false)]);
+    assert_eq!(bdd.exists(&[v2, v4]), bdd.exists_cube(&cube));
+    assert_eq!(bdd, bdd.exists_cube(&BddPartialValuation::empty()));
+    let cube = BddPartialValuation::from_values(&[(v1, false), (v3, true), (v5, true)]);
+    assert_eq!(bdd.exists(&[v1, v3, v5]), bdd.exists_cube(&cube));
+    assert_eq!(
+        variables.eval_expression_string("!v4"),
+        bdd.and_cube(&cube).exists_cube(&cube)
+    );
+}
+
+#[test]
+fn bdd_exists_deep_chain() {
+    // The chains are much deeper than what a recursive traversal can handle.
+    let variables = BddVariableSet::new_anonymous(60_000);
+    let all = variables.variables();
+    let chain = |last: bool| {
+        let mut values: Vec<(BddVariable, bool)> = all[1..].iter().map(|v| (*v, true)).collect();
+        values.last_mut().unwrap().1 = last;
+        variables.mk_conjunctive_clause(&BddPartialValuation::from_values(&values))
+    };
+    let first = variables.mk_var(all[0]);
+    let bdd = first.and(&chain(true));
+    assert_eq!(first, bdd.exists(&all[1..]));
+    assert!(bdd.exists(&all).is_true());
+    // Eliminating the first variable combines two chains which only differ at the end.
+    let bdd = bdd.or(&first.not().and(&chain(false)));
+    assert_eq!(chain(true).or(&chain(false)), bdd.exists(&all[..1]));
+    assert!(bdd.for_all(&all[..1]).is_false());
+}
+
+#[test]
+fn bdd_for_all() {
+    let variables = mk_5_variable_set();
+    let (v1, v2, v3, v4, v5) = vars();
+    let all = [v1, v2, v3, v4, v5];
+    for expression in &[
+        "(v1 <=> v2) | (v4 <=> v5)",
+        "(v4 => (v1 & v2)) & (!v4 => (!v1 & v3))",
+        "(v1 ^ v3 ^ v5) | (v2 & !v4)",
+        "v1 | v2 | v3 | v4 | v5",
+        "true",
+        "false",
+    ] {
+        let bdd = variables.eval_expression_string(expression);
+        for k in 0..all.len() {
+            assert_eq!(bdd.not().project(&all[k..]).not(), bdd.for_all(&all[k..]));
+            assert_eq!(bdd.not().project(&all[..k]).not(), bdd.for_all(&all[..k]));
+        }
+    }
+    let bdd = variables.eval_expression_string("(v1 & v2) | (!v1 & v3)");
+    assert_eq!(
+        variables.eval_expression_string("v2 & v3"),
+        bdd.for_all(&[v1])
+    );
+    assert_eq!(variables.mk_false(), bdd.for_all(&[v2, v3]));
+}
+
+#[test]
+fn bdd_exists_clustered() {
+    let variables = BddVariableSet::new_anonymous(12);
+    let v = variables.variables();
+    let bdd = variables.eval_expression_string(
+        "((x_0 <=> x_6) & (x_1 <=> x_7) & (x_2 <=> x_8)) | ((x_3 ^ x_9) & (x_4 | x_10) & !x_5) | x_11",
+    );
+    let configs = [
+        ClusteringConfig::default(),
+        ClusteringConfig::new(1, 1.0),
+        ClusteringConfig::new(2, 1.0),
+        ClusteringConfig::new(3, 100.0),
+    ];
+    let quantified = [
+        v[..6].to_vec(),
+        v[6..].to_vec(),
+        vec![v[0], v[2], v[3], v[7], v[9], v[10]],
+        v.clone(),
+        Vec::new(),
+    ];
+    for variables in quantified.iter() {
+        for config in configs.iter() {
+            assert_eq!(
+                bdd.project(variables),
+                bdd.exists_clustered(variables, config)
+            );
+        }
     }
 }
 
+#[test]
+#[should_panic]
+fn bdd_exists_clustered_invalid_variable() {
+    let variables = mk_5_variable_set();
+    variables
+        .mk_true()
+        .exists_clustered(&[BddVariable(5)], &ClusteringConfig::default());
+}
+
+#[test]
+#[should_panic]
+fn clustering_config_invalid() {
+    ClusteringConfig::new(0, 2.0);
+}
+
+#[test]
+#[should_panic]
+fn bdd_exists_invalid_variable() {
+    let variables = mk_5_variable_set();
+    variables.mk_true().exists(&[BddVariable(5)]);
+}
+
 #[test]
 fn bdd_pick_trivial() {
     let variables = mk_5_variable_set();
@@ -101,20 +242,20 @@ fn bdd_pick_trivial() {
     let ff = variables.mk_false();
     let (v1, v2, v3, v4, v5) = vars();
 
-    assert_eq!(ff, ff.pick(&vec![v2, v3, v4]));
-    assert_eq!(ff, ff.pick(&vec![]));
+    assert_eq!(ff, ff.pick(&[v2, v3, v4]));
+    assert_eq!(ff, ff.pick(&[]));
 
-    assert_eq!(tt, tt.pick(&vec![]));
+    assert_eq!(tt, tt.pick(&[]));
     let expected = variables.eval_expression_string("!v1 & !v2 & !v3 & !v4 & !v5");
-    assert_eq!(expected, tt.pick(&vec![v1, v2, v3, v4, v5]));
+    assert_eq!(expected, tt.pick(&[v1, v2, v3, v4, v5]));
     let expected = variables.eval_expression_string("!v4 & !v5");
-    assert_eq!(expected, tt.pick(&vec![v4, v5]));
+    assert_eq!(expected, tt.pick(&[v4, v5]));
 
-    assert_eq!(bdd, bdd.pick(&vec![]));
+    assert_eq!(bdd, bdd.pick(&[]));
     let expected = variables.eval_expression_string("!v1 & !v2 & v3 & !v4 & !v5");
-    assert_eq!(expected, bdd.pick(&vec![v1, v2, v3, v4, v5]));
+    assert_eq!(expected, bdd.pick(&[v1, v2, v3, v4, v5]));
     let expected = variables.eval_expression_string("v3 & !v4 & !v5");
-    assert_eq!(expected, bdd.pick(&vec![v4, v5]));
+    assert_eq!(expected, bdd.pick(&[v4, v5]));
 }
 
 #[test]
@@ -123,20 +264,20 @@ fn bdd_pick_simple() {
     let bdd = variables.eval_expression_string("(v1 => (v4 <=> v5)) & (!v1 => !(v4 <=> v5))");
     let expected = variables.eval_expression_string("(v1 => (!v4 & !v5)) & (!v1 => (!v4 & v5))");
     let (v1, v2, v3, v4, v5) = vars();
-    assert_eq!(expected, bdd.pick(&vec![v4, v5]));
-    assert_eq!(bdd, bdd.pick(&vec![v5]));
+    assert_eq!(expected, bdd.pick(&[v4, v5]));
+    assert_eq!(bdd, bdd.pick(&[v5]));
 
     let bdd = variables.eval_expression_string("(v1 <=> v5) & (v2 => v4) & (v3 ^ v2)");
     let witness_bdd: Bdd = variables.eval_expression_string("!v1 & !v2 & v3 & !v4 & !v5");
-    assert_eq!(witness_bdd, bdd.pick(&vec![v1, v2, v3, v4, v5]));
+    assert_eq!(witness_bdd, bdd.pick(&[v1, v2, v3, v4, v5]));
     let expected = variables
         .eval_expression_string("(v1 => (!v2 & v3 & !v4 & v5)) & (!v1 => (!v2 & v3 & !v4 & !v5))");
-    assert_eq!(expected, bdd.pick(&vec![v2, v3, v4, v5]));
+    assert_eq!(expected, bdd.pick(&[v2, v3, v4, v5]));
     let expected = variables.eval_expression_string("((v1 & v2) => (!v3 & v4 & v5)) & ((v1 & !v2) => (v3 & !v4 & v5)) & ((!v1 & v2) => (!v3 & v4 & !v5)) & ((!v1 & !v2) => (v3 & !v4 & !v5))");
-    assert_eq!(expected, bdd.pick(&vec![v3, v4, v5]));
-    assert_eq!(expected, bdd.pick(&vec![v4, v5])); // accidentally, this works out
-    assert_eq!(bdd, bdd.pick(&vec![v5]));
-    assert_eq!(bdd, bdd.pick(&vec![]));
+    assert_eq!(expected, bdd.pick(&[v3, v4, v5]));
+    assert_eq!(expected, bdd.pick(&[v4, v5])); // accidentally, this works out
+    assert_eq!(bdd, bdd.pick(&[v5]));
+    assert_eq!(bdd, bdd.pick(&[]));
 }
 
 #[test]
@@ -147,6 +288,319 @@ fn bdd_select() {
     let (v1, _, v3, v4, _) = vars();
     assert_eq!(
         expected,
-        bdd.select(&vec![(v1, true), (v4, false), (v3, false)])
+        bdd.select(&[(v1, true), (v4, false), (v3, false)])
     );
 }
+
+#[test]
+fn bdd_restrict() {
+    let variables = mk_5_variable_set();
+    let (v1, v2, _, _, v5) = vars();
+    let bdd = variables.eval_expression_string("(v1 => (v2 <=> v3)) & (!v1 => !(v2 <=> v5))");
+    let valuation = BddPartialValuation::from_values(&[(v1, true)]);
+    assert_eq!(
+        variables.eval_expression_string("v2 <=> v3"),
+        bdd.restrict(&valuation)
+    );
+    let valuation = BddPartialValuation::from_values(&[(v1, false), (v5, true)]);
+    assert_eq!(
+        variables.eval_expression_string("!v2"),
+        bdd.restrict(&valuation)
+    );
+    let valuation = BddPartialValuation::from_values(&[(v1, false), (v2, true), (v5, true)]);
+    assert!(bdd.restrict(&valuation).is_false());
+    assert_eq!(bdd, bdd.restrict(&BddPartialValuation::empty()));
+}
+
+#[test]
+fn bdd_restrict_exhaustive() {
+    let variables = mk_5_variable_set();
+    let expressions = [
+        "(v1 => (v2 <=> v3)) & (!v1 => !(v2 <=> v5))",
+        "(v1 ^ v3) | (v2 & v4 & !v5) | (v2 <=> v5)",
+        "(v1 & v2) | (v3 & v4) | (!v1 & v5) | (v2 ^ v4)",
+    ];
+    let (v1, v2, v3, v4, v5) = vars();
+    let all = [v1, v2, v3, v4, v5];
+    for expression in expressions.iter() {
+        let bdd = variables.eval_expression_string(expression);
+        // Every partial valuation over five variables: each is unset, false or true.
+        for code in 0..243 {
+            let mut code = code;
+            let mut valuation = BddPartialValuation::empty();
+            let mut fixed = Vec::new();
+            for var in all.iter() {
+                match code % 3 {
+                    1 => valuation.set_value(*var, false),
+                    2 => valuation.set_value(*var, true),
+                    _ => {}
+                }
+                if code % 3 != 0 {
+                    fixed.push(*var);
+                }
+                code /= 3;
+            }
+            let expected = bdd.select(&valuation.to_values()).project(&fixed);
+            assert_eq!(expected, bdd.restrict(&valuation));
+            let mut in_place = bdd.clone();
+            in_place.restrict_assign(&valuation);
+            assert_eq!(expected, in_place);
+        }
+    }
+}
+
+#[test]
+fn bdd_cofactor() {
+    let variables = mk_5_variable_set();
+    let bdd = variables.eval_expression_string("(v1 ^ v3) | (v2 & v4 & !v5) | (v2 <=> v5)");
+    for var in variables.variables() {
+        for value in [false, true].iter() {
+            let cofactor = bdd.cofactor(var, *value);
+            let expected = bdd.select(&[(var, *value)]).var_project(var);
+            assert_eq!(expected, cofactor);
+            assert!(!cofactor.support_set().contains(&var));
+        }
+    }
+    let (v1, _, _, _, _) = vars();
+    let literal = variables.mk_var(v1);
+    assert!(literal.cofactor(v1, true).is_true());
+    assert!(literal.cofactor(v1, false).is_false());
+}
+
+#[test]
+fn bdd_derivative_smooth_consensus() {
+    let variables = mk_5_variable_set();
+    let bdd = variables.eval_expression_string("(v1 & v2) | (v3 ^ v5)");
+    let (v1, _, v3, v4, _) = vars();
+    assert_eq!(
+        variables.eval_expression_string("v2 & !(v3 ^ v5)"),
+        bdd.derivative(v1)
+    );
+    assert_eq!(
+        variables.eval_expression_string("v2 | (v3 ^ v5)"),
+        bdd.smooth(v1)
+    );
+    assert_eq!(
+        variables.eval_expression_string("v3 ^ v5"),
+        bdd.consensus(v1)
+    );
+    assert_eq!(
+        variables.eval_expression_string("!(v1 & v2)"),
+        bdd.derivative(v3)
+    );
+    assert!(bdd.derivative(v4).is_false());
+    assert_eq!(bdd, bdd.smooth(v4));
+    assert_eq!(bdd, bdd.consensus(v4));
+    for var in variables.variables() {
+        assert_eq!(bdd.var_project(var), bdd.smooth(var));
+        assert_eq!(bdd.for_all(&[var]), bdd.consensus(var));
+    }
+}
+
+#[test]
+#[should_panic]
+fn bdd_cofactor_invalid_variable() {
+    mk_5_variable_set().mk_true().cofactor(BddVariable(5), true);
+}
+
+#[test]
+fn bdd_map_nodes() {
+    let variables = mk_5_variable_set();
+    let (_, v2, _, v4, _) = vars();
+    let bdd = variables.eval_expression_string("(v1 ^ v3) | (v2 & v4 & !v5) | (v2 <=> v5)");
+    assert_eq!(bdd, bdd.map_nodes(|_| NodeRewrite::Keep));
+    let restricted = bdd.restrict(&BddPartialValuation::from_values(&[
+        (v2, true),
+        (v4, false),
+    ]));
+    let rewritten = bdd.map_nodes(|var| match var {
+        var if var == v2 => NodeRewrite::High,
+        var if var == v4 => NodeRewrite::Low,
+        _ => NodeRewrite::Keep,
+    });
+    assert_eq!(restricted, rewritten);
+    // Swapping the links of a variable negates the variable.
+    let expected = variables.eval_expression_string("(v1 ^ v3) | (!v2 & v4 & !v5) | (!v2 <=> v5)");
+    let swapped = bdd.map_nodes(|var| {
+        if var == v2 {
+            NodeRewrite::Swap
+        } else {
+            NodeRewrite::Keep
+        }
+    });
+    assert_eq!(expected, swapped);
+    assert!(bdd.map_nodes(|_| NodeRewrite::Constant(false)).is_false());
+}
+
+#[test]
+fn bdd_map_nodes_rename() {
+    let variables = mk_5_variable_set();
+    let (v1, v2, v3, _, v5) = vars();
+    let bdd = variables.eval_expression_string("(v1 ^ v3) | (v2 & v4 & !v5) | (v2 <=> v5)");
+    // Renaming a variable substitutes another variable for it (in both directions of the
+    // variable ordering).
+    let rename = |from: BddVariable, to: BddVariable| {
+        bdd.map_nodes(|var| {
+            if var == from {
+                NodeRewrite::Rename(to)
+            } else {
+                NodeRewrite::Keep
+            }
+        })
+    };
+    let expected = variables.eval_expression_string("(v1 ^ v3) | (v5 & v4 & !v5) | (v5 <=> v5)");
+    assert_eq!(expected, rename(v2, v5));
+    let expected = variables.eval_expression_string("(v1 ^ v3) | (v2 & v4 & !v1) | (v2 <=> v1)");
+    assert_eq!(expected, rename(v5, v1));
+    let expected = variables.eval_expression_string("(v3 ^ v3) | (v2 & v4 & !v5) | (v2 <=> v5)");
+    assert_eq!(expected, rename(v1, v3));
+    let expected = variables.eval_expression_string("(v1 ^ v2) | (v2 & v4 & !v5) | (v2 <=> v5)");
+    assert_eq!(expected, rename(v3, v2));
+}
+
+#[test]
+fn bdd_permute_variables() {
+    let variables = mk_5_variable_set();
+    let (v1, v2, v3, v4, v5) = vars();
+    let bdd = variables.eval_expression_string("(v1 ^ v3) | (v2 & !v4)");
+    assert_eq!(bdd, bdd.permute_variables(&HashMap::new()));
+    // Order-preserving renaming.
+    let mapping: HashMap<BddVariable, BddVariable> =
+        vec![(v2, v3), (v3, v4), (v4, v5)].into_iter().collect();
+    let expected = variables.eval_expression_string("(v1 ^ v4) | (v3 & !v5)");
+    assert_eq!(expected, bdd.permute_variables(&mapping));
+    // Swapping blocks `(v1, v2)` and `(v3, v4)`.
+    let mapping: HashMap<BddVariable, BddVariable> = vec![(v1, v3), (v2, v4), (v3, v1), (v4, v2)]
+        .into_iter()
+        .collect();
+    let expected = variables.eval_expression_string("(v3 ^ v1) | (v4 & !v2)");
+    assert_eq!(expected, bdd.permute_variables(&mapping));
+    assert_eq!(
+        bdd,
+        bdd.permute_variables(&mapping).permute_variables(&mapping)
+    );
+}
+
+#[test]
+fn bdd_permute_variables_deep_chain() {
+    // Moving the top variable to the bottom of a long chain pushes it below every node.
+    let variables = BddVariableSet::new_anonymous(60_000);
+    let all = variables.variables();
+    let (first, last) = (all[0], all[all.len() - 1]);
+    let cube = |first_value: bool| {
+        let values: Vec<(BddVariable, bool)> = all
+            .iter()
+            .map(|v| {
+                (
+                    *v,
+                    if *v == first {
+                        first_value
+                    } else {
+                        v.0 % 3 != 1
+                    },
+                )
+            })
+            .collect();
+        variables.mk_conjunctive_clause(&BddPartialValuation::from_values(&values))
+    };
+    let mapping: HashMap<BddVariable, BddVariable> =
+        vec![(first, last), (last, first)].into_iter().collect();
+    // The last variable is `x_59999`, which is fixed to `true` in both cubes.
+    assert_eq!(cube(true), cube(true).permute_variables(&mapping));
+    let swapped = variables.mk_conjunctive_clause(&BddPartialValuation::from_values(
+        &all.iter()
+            .map(|v| (*v, if *v == last { false } else { v.0 % 3 != 1 }))
+            .collect::<Vec<_>>(),
+    ));
+    assert_eq!(swapped, cube(false).permute_variables(&mapping));
+}
+
+#[test]
+#[should_panic]
+fn bdd_permute_variables_invalid_variable() {
+    let variables = mk_5_variable_set();
+    let mapping: HashMap<BddVariable, BddVariable> =
+        vec![(BddVariable(0), BddVariable(5))].into_iter().collect();
+    variables.mk_true().permute_variables(&mapping);
+}
+
+#[test]
+fn bdd_swap_variable_blocks() {
+    let variables = BddVariableSet::new_anonymous(6);
+    let v = variables.variables();
+    let (state, primed) = ([v[0], v[2], v[4]], [v[1], v[3], v[5]]);
+    let bdd = variables.eval_expression_string("(x_0 & !x_3) | (x_2 ^ x_5) | (x_1 <=> x_4)");
+    let expected = variables.eval_expression_string("(x_1 & !x_2) | (x_3 ^ x_4) | (x_0 <=> x_5)");
+    let swapped = bdd.swap_variable_blocks(&state, &primed);
+    assert_eq!(expected, swapped);
+    assert_eq!(bdd, swapped.swap_variable_blocks(&primed, &state));
+    let mapping: HashMap<BddVariable, BddVariable> = state
+        .iter()
+        .cloned()
+        .zip(primed.iter().cloned())
+        .flat_map(|(a, b)| vec![(a, b), (b, a)])
+        .collect();
+    assert_eq!(swapped, bdd.permute_variables(&mapping));
+    // Non-interleaved blocks.
+    let swapped = bdd.swap_variable_blocks(&[v[0], v[1]], &[v[4], v[5]]);
+    let expected = variables.eval_expression_string("(x_4 & !x_3) | (x_2 ^ x_1) | (x_5 <=> x_0)");
+    assert_eq!(expected, swapped);
+}
+
+#[test]
+fn project_ordered() {
+    let variables = mk_5_variable_set();
+    let (v1, v2, v3, v4, v5) = vars();
+    let bdd = variables.eval_expression_string("(v1 <=> v3) & (v2 ^ v5) & (v4 | v1)");
+    let orders = [
+        QuantificationOrder::BottomUp,
+        QuantificationOrder::AsGiven,
+        QuantificationOrder::FewestNodes,
+    ];
+    for eliminated in &[
+        vec![v1],
+        vec![v3, v1],
+        vec![v5, v2, v4],
+        vec![v1, v2, v3, v4, v5],
+        vec![v2, v2, v4],
+        vec![],
+    ] {
+        let expected = bdd.project(eliminated);
+        for order in orders.iter() {
+            assert_eq!(expected, bdd.project_ordered(eliminated, *order));
+        }
+    }
+    assert!(variables
+        .mk_false()
+        .project_ordered(&[v1, v2], QuantificationOrder::FewestNodes)
+        .is_false());
+}
+
+#[test]
+fn merge_equivalent_variables() {
+    let variables = mk_5_variable_set();
+    let (v1, v2, v3, _, v5) = vars();
+    let bdd = variables.eval_expression_string("(v1 <=> v3) & (v2 ^ v5) & (v4 | v1) & (v5 => v3)");
+    let (merged, substitution) = bdd.merge_equivalent_variables();
+    assert_eq!(vec![(v3, v1, true), (v5, v2, false)], substitution);
+    assert_eq!(
+        variables.eval_expression_string("(v4 | v1) & (!v2 => v1)"),
+        merged
+    );
+    // The original function is the result together with the equivalences.
+    let rebuilt = merged
+        .and(&variables.eval_expression_string("v1 <=> v3"))
+        .and(&variables.eval_expression_string("v2 ^ v5"));
+    assert_eq!(bdd, rebuilt);
+
+    // Constant variables are equivalent as well.
+    let bdd = variables.eval_expression_string("v1 & !v2 & (v3 | v4)");
+    let (merged, substitution) = bdd.merge_equivalent_variables();
+    assert_eq!(vec![(v2, v1, false)], substitution);
+    assert_eq!(variables.eval_expression_string("v1 & (v3 | v4)"), merged);
+
+    let bdd = variables.eval_expression_string("v1 | v2");
+    assert_eq!((bdd.clone(), vec![]), bdd.merge_equivalent_variables());
+    let bdd = variables.mk_false();
+    assert_eq!((bdd.clone(), vec![]), bdd.merge_equivalent_variables());
+}
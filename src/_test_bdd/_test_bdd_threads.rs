@@ -0,0 +1,89 @@
+use crate::_test_util::mk_5_variable_set;
+use crate::boolean_expression::{BooleanExpression, Diagnostic};
+use crate::op_function::BooleanOp;
+use crate::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn public_types_are_send_and_sync() {
+    struct Universe;
+    assert_send_sync::<Bdd>();
+    assert_send_sync::<BddVariable>();
+    assert_send_sync::<BddValuation>();
+    assert_send_sync::<BddPartialValuation>();
+    assert_send_sync::<BddValuationIterator>();
+    assert_send_sync::<BddSatisfyingValuations<'static>>();
+    assert_send_sync::<TypedBdd<Universe>>();
+    // The universe marker does not have to be thread-safe.
+    assert_send_sync::<TypedBdd<std::rc::Rc<Universe>>>();
+    assert_send_sync::<Bdd3>();
+    assert_send_sync::<BddInterval>();
+    assert_send_sync::<BddExpr>();
+    assert_send_sync::<FrozenBdd>();
+    assert_send_sync::<BddComputation>();
+    assert_send_sync::<BddVariableSet>();
+    assert_send_sync::<BddVariableSetBuilder>();
+    assert_send_sync::<BooleanExpression>();
+    assert_send_sync::<Diagnostic>();
+    assert_send_sync::<BooleanOp>();
+    assert_send_sync::<Cancelled>();
+}
+
+#[test]
+fn concurrent_read_only_operations() {
+    let variables = Arc::new(mk_5_variable_set());
+    let shared = Arc::new(
+        variables.eval_expression_string("(v1 => (v2 <=> v3)) & (!v1 => !(v2 <=> v5)) | v4"),
+    );
+    let compute = |vars: &BddVariableSet, bdd: &Bdd, i: usize| {
+        let var = vars.variables()[i % 5];
+        let other = vars.mk_var(vars.variables()[(i + 2) % 5]);
+        let result = bdd.var_project(var).xor(&bdd.and(&other)).or(&bdd.not());
+        (result.cardinality(), result.sat_witness(), result)
+    };
+    let expected: Vec<_> = (0..16).map(|i| compute(&variables, &shared, i)).collect();
+    let handles: Vec<_> = (0..16)
+        .map(|i| {
+            let variables = variables.clone();
+            let shared = shared.clone();
+            thread::spawn(move || compute(&variables, &shared, i))
+        })
+        .collect();
+    let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(expected, results);
+}
+
+#[test]
+fn cancel_operation_from_another_thread() {
+    // Equality of two non-interleaved blocks has an exponential number of nodes.
+    let variables = BddVariableSet::new_anonymous(20);
+    let v = variables.variables();
+    let a = Arc::new(variables.mk_block_equality(&v[..10], &v[10..]));
+    let b = Arc::new(variables.mk_block_hamming_at_most(&v[..10], &v[10..], 2));
+    assert_eq!(
+        Err(Cancelled),
+        a.cancellable_and(&b, &AtomicBool::new(true))
+    );
+    assert_eq!(
+        Ok(a.xor(&b)),
+        a.cancellable_xor(&b, &AtomicBool::new(false))
+    );
+
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let worker = {
+        let cancelled = cancelled.clone();
+        thread::spawn(move || loop {
+            if let Err(error) = a.cancellable_xor(&b, &cancelled) {
+                return error;
+            }
+        })
+    };
+    thread::sleep(Duration::from_millis(20));
+    cancelled.store(true, Ordering::Relaxed);
+    assert_eq!(Cancelled, worker.join().unwrap());
+}
@@ -7,3 +7,7 @@ mod _test_bdd_logic_fuzzing;
 
 /// **(internal)** Basic tests for advanced relation operations on `Bdd`s.
 mod _test_bdd_relation_ops;
+
+/// **(internal)** Check that public types can be shared between threads and that concurrent
+/// read-only operations on shared `Bdd`s give the same results as sequential ones.
+mod _test_bdd_threads;
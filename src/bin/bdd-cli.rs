@@ -0,0 +1,297 @@
+//! A small command line tool for working with `Bdd`s stored in files.
+//!
+//! Run `bdd-cli help` for the list of supported commands.
+
+use biodivine_lib_bdd::boolean_expression::BooleanExpression;
+use biodivine_lib_bdd::conformance;
+use biodivine_lib_bdd::{Bdd, BddVariableSet};
+use std::convert::TryFrom;
+use std::io::Write;
+use std::path::Path;
+
+const USAGE: &str = "Usage: bdd-cli <command> [arguments] [options]
+
+Commands:
+  parse <expression>          Build a BDD from a Boolean expression.
+  dimacs <file.cnf>           Build a BDD from a DIMACS CNF file (variable i is named x_{i-1}).
+  apply <op> <a.bdd> <b.bdd>  Combine two stored BDDs (and, or, xor, imp, iff, and_not).
+  not <a.bdd>                 Negate a stored BDD.
+  convert <a.bdd>             Re-encode a stored BDD (see --format).
+  stats <a.bdd>               Print the number of variables, nodes and satisfying valuations.
+  count <a.bdd>               Print the number of satisfying valuations.
+  models <a.bdd>              Print satisfying valuations (see --limit).
+  fixtures <directory>        (Re)generate the serialisation conformance fixtures.
+  verify-fixtures <directory> Check the serialisation conformance fixtures.
+  help                        Print this message.
+
+Stored BDDs can use the text or the binary format (detected automatically).
+
+Options:
+  --vars a,b,c     Variable names (default: variables of the expression for `parse`,
+                   x_0, x_1, ... otherwise).
+  --format FORMAT  Output format: text (default), binary or dot.
+  --output FILE    Write the result into FILE instead of the standard output.
+  --limit N        Maximal number of printed valuations (default: 100).";
+
+/// Parsed command line options.
+struct Options {
+    arguments: Vec<String>,
+    vars: Option<Vec<String>>,
+    format: String,
+    output: Option<String>,
+    limit: usize,
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(message) = run(args) {
+        eprintln!("Error: {}", message);
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let options = parse_options(args)?;
+    let arguments: Vec<&str> = options.arguments.iter().map(|s| s.as_str()).collect();
+    match arguments.as_slice() {
+        ["parse", expression] => {
+            let expression = BooleanExpression::try_from(*expression)?;
+            let vars = match &options.vars {
+                Some(names) => mk_variable_set(names)?,
+                None => {
+                    let mut names = Vec::new();
+                    collect_variables(&expression, &mut names);
+                    mk_variable_set(&names)?
+                }
+            };
+            let bdd = vars
+                .safe_eval_expression(&expression)
+                .ok_or_else(|| "Expression contains unknown variables.".to_string())?;
+            write_bdd(&bdd, &vars, &options)
+        }
+        ["dimacs", path] => {
+            let bdd = read_dimacs(&read_text(path)?)?;
+            write_bdd(&bdd, &variable_set(&bdd, &options)?, &options)
+        }
+        ["apply", op, left, right] => {
+            let (left, right) = (read_bdd(left)?, read_bdd(right)?);
+            if left.num_vars() != right.num_vars() {
+                return Err(format!(
+                    "BDDs have different variable counts ({} != {}).",
+                    left.num_vars(),
+                    right.num_vars()
+                ));
+            }
+            let result = match *op {
+                "and" => left.and(&right),
+                "or" => left.or(&right),
+                "xor" => left.xor(&right),
+                "imp" => left.imp(&right),
+                "iff" => left.iff(&right),
+                "and_not" => left.and_not(&right),
+                _ => return Err(format!("Unknown operation `{}`.", op)),
+            };
+            write_bdd(&result, &variable_set(&result, &options)?, &options)
+        }
+        ["not", path] => {
+            let bdd = read_bdd(path)?.not();
+            write_bdd(&bdd, &variable_set(&bdd, &options)?, &options)
+        }
+        ["convert", path] => {
+            let bdd = read_bdd(path)?;
+            write_bdd(&bdd, &variable_set(&bdd, &options)?, &options)
+        }
+        ["stats", path] => {
+            let bdd = read_bdd(path)?;
+            let stats = format!(
+                "variables: {}\nnodes: {}\ncardinality: {}\n",
+                bdd.num_vars(),
+                bdd.size(),
+                bdd.cardinality()
+            );
+            write_output(stats.as_bytes(), &options)
+        }
+        ["count", path] => {
+            let count = format!("{}\n", read_bdd(path)?.cardinality());
+            write_output(count.as_bytes(), &options)
+        }
+        ["models", path] => {
+            let bdd = read_bdd(path)?;
+            let mut output = String::new();
+            for valuation in bdd.sat_valuations().take(options.limit) {
+                output.push_str(&format!("{}\n", valuation));
+            }
+            write_output(output.as_bytes(), &options)
+        }
+        ["fixtures", directory] => conformance::write_fixtures(Path::new(directory)),
+        ["verify-fixtures", directory] => conformance::verify_fixtures(Path::new(directory)),
+        ["help"] | [] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("Invalid arguments.\n\n{}", USAGE)),
+    }
+}
+
+fn parse_options(args: Vec<String>) -> Result<Options, String> {
+    let mut options = Options {
+        arguments: Vec::new(),
+        vars: None,
+        format: "text".to_string(),
+        output: None,
+        limit: 100,
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value of `{}`.", arg))
+        };
+        match arg.as_str() {
+            "--vars" => {
+                options.vars = Some(value()?.split(',').map(|s| s.trim().to_string()).collect())
+            }
+            "--format" => options.format = value()?,
+            "--output" => options.output = Some(value()?),
+            "--limit" => {
+                options.limit = value()?
+                    .parse()
+                    .map_err(|_| "Invalid value of `--limit`.".to_string())?
+            }
+            _ => options.arguments.push(arg),
+        }
+    }
+    Ok(options)
+}
+
+/// Create a variable set from the given names, checking that the names are valid.
+fn mk_variable_set(names: &[String]) -> Result<BddVariableSet, String> {
+    BddVariableSet::try_new(names.iter().map(|s| s.as_str()).collect())
+}
+
+/// Variable set for an existing `Bdd`: either from `--vars`, or anonymous.
+fn variable_set(bdd: &Bdd, options: &Options) -> Result<BddVariableSet, String> {
+    if let Some(names) = &options.vars {
+        if names.len() != usize::from(bdd.num_vars()) {
+            return Err(format!(
+                "BDD has {} variables, but {} names were given.",
+                bdd.num_vars(),
+                names.len()
+            ));
+        }
+        mk_variable_set(names)
+    } else {
+        Ok(BddVariableSet::new_anonymous(bdd.num_vars()))
+    }
+}
+
+/// Collect variable names of the expression in the order of their first appearance.
+fn collect_variables(expression: &BooleanExpression, names: &mut Vec<String>) {
+    match expression {
+        BooleanExpression::Const(_) => {}
+        BooleanExpression::Variable(name) => {
+            if !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+        BooleanExpression::Not(inner) => collect_variables(inner, names),
+        BooleanExpression::And(l, r)
+        | BooleanExpression::Or(l, r)
+        | BooleanExpression::Xor(l, r)
+        | BooleanExpression::Imp(l, r)
+        | BooleanExpression::Iff(l, r) => {
+            collect_variables(l, names);
+            collect_variables(r, names);
+        }
+    }
+}
+
+/// Read a CNF formula in the DIMACS format and convert it to a `Bdd`.
+fn read_dimacs(input: &str) -> Result<Bdd, String> {
+    let mut lines = input
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('c'));
+    let header: Vec<&str> = lines
+        .next()
+        .ok_or_else(|| "Missing DIMACS header.".to_string())?
+        .split_whitespace()
+        .collect();
+    let num_vars = match header.as_slice() {
+        ["p", "cnf", num_vars, _] => num_vars
+            .parse::<u16>()
+            .map_err(|_| "Invalid variable count.".to_string())?,
+        _ => return Err("Invalid DIMACS header.".to_string()),
+    };
+    let vars = BddVariableSet::new_anonymous(num_vars);
+    let variables = vars.variables();
+    let mut result = vars.mk_true();
+    // Some benchmark files end with a `%` line followed by garbage, so we stop reading there.
+    // A clause which is not terminated by `0` at the end of input is still included.
+    let mut clause: Option<Bdd> = None;
+    let tokens = lines
+        .take_while(|line| !line.starts_with('%'))
+        .flat_map(|line| line.split_whitespace());
+    for token in tokens {
+        let literal = token
+            .parse::<i32>()
+            .map_err(|_| format!("Invalid literal `{}`.", token))?;
+        if literal == 0 {
+            let finished = clause.take().unwrap_or_else(|| vars.mk_false());
+            result = result.and(&finished);
+        } else {
+            let index = (literal.unsigned_abs() - 1) as usize;
+            let var = *variables
+                .get(index)
+                .ok_or_else(|| format!("Unknown variable `{}`.", literal))?;
+            let literal = vars.mk_literal(var, literal > 0);
+            clause = Some(match clause {
+                Some(clause) => clause.or(&literal),
+                None => literal,
+            });
+        }
+    }
+    if let Some(clause) = clause {
+        result = result.and(&clause);
+    }
+    Ok(result)
+}
+
+fn read_text(path: &str) -> Result<String, String> {
+    std::fs::read_to_string(path).map_err(|e| format!("Cannot read `{}`: {}", path, e))
+}
+
+/// Read a stored `Bdd`, detecting whether it uses the text or the binary format.
+fn read_bdd(path: &str) -> Result<Bdd, String> {
+    let data = std::fs::read(path).map_err(|e| format!("Cannot read `{}`: {}", path, e))?;
+    // The text format only contains digits, commas and `|`. The binary format always contains
+    // zero bytes (at least in the links of the terminal nodes).
+    let is_text = data
+        .iter()
+        .all(|b| b.is_ascii_digit() || *b == b',' || *b == b'|' || b.is_ascii_whitespace());
+    if is_text {
+        let text = String::from_utf8_lossy(&data);
+        Bdd::read_as_string(&mut text.trim().as_bytes())
+    } else {
+        Bdd::read_as_bytes(&mut data.as_slice()).map_err(|e| e.to_string())
+    }
+    .map_err(|e| format!("Invalid BDD in `{}`: {}", path, e))
+}
+
+fn write_bdd(bdd: &Bdd, vars: &BddVariableSet, options: &Options) -> Result<(), String> {
+    let data = match options.format.as_str() {
+        "text" => format!("{}\n", bdd).into_bytes(),
+        "binary" => bdd.to_bytes(),
+        "dot" => bdd.to_dot_string(vars, true).into_bytes(),
+        format => return Err(format!("Unknown format `{}`.", format)),
+    };
+    write_output(&data, options)
+}
+
+fn write_output(data: &[u8], options: &Options) -> Result<(), String> {
+    match &options.output {
+        Some(path) => std::fs::write(path, data),
+        None => std::io::stdout().write_all(data),
+    }
+    .map_err(|e| format!("Cannot write output: {}", e))
+}
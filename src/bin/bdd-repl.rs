@@ -0,0 +1,141 @@
+//! An interactive shell for exploring `Bdd`s (requires the `repl` feature).
+//!
+//! Run `cargo run --features repl --bin bdd-repl` and type `help` for the list of commands.
+
+use biodivine_lib_bdd::boolean_expression::BooleanExpression;
+use biodivine_lib_bdd::{Bdd, BddVariableSet};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io::{BufRead, Write};
+
+const HELP: &str = "Commands:
+  vars <a> <b> ...        Declare the variables (clears all definitions).
+  let <name> = <expr>     Define a BDD. The expression can use variables and defined BDDs.
+  show <name>             Print the BDD as an expression.
+  size <name>             Print the number of nodes.
+  count <name>            Print the number of satisfying valuations.
+  models <name> [limit]   Print satisfying valuations (default limit: 10).
+  dot <name> [file]       Print (or save) the BDD as a .dot graph.
+  list                    List all defined BDDs.
+  help                    Print this message.
+  quit                    Exit the shell.";
+
+/// State of the interactive session.
+struct Session {
+    vars: BddVariableSet,
+    definitions: BTreeMap<String, Bdd>,
+}
+
+fn main() {
+    let mut session = Session {
+        vars: BddVariableSet::new_anonymous(0),
+        definitions: BTreeMap::new(),
+    };
+    println!("BDD shell. Type `help` for the list of commands.");
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        std::io::stdout()
+            .flush()
+            .expect("Cannot write to standard output.");
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        match session.execute(line.trim()) {
+            Ok(Some(output)) => println!("{}", output),
+            Ok(None) => {}
+            Err(message) if message == "quit" => break,
+            Err(message) => println!("Error: {}", message),
+        }
+    }
+}
+
+impl Session {
+    /// Execute one command, returning the text that should be printed.
+    fn execute(&mut self, line: &str) -> Result<Option<String>, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => Ok(None),
+            ["quit"] | ["exit"] => Err("quit".to_string()),
+            ["help"] => Ok(Some(HELP.to_string())),
+            ["vars", names @ ..] => {
+                self.vars = BddVariableSet::try_new(names.to_vec())?;
+                self.definitions.clear();
+                Ok(None)
+            }
+            ["let", name, "=", ..] => {
+                let expression = line.split_once('=').map(|(_, e)| e).unwrap_or_default();
+                let expression = BooleanExpression::try_from(expression.trim())?;
+                let bdd = self.eval(&expression)?;
+                self.definitions.insert(name.to_string(), bdd);
+                Ok(None)
+            }
+            ["list"] => Ok(Some(
+                self.definitions
+                    .iter()
+                    .map(|(name, bdd)| format!("{} ({} nodes)", name, bdd.size()))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )),
+            ["show", name] => Ok(Some(
+                self.get(name)?
+                    .to_boolean_expression(&self.vars)
+                    .to_string(),
+            )),
+            ["size", name] => Ok(Some(self.get(name)?.size().to_string())),
+            ["count", name] => Ok(Some(self.get(name)?.cardinality().to_string())),
+            ["models", name, rest @ ..] => {
+                let limit = match rest {
+                    [] => 10,
+                    [limit] => limit.parse().map_err(|_| "Invalid limit.".to_string())?,
+                    _ => return Err("Too many arguments.".to_string()),
+                };
+                let models: Vec<String> = self
+                    .get(name)?
+                    .sat_valuations()
+                    .take(limit)
+                    .map(|v| v.to_string())
+                    .collect();
+                Ok(Some(models.join("\n")))
+            }
+            ["dot", name] => Ok(Some(self.get(name)?.to_dot_string(&self.vars, true))),
+            ["dot", name, file] => {
+                let dot = self.get(name)?.to_dot_string(&self.vars, true);
+                std::fs::write(file, dot).map_err(|e| e.to_string())?;
+                Ok(None)
+            }
+            _ => Err("Unknown command. Type `help` for the list of commands.".to_string()),
+        }
+    }
+
+    fn get(&self, name: &str) -> Result<&Bdd, String> {
+        self.definitions
+            .get(name)
+            .ok_or_else(|| format!("`{}` is not defined.", name))
+    }
+
+    /// Evaluate an expression where names refer to defined BDDs or declared variables.
+    fn eval(&self, expression: &BooleanExpression) -> Result<Bdd, String> {
+        use BooleanExpression::*;
+        Ok(match expression {
+            Const(true) => self.vars.mk_true(),
+            Const(false) => self.vars.mk_false(),
+            Variable(name) => match self.definitions.get(name) {
+                Some(bdd) => bdd.clone(),
+                None => self
+                    .vars
+                    .var_by_name(name)
+                    .map(|v| self.vars.mk_var(v))
+                    .ok_or_else(|| format!("Unknown variable `{}`.", name))?,
+            },
+            Not(inner) => self.eval(inner)?.not(),
+            And(l, r) => self.eval(l)?.and(&self.eval(r)?),
+            Or(l, r) => self.eval(l)?.or(&self.eval(r)?),
+            Xor(l, r) => self.eval(l)?.xor(&self.eval(r)?),
+            Imp(l, r) => self.eval(l)?.imp(&self.eval(r)?),
+            Iff(l, r) => self.eval(l)?.iff(&self.eval(r)?),
+        })
+    }
+}
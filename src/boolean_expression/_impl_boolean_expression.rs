@@ -1,7 +1,7 @@
 use super::super::{Bdd, BddVariableSet};
-use super::BooleanExpression;
+use super::_impl_parser::{find_ambiguous_operators, parse_boolean_expression};
 use super::BooleanExpression::*;
-use super::_impl_parser::parse_boolean_expression;
+use super::{BooleanExpression, Diagnostic};
 use std::convert::TryFrom;
 use std::fmt::{Display, Error, Formatter};
 
@@ -84,10 +84,119 @@ impl BddVariableSet {
     }
 }
 
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            Diagnostic::SyntaxError(message) => write!(f, "Syntax error: {}", message),
+            Diagnostic::UnknownVariable(name) => write!(f, "Unknown variable `{}`.", name),
+            Diagnostic::AmbiguousPrecedence(message) => write!(f, "{}", message),
+            Diagnostic::Tautology(e) => write!(f, "Expression `{}` is always true.", e),
+            Diagnostic::Contradiction(e) => write!(f, "Expression `{}` is always false.", e),
+        }
+    }
+}
+
+/// **(internal)** Sub-expressions are only evaluated into `Bdd`s while their size stays below
+/// this limit when checking an expression, so that checking remains fast.
+const CHECK_SIZE_LIMIT: usize = 10_000;
+
+/// Methods for validating boolean expressions.
+impl BddVariableSet {
+    /// Check the given expression for problems without fully evaluating it into a `Bdd`.
+    ///
+    /// Reports syntax errors (in which case no other checks are performed), unknown variables,
+    /// operators combined without parentheses, and sub-expressions which are tautologies or
+    /// contradictions (only the innermost such sub-expressions are reported). Sub-expressions
+    /// are only evaluated while their `Bdd`s stay small, so some constant sub-expressions
+    /// may not be detected.
+    pub fn check_expression(&self, expression: &str) -> Vec<Diagnostic> {
+        let parsed = match BooleanExpression::try_from(expression) {
+            Ok(parsed) => parsed,
+            Err(message) => return vec![Diagnostic::SyntaxError(message)],
+        };
+        let mut diagnostics = Vec::new();
+        let mut unknown = Vec::new();
+        parsed.collect_unknown_variables(self, &mut unknown);
+        diagnostics.extend(unknown.into_iter().map(Diagnostic::UnknownVariable));
+        let ambiguous = find_ambiguous_operators(expression).unwrap_or_default();
+        diagnostics.extend(ambiguous.into_iter().map(Diagnostic::AmbiguousPrecedence));
+        self.check_constant_expressions(&parsed, &mut diagnostics);
+        diagnostics
+    }
+
+    /// **(internal)** Evaluate the `expression` (if it is small enough) and report its innermost
+    /// constant sub-expressions.
+    fn check_constant_expressions(
+        &self,
+        expression: &BooleanExpression,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<Bdd> {
+        let result = match expression {
+            Const(value) => {
+                return Some(if *value {
+                    self.mk_true()
+                } else {
+                    self.mk_false()
+                })
+            }
+            Variable(name) => return self.var_by_name(name).map(|v| self.mk_var(v)),
+            Not(inner) => {
+                return self
+                    .check_constant_expressions(inner, diagnostics)
+                    .map(|b| b.not())
+            }
+            And(l, r) | Or(l, r) | Xor(l, r) | Imp(l, r) | Iff(l, r) => {
+                let left = self.check_constant_expressions(l, diagnostics);
+                let right = self.check_constant_expressions(r, diagnostics);
+                let (left, right) = (left?, right?);
+                if left.is_true() || left.is_false() || right.is_true() || right.is_false() {
+                    // Operands are already constant, this is not the innermost expression.
+                    return None;
+                }
+                match expression {
+                    And(_, _) => left.and(&right),
+                    Or(_, _) => left.or(&right),
+                    Xor(_, _) => left.xor(&right),
+                    Imp(_, _) => left.imp(&right),
+                    _ => left.iff(&right),
+                }
+            }
+        };
+        if result.is_true() {
+            diagnostics.push(Diagnostic::Tautology(expression.clone()));
+        } else if result.is_false() {
+            diagnostics.push(Diagnostic::Contradiction(expression.clone()));
+        }
+        Some(result).filter(|bdd| bdd.size() <= CHECK_SIZE_LIMIT)
+    }
+}
+
+impl BooleanExpression {
+    /// **(internal)** Collect the names of variables which are not in the given variable set
+    /// (in the order of first appearance, without duplicates).
+    fn collect_unknown_variables(&self, variables: &BddVariableSet, unknown: &mut Vec<String>) {
+        match self {
+            Const(_) => {}
+            Variable(name) => {
+                if variables.var_by_name(name).is_none() && !unknown.contains(name) {
+                    unknown.push(name.clone());
+                }
+            }
+            Not(inner) => inner.collect_unknown_variables(variables, unknown),
+            And(l, r) | Or(l, r) | Xor(l, r) | Imp(l, r) | Iff(l, r) => {
+                l.collect_unknown_variables(variables, unknown);
+                r.collect_unknown_variables(variables, unknown);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::super::BddVariableSet;
     use crate::bdd;
+    use crate::boolean_expression::{BooleanExpression, Diagnostic};
+    use std::convert::TryFrom;
 
     #[test]
     fn bdd_universe_eval_boolean_formula() {
@@ -104,4 +213,31 @@ mod tests {
 
         assert_eq!(expected, evaluated);
     }
+
+    #[test]
+    fn check_expression() {
+        let variables = BddVariableSet::new(vec!["a", "b", "c"]);
+        let parse = |e: &str| BooleanExpression::try_from(e).unwrap();
+        assert!(variables.check_expression("(a & b) | !c").is_empty());
+        assert!(matches!(
+            variables.check_expression("a & (b").as_slice(),
+            [Diagnostic::SyntaxError(_)]
+        ));
+        assert_eq!(
+            vec![
+                Diagnostic::UnknownVariable("d".to_string()),
+                Diagnostic::Contradiction(parse("a & !a"))
+            ],
+            variables.check_expression("(d | c) & (a & !a)")
+        );
+        assert_eq!(
+            vec![
+                Diagnostic::AmbiguousPrecedence(
+                    "Operators `&`, `|` are combined without parentheses (they bind in this order: `&`, `|`).".to_string()
+                ),
+                Diagnostic::Tautology(parse("(b | c) | !(b | c)"))
+            ],
+            variables.check_expression("a & ((b | c) | !(b | c)) | c")
+        );
+    }
 }
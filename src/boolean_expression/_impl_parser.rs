@@ -33,6 +33,55 @@ pub fn parse_boolean_expression(from: &str) -> Result<BooleanExpression, String>
     Ok(*(parse_formula(&tokens)?))
 }
 
+/// **(internal)** Find places in the (syntactically valid) expression where operators are
+/// combined in a way that is easy to misread, and describe them.
+///
+/// This reports every parenthesis group which mixes different binary operators (since the
+/// operator precedence is not obvious, especially for `^`), and chained implications.
+pub(super) fn find_ambiguous_operators(from: &str) -> Result<Vec<String>, String> {
+    fn check_group(group: &[ExprToken], warnings: &mut Vec<String>) {
+        // Operators in the order of decreasing precedence.
+        let operators = [
+            (ExprToken::Xor, "^"),
+            (ExprToken::And, "&"),
+            (ExprToken::Or, "|"),
+            (ExprToken::Imp, "=>"),
+            (ExprToken::Iff, "<=>"),
+        ];
+        let used: Vec<&str> = operators
+            .iter()
+            .filter(|(token, _)| group.contains(token))
+            .map(|(_, symbol)| *symbol)
+            .collect();
+        if used.len() > 1 {
+            warnings.push(format!(
+                "Operators {} are combined without parentheses (they bind in this order: `{}`).",
+                used.iter()
+                    .map(|s| format!("`{}`", s))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                used.join("`, `")
+            ));
+        }
+        if group.iter().filter(|t| **t == ExprToken::Imp).count() > 1 {
+            warnings.push(
+                "Chained `=>` is right-associative: `a => b => c` means `a => (b => c)`."
+                    .to_string(),
+            );
+        }
+        for token in group {
+            if let ExprToken::Tokens(inner) = token {
+                check_group(inner, warnings);
+            }
+        }
+    }
+
+    let tokens = tokenize_group(&mut from.chars().peekable(), true)?;
+    let mut warnings = Vec::new();
+    check_group(&tokens, &mut warnings);
+    Ok(warnings)
+}
+
 /// **(internal)** Process a peekable iterator of characters into a vector of `ExprToken`s.
 ///
 /// The outer method always consumes the opening parenthesis and the recursive call consumes the
@@ -271,6 +320,22 @@ mod tests {
         )
     }
 
+    #[test]
+    fn find_ambiguous_operators_basic() {
+        assert!(find_ambiguous_operators("(a & b) | !(c ^ d)")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            vec!["Operators `^`, `&` are combined without parentheses (they bind in this order: `^`, `&`).".to_string()],
+            find_ambiguous_operators("a | (b & c ^ d)").unwrap()
+        );
+        assert_eq!(
+            2,
+            find_ambiguous_operators("a => b => c | d").unwrap().len()
+        );
+        assert!(find_ambiguous_operators("a & (b").is_err());
+    }
+
     #[test]
     #[should_panic]
     fn parse_boolean_formula_invalid_token_1() {
@@ -0,0 +1,331 @@
+use super::super::{Bdd, BddVariableSet, BddVariableSetBuilder, NOT_IN_VAR_NAME};
+use super::BooleanExpression::*;
+use super::{BooleanExpression, PolicyFormat};
+use std::iter::Peekable;
+use std::str::Chars;
+
+impl PolicyFormat {
+    /// Create a policy format with the given keys of the conjunction, disjunction, negation
+    /// and variable objects.
+    pub fn new(and: &str, or: &str, not: &str, var: &str) -> PolicyFormat {
+        PolicyFormat {
+            and: and.to_string(),
+            or: or.to_string(),
+            not: not.to_string(),
+            var: var.to_string(),
+        }
+    }
+}
+
+impl Default for PolicyFormat {
+    /// The format with keys `and`, `or`, `not` and `var`.
+    fn default() -> Self {
+        PolicyFormat::new("and", "or", "not", "var")
+    }
+}
+
+impl BooleanExpression {
+    /// Read a `BooleanExpression` from a JSON policy document in the given format.
+    ///
+    /// A policy is either a JSON boolean, or an object with exactly one key: the conjunction
+    /// (disjunction) key with an array of policies, the negation key with one policy, or
+    /// the variable key with the name of the variable. For example, with the default format,
+    /// `{"and": [{"var": "a"}, {"not": {"var": "b"}}]}` is the expression `a & !b`. An empty
+    /// conjunction is `true` and an empty disjunction is `false`.
+    pub fn from_policy_json(
+        json: &str,
+        format: &PolicyFormat,
+    ) -> Result<BooleanExpression, String> {
+        let mut reader = JsonReader {
+            chars: json.chars().peekable(),
+        };
+        let value = reader.read_value()?;
+        reader.skip_whitespace();
+        if reader.chars.peek().is_some() {
+            return Err("Unexpected characters after the JSON value.".to_string());
+        }
+        policy_to_expression(&value, format)
+    }
+}
+
+impl BddVariableSet {
+    /// Read a JSON policy document (see `BooleanExpression::from_policy_json`) and create
+    /// a `Bdd` of the policy, together with a new `BddVariableSet` which contains exactly
+    /// the variables used in the policy (in the order of their first occurrence).
+    pub fn from_policy_json(
+        json: &str,
+        format: &PolicyFormat,
+    ) -> Result<(BddVariableSet, Bdd), String> {
+        let expression = BooleanExpression::from_policy_json(json, format)?;
+        let mut names: Vec<&str> = Vec::new();
+        expression.collect_variable_names(&mut names);
+        let mut builder = BddVariableSetBuilder::new();
+        for name in names {
+            if name.is_empty() || name.chars().any(|c| NOT_IN_VAR_NAME.contains(&c)) {
+                return Err(format!("Invalid variable name `{}`.", name));
+            }
+            builder.make_variable(name);
+        }
+        let variables = builder.build();
+        let bdd = variables.eval_expression(&expression);
+        Ok((variables, bdd))
+    }
+}
+
+impl BooleanExpression {
+    /// **(internal)** Append the names of all variables which are not in `names` yet.
+    fn collect_variable_names<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Const(_) => {}
+            Variable(name) => {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+            Not(inner) => inner.collect_variable_names(names),
+            And(l, r) | Or(l, r) | Xor(l, r) | Imp(l, r) | Iff(l, r) => {
+                l.collect_variable_names(names);
+                r.collect_variable_names(names);
+            }
+        }
+    }
+}
+
+/// **(internal)** A parsed JSON value.
+#[derive(Clone, Debug, PartialEq)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+/// **(internal)** Convert a parsed JSON policy to a `BooleanExpression`.
+fn policy_to_expression(
+    value: &JsonValue,
+    format: &PolicyFormat,
+) -> Result<BooleanExpression, String> {
+    let (key, inner) = match value {
+        JsonValue::Bool(value) => return Ok(Const(*value)),
+        JsonValue::Object(entries) if entries.len() == 1 => (&entries[0].0, &entries[0].1),
+        _ => return Err(format!("Expected a policy, but found {:?}.", value)),
+    };
+    let operands = |neutral: bool| match inner {
+        JsonValue::Array(items) => items
+            .iter()
+            .map(|item| policy_to_expression(item, format))
+            .collect::<Result<Vec<BooleanExpression>, String>>()
+            .map(|operands| (neutral, operands)),
+        _ => Err(format!("Expected an array of policies in `{}`.", key)),
+    };
+    if *key == format.and || *key == format.or {
+        let (neutral, operands) = operands(*key == format.and)?;
+        let result = operands.into_iter().fold(None, |result, operand| {
+            Some(match result {
+                None => operand,
+                Some(result) if neutral => And(Box::new(result), Box::new(operand)),
+                Some(result) => Or(Box::new(result), Box::new(operand)),
+            })
+        });
+        Ok(result.unwrap_or(Const(neutral)))
+    } else if *key == format.not {
+        Ok(Not(Box::new(policy_to_expression(inner, format)?)))
+    } else if *key == format.var {
+        match inner {
+            JsonValue::String(name) => Ok(Variable(name.clone())),
+            _ => Err(format!("Expected a variable name in `{}`.", key)),
+        }
+    } else {
+        Err(format!("Unknown policy operator `{}`.", key))
+    }
+}
+
+/// **(internal)** A minimal reader of JSON values.
+struct JsonReader<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> JsonReader<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("Expected `{}`, but found `{}`.", expected, c)),
+            None => Err(format!("Expected `{}`, but found end of input.", expected)),
+        }
+    }
+
+    fn read_value(&mut self) -> Result<JsonValue, String> {
+        self.skip_whitespace();
+        match self.chars.peek().cloned() {
+            Some('{') => self.read_object(),
+            Some('[') => self.read_array(),
+            Some('"') => self.read_string().map(JsonValue::String),
+            Some(c) if c == '-' || c.is_ascii_alphanumeric() => {
+                let mut token = String::new();
+                while let Some(c) = self.chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || ['-', '+', '.'].contains(c)) {
+                        break;
+                    }
+                    token.push(*c);
+                    self.chars.next();
+                }
+                match token.as_str() {
+                    "true" => Ok(JsonValue::Bool(true)),
+                    "false" => Ok(JsonValue::Bool(false)),
+                    "null" => Ok(JsonValue::Null),
+                    _ => token
+                        .parse()
+                        .map(JsonValue::Number)
+                        .map_err(|_| format!("Invalid JSON value `{}`.", token)),
+                }
+            }
+            Some(c) => Err(format!("Unexpected character `{}`.", c)),
+            None => Err("Unexpected end of input.".to_string()),
+        }
+    }
+
+    fn read_object(&mut self) -> Result<JsonValue, String> {
+        self.expect('{')?;
+        let mut entries = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.chars.next();
+            return Ok(JsonValue::Object(entries));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.read_string()?;
+            self.expect(':')?;
+            entries.push((key, self.read_value()?));
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(JsonValue::Object(entries)),
+                _ => return Err("Expected `,` or `}` in a JSON object.".to_string()),
+            }
+        }
+    }
+
+    fn read_array(&mut self) -> Result<JsonValue, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.chars.next();
+            return Ok(JsonValue::Array(items));
+        }
+        loop {
+            items.push(self.read_value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(JsonValue::Array(items)),
+                _ => return Err("Expected `,` or `]` in a JSON array.".to_string()),
+            }
+        }
+    }
+
+    fn read_string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut result = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(result),
+                Some('\\') => {
+                    let escaped = match self.chars.next() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('u') => {
+                            let code: String = (0..4).filter_map(|_| self.chars.next()).collect();
+                            u32::from_str_radix(&code, 16)
+                                .ok()
+                                .and_then(std::char::from_u32)
+                                .ok_or_else(|| format!("Invalid escape sequence `\\u{}`.", code))?
+                        }
+                        Some(c) if ['"', '\\', '/'].contains(&c) => c,
+                        _ => return Err("Invalid escape sequence in a JSON string.".to_string()),
+                    };
+                    result.push(escaped);
+                }
+                Some(c) => result.push(c),
+                None => return Err("Unterminated JSON string.".to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::boolean_expression::{BooleanExpression, PolicyFormat};
+    use crate::BddVariableSet;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn policy_json_basic() {
+        let format = PolicyFormat::default();
+        let json =
+            r#"{"and": [{"var": "admin"}, {"or": [{"var": "eu"}, {"not": {"var": "guest"}}]}]}"#;
+        let (variables, bdd) = BddVariableSet::from_policy_json(json, &format).unwrap();
+        assert_eq!(3, variables.num_vars());
+        assert_eq!("admin", variables.name_of(variables.variables()[0]));
+        assert_eq!("guest", variables.name_of(variables.variables()[2]));
+        assert_eq!(
+            variables.eval_expression_string("admin & (eu | !guest)"),
+            bdd
+        );
+
+        let expression = BooleanExpression::from_policy_json("{ \"or\" : [ ] }", &format);
+        assert_eq!(Ok(BooleanExpression::Const(false)), expression);
+        let expression = BooleanExpression::from_policy_json("{\"and\": [true]}", &format);
+        assert_eq!(Ok(BooleanExpression::Const(true)), expression);
+    }
+
+    #[test]
+    fn policy_json_custom_format() {
+        let format = PolicyFormat::new("all", "any", "none", "attr");
+        let json = "{\"all\": [{\"attr\": \"a\\u0062\"}, {\"none\": {\"attr\": \"c\"}}]}";
+        let expression = BooleanExpression::from_policy_json(json, &format).unwrap();
+        assert_eq!(BooleanExpression::try_from("ab & !c").unwrap(), expression);
+        assert!(BooleanExpression::from_policy_json(json, &PolicyFormat::default()).is_err());
+    }
+
+    #[test]
+    fn policy_json_errors() {
+        let format = PolicyFormat::default();
+        let invalid = [
+            "",
+            "{\"var\": \"a\"} x",
+            "{\"var\": 1}",
+            "{\"and\": {\"var\": \"a\"}}",
+            "{\"var\": \"a\", \"not\": true}",
+            "{\"xor\": []}",
+            "[true]",
+            "{\"var\": \"a",
+            "null",
+        ];
+        for json in invalid.iter() {
+            assert!(
+                BooleanExpression::from_policy_json(json, &format).is_err(),
+                "{}",
+                json
+            );
+        }
+        let invalid_name = "{\"var\": \"a & b\"}";
+        assert!(BddVariableSet::from_policy_json(invalid_name, &format).is_err());
+    }
+}
@@ -15,6 +15,9 @@ mod _impl_boolean_expression;
 /// **(internal)** Parsing functions for boolean expressions.
 mod _impl_parser;
 
+/// **(internal)** Import of boolean expressions from JSON policy documents.
+mod _impl_policy_json;
+
 /// Recursive type for boolean expression tree.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum BooleanExpression {
@@ -27,3 +30,28 @@ pub enum BooleanExpression {
     Imp(Box<BooleanExpression>, Box<BooleanExpression>),
     Iff(Box<BooleanExpression>, Box<BooleanExpression>),
 }
+
+/// A problem found by `BddVariableSet::check_expression`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Diagnostic {
+    /// The expression cannot be parsed.
+    SyntaxError(String),
+    /// The expression uses a variable which does not exist in the `BddVariableSet`.
+    UnknownVariable(String),
+    /// Operators are combined in a way that is easy to misread.
+    AmbiguousPrecedence(String),
+    /// A sub-expression which is always `true`.
+    Tautology(BooleanExpression),
+    /// A sub-expression which is always `false`.
+    Contradiction(BooleanExpression),
+}
+
+/// Names of the keys which identify the operators in JSON policy documents (see
+/// `BooleanExpression::from_policy_json`).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PolicyFormat {
+    and: String,
+    or: String,
+    not: String,
+    var: String,
+}
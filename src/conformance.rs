@@ -0,0 +1,150 @@
+//! Golden fixtures which protect the serialisation formats of `Bdd`s against accidental changes.
+//!
+//! The module defines a fixed set of `Bdd`s over four variables (`a`, `b`, `c`, `d`). For each
+//! of them, `write_fixtures` stores the text (`.txt`), binary (`.bin`), `.dot` and `.nnf`
+//! representation into a directory. Later, `verify_fixtures` checks that the current version
+//! of the crate still produces exactly the same files and that it can read the stored text,
+//! binary and `.nnf` files back. The fixtures of this crate are stored in `res/conformance`
+//! and can be (re)generated using `bdd-cli fixtures <directory>`.
+//!
+//! Downstream crates which store `Bdd`s can keep a copy of the fixtures to detect format
+//! changes when updating this crate:
+//!
+//! ```rust
+//! use biodivine_lib_bdd::conformance::{verify_fixtures, write_fixtures};
+//!
+//! let directory = std::env::temp_dir().join("lib-bdd-conformance-example");
+//! std::fs::create_dir_all(&directory).unwrap();
+//! write_fixtures(&directory).unwrap();
+//! // Later (e.g. in a test after a dependency update), the stored files are checked.
+//! assert_eq!(Ok(()), verify_fixtures(&directory));
+//! ```
+
+use crate::{Bdd, BddVariableSet};
+use std::path::Path;
+
+/// **(internal)** Names and expressions of the fixture `Bdd`s.
+const FIXTURES: [(&str, &str); 6] = [
+    ("false", "false"),
+    ("true", "true"),
+    ("literal", "!b"),
+    ("cube", "a & !c & d"),
+    ("parity", "a ^ b ^ c ^ d"),
+    ("mixed", "(a => b) & (c | !d) & !(a & d)"),
+];
+
+/// The variables used by all fixture `Bdd`s.
+pub fn fixture_variables() -> BddVariableSet {
+    BddVariableSet::new(vec!["a", "b", "c", "d"])
+}
+
+/// The fixture `Bdd`s, together with their names.
+pub fn fixture_bdds() -> Vec<(&'static str, Bdd)> {
+    let variables = fixture_variables();
+    FIXTURES
+        .iter()
+        .map(|(name, expression)| (*name, variables.eval_expression_string(expression)))
+        .collect()
+}
+
+/// Write the fixture files into the given directory (which must exist). Existing files with
+/// the same names are overwritten.
+pub fn write_fixtures(directory: &Path) -> Result<(), String> {
+    for (name, file, data) in expected_files() {
+        let path = directory.join(format!("{}.{}", name, file));
+        std::fs::write(&path, data)
+            .map_err(|e| format!("Cannot write `{}`: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Check the fixture files in the given directory against the current implementation.
+///
+/// Every file must be identical to the file that `write_fixtures` would create, and the text,
+/// binary and `.nnf` files must be readable and yield the fixture `Bdd`. If some check fails,
+/// the error describes all differences (one per line).
+pub fn verify_fixtures(directory: &Path) -> Result<(), String> {
+    let variables = fixture_variables();
+    let bdds = fixture_bdds();
+    let mut errors: Vec<String> = Vec::new();
+    for (name, file, expected) in expected_files() {
+        let path = directory.join(format!("{}.{}", name, file));
+        let stored = match std::fs::read(&path) {
+            Ok(stored) => stored,
+            Err(e) => {
+                errors.push(format!("Cannot read `{}`: {}", path.display(), e));
+                continue;
+            }
+        };
+        if stored != expected {
+            errors.push(format!(
+                "`{}` differs from the current format.",
+                path.display()
+            ));
+        }
+        let read = match file {
+            "txt" => Some(Bdd::read_as_string(&mut stored.as_slice())),
+            "bin" => Some(Bdd::read_as_bytes(&mut stored.as_slice()).map_err(|e| e.to_string())),
+            "nnf" => Some(variables.read_nnf(&mut stored.as_slice())),
+            _ => None,
+        };
+        let bdd = &bdds.iter().find(|(n, _)| *n == name).unwrap().1;
+        match read {
+            Some(Ok(read)) if &read != bdd => {
+                errors.push(format!("`{}` is read as a different Bdd.", path.display()))
+            }
+            Some(Err(e)) => errors.push(format!("Cannot parse `{}`: {}", path.display(), e)),
+            _ => {}
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// **(internal)** The name, file extension and content of every fixture file.
+fn expected_files() -> Vec<(&'static str, &'static str, Vec<u8>)> {
+    let variables = fixture_variables();
+    let mut files = Vec::new();
+    for (name, bdd) in fixture_bdds() {
+        files.push((name, "txt", bdd.to_string().into_bytes()));
+        files.push((name, "bin", bdd.to_bytes()));
+        files.push((
+            name,
+            "dot",
+            bdd.to_dot_string(&variables, true).into_bytes(),
+        ));
+        files.push((name, "nnf", bdd.to_nnf_string().into_bytes()));
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conformance_fixtures_match() {
+        verify_fixtures(Path::new("res/conformance")).unwrap();
+    }
+
+    #[test]
+    fn conformance_detects_changes() {
+        let directory = std::env::temp_dir().join(format!(
+            "biodivine-lib-bdd-conformance-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&directory).unwrap();
+        write_fixtures(&directory).unwrap();
+        verify_fixtures(&directory).unwrap();
+        std::fs::write(directory.join("cube.txt"), "|2,0,0|2,1,1|").unwrap();
+        std::fs::remove_file(directory.join("mixed.bin")).unwrap();
+        let errors = verify_fixtures(&directory).unwrap_err();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(3, errors.lines().count(), "{}", errors);
+        assert!(errors.contains("cube.txt"));
+        assert!(errors.contains("mixed.bin"));
+    }
+}
@@ -0,0 +1,167 @@
+//! Analysis of feature models (product lines) represented as `Bdd`s.
+//!
+//! Every variable of the `Bdd` is a feature and the `Bdd` describes the valid configurations
+//! of the product line. The functions in this module compute the standard feature model
+//! metrics: core and dead features, atomic sets and feature commonality.
+
+use crate::{Bdd, BddPartialValuation, BddVariable};
+
+/// Features which are selected in every valid configuration.
+///
+/// If there is no valid configuration, the result is empty.
+pub fn core_features(model: &Bdd) -> Vec<BddVariable> {
+    forced_features(model, true)
+}
+
+/// Features which are not selected in any valid configuration.
+///
+/// If there is no valid configuration, the result is empty.
+pub fn dead_features(model: &Bdd) -> Vec<BddVariable> {
+    forced_features(model, false)
+}
+
+/// Partition the features into atomic sets: features in the same set are either all selected
+/// or all deselected in every valid configuration.
+///
+/// The sets are sorted by their smallest feature and each set is sorted as well. Note that all
+/// core features form one atomic set and all dead features form another. If there is no valid
+/// configuration, the result is empty.
+pub fn atomic_sets(model: &Bdd) -> Vec<Vec<BddVariable>> {
+    let witness = match model.sat_witness() {
+        Some(witness) => witness,
+        None => return Vec::new(),
+    };
+    let mut sets: Vec<Vec<BddVariable>> = Vec::new();
+    for i in 0..model.num_vars() {
+        let var = BddVariable(i);
+        // Two features can only be in the same set if they have the same value in
+        // the witness. If so, check that they can never differ.
+        let same_set = sets
+            .iter_mut()
+            .find(|set| witness[set[0]] == witness[var] && never_differ(model, set[0], var));
+        match same_set {
+            Some(set) => set.push(var),
+            None => sets.push(vec![var]),
+        }
+    }
+    sets
+}
+
+/// The commonality of every feature: the fraction of valid configurations in which
+/// the feature is selected (indexed by variable).
+///
+/// All ratios are computed in one pass over the `Bdd` (similar to `Bdd::cardinality`, the
+/// numbers are approximate for very large models). If there is no valid configuration,
+/// the result is empty.
+pub fn commonality(model: &Bdd) -> Vec<f64> {
+    if model.is_false() {
+        return Vec::new();
+    }
+    let num_vars = usize::from(model.num_vars());
+    let level = |node| usize::from(model.var_of(node).0);
+    let skip = |from: usize, to: usize| 2.0_f64.powi((to - from - 1) as i32);
+
+    // Number of valuations of the variables below (and including) each node that lead to `1`.
+    let mut below = vec![0.0; model.size()];
+    below[1] = 1.0;
+    for node in model.pointers().skip(2) {
+        let (low, high) = (model.low_link_of(node), model.high_link_of(node));
+        below[node.to_index()] = below[low.to_index()] * skip(level(node), level(low))
+            + below[high.to_index()] * skip(level(node), level(high));
+    }
+    // Number of valuations of the variables above each node that lead to the node.
+    let root = model.root_pointer();
+    let mut above = vec![0.0; model.size()];
+    above[root.to_index()] = 2.0_f64.powi(level(root) as i32);
+    let total = above[root.to_index()] * below[root.to_index()];
+
+    // Variables skipped by an edge are selected in half of the valuations flowing through
+    // the edge. These are accumulated in a difference array and resolved at the end.
+    let mut selected = vec![0.0; num_vars];
+    let mut skipped = vec![0.0; num_vars + 1];
+    skipped[0] += total / 2.0;
+    skipped[level(root)] -= total / 2.0;
+    for node in model.pointers().skip(2).rev() {
+        let var = level(node);
+        for (child, is_high) in [
+            (model.low_link_of(node), false),
+            (model.high_link_of(node), true),
+        ]
+        .iter()
+        {
+            let paths = above[node.to_index()] * skip(var, level(*child));
+            above[child.to_index()] += paths;
+            let flow = paths * below[child.to_index()];
+            if *is_high {
+                selected[var] += flow;
+            }
+            skipped[var + 1] += flow / 2.0;
+            skipped[level(*child)] -= flow / 2.0;
+        }
+    }
+    let mut skipping = 0.0;
+    (0..num_vars)
+        .map(|i| {
+            skipping += skipped[i];
+            (selected[i] + skipping) / total
+        })
+        .collect()
+}
+
+/// **(internal)** Features which have the given value in all valid configurations.
+fn forced_features(model: &Bdd, value: bool) -> Vec<BddVariable> {
+    model
+        .valid_domains(&BddPartialValuation::empty())
+        .into_iter()
+        .filter(|(_, forced)| *forced == Some(value))
+        .map(|(var, _)| var)
+        .collect()
+}
+
+/// **(internal)** True if the two features have the same value in every valid configuration.
+fn never_differ(model: &Bdd, a: BddVariable, b: BddVariable) -> bool {
+    let num_vars = model.num_vars();
+    let differ = Bdd::mk_var(num_vars, a).xor(&Bdd::mk_var(num_vars, b));
+    model.and(&differ).is_false()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::_test_util::mk_5_variable_set;
+
+    #[test]
+    fn feature_model_metrics() {
+        let variables = mk_5_variable_set();
+        let v: Vec<BddVariable> = variables.variables();
+        // v1 is the root feature, v2 and v3 are mandatory together, v4 excludes v1
+        // and v5 is optional.
+        let model = variables.eval_expression_string("v1 & (v2 <=> v3) & (v2 => v1) & !v4");
+        assert_eq!(vec![v[0]], core_features(&model));
+        assert_eq!(vec![v[3]], dead_features(&model));
+        assert_eq!(
+            vec![vec![v[0]], vec![v[1], v[2]], vec![v[3]], vec![v[4]]],
+            atomic_sets(&model)
+        );
+        let expected: Vec<f64> = v
+            .iter()
+            .map(|var| model.var_select(*var, true).cardinality() / model.cardinality())
+            .collect();
+        assert_eq!(vec![1.0, 0.5, 0.5, 0.0, 0.5], expected);
+        assert_eq!(expected, commonality(&model));
+
+        let model = variables.eval_expression_string("(v1 | v3) & (v2 => v5) & (v4 ^ v1)");
+        let expected: Vec<f64> = v
+            .iter()
+            .map(|var| model.var_select(*var, true).cardinality() / model.cardinality())
+            .collect();
+        assert_eq!(expected, commonality(&model));
+        assert_eq!(vec![0.5; 5], commonality(&variables.mk_true()));
+
+        let empty = variables.mk_false();
+        assert!(core_features(&empty).is_empty());
+        assert!(dead_features(&empty).is_empty());
+        assert!(atomic_sets(&empty).is_empty());
+        assert!(commonality(&empty).is_empty());
+    }
+}
@@ -6,6 +6,29 @@
 //! serialise, but also to share between threads. This makes it useful for applications that
 //! process high number of BDDs concurrently.
 //!
+//! All public types of this crate are `Send` and `Sync` and none of them use interior
+//! mutability: every operation that does not explicitly mutate its operand (like
+//! `Bdd::and_assign`) takes `&self`. A `Bdd` can be therefore shared between threads using
+//! plain references or `Arc<Bdd>` without any locking:
+//!
+//! ```rust
+//! use biodivine_lib_bdd::*;
+//! use std::sync::Arc;
+//!
+//! let vars = BddVariableSet::new(vec!["a", "b", "c"]);
+//! let shared = Arc::new(vars.eval_expression_string("(a & b) | c"));
+//! let handles: Vec<_> = vars
+//!     .variables()
+//!     .into_iter()
+//!     .map(|v| {
+//!         let bdd = shared.clone();
+//!         std::thread::spawn(move || bdd.var_project(v).cardinality())
+//!     })
+//!     .collect();
+//! let counts: Vec<f64> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+//! assert_eq!(vec![6.0, 6.0, 8.0], counts);
+//! ```
+//!
 //! We currently provide support for explicit operations as well as evaluation of basic boolean
 //! expressions and a custom `bdd` macro for hybrid usage:
 //!
@@ -34,9 +57,15 @@
 //! There is also an experimental support for converting BDDs back into boolean expressions.
 
 use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
 
 pub mod boolean_expression;
+pub mod conformance;
+pub mod feature_model;
 pub mod op_function;
+pub mod prelude;
+#[cfg(feature = "regression")]
+pub mod regression;
 pub mod tutorial;
 
 /// **(internal)** Implementations for the `Bdd` struct.
@@ -55,6 +84,9 @@ mod _impl_bdd_pointer;
 /// **(internal)** Implementation of the `BddValuation`.
 mod _impl_bdd_valuation;
 
+/// **(internal)** Implementation of the `BddPartialValuation`.
+mod _impl_bdd_partial_valuation;
+
 /// **(internal)** Implementation of the `BddValuationsIterator`.
 mod _impl_bdd_satisfying_valuations;
 
@@ -64,9 +96,33 @@ mod _impl_bdd_variable;
 /// **(internal)** Implementation of the `BddVariableSet`.
 mod _impl_bdd_variable_set;
 
+/// **(internal)** Implementation of the `BddSubUniverse`.
+mod _impl_bdd_sub_universe;
+
 /// **(internal)** Implementation of the `BddVariableSetBuilder`.
 mod _impl_bdd_variable_set_builder;
 
+/// **(internal)** Implementation of the `Bdd3`.
+mod _impl_bdd3;
+
+/// **(internal)** Implementation of the `BddInterval`.
+mod _impl_bdd_interval;
+
+/// **(internal)** Implementation of the `TypedBdd`.
+mod _impl_typed_bdd;
+
+/// **(internal)** Implementation of the `BddExpr`.
+mod _impl_bdd_expr;
+
+/// **(internal)** Implementation of the `ConstraintPool`.
+mod _impl_constraint_pool;
+
+/// **(internal)** Implementation of the `FrozenBdd`.
+mod _impl_frozen_bdd;
+
+/// **(internal)** Implementation of the `BddComputation`.
+mod _impl_bdd_computation;
+
 /// **(internal)** A macro module for simplifying BDD operations.
 mod _macro_bdd;
 
@@ -95,19 +151,287 @@ pub struct BddVariable(u16);
 #[derive(Clone, Debug, Eq, Hash, PartialEq, Ord, PartialOrd)]
 pub struct BddValuation(Vec<bool>);
 
+/// Assigns boolean values to some (not necessarily all) variables of a `Bdd`.
+///
+/// Variables without a value are unconstrained, so a partial valuation describes
+/// a conjunctive clause (a cube) of literals.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BddPartialValuation(Vec<Option<bool>>);
+
+/// Determines the order in which `Bdd::project_ordered` eliminates variables.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum QuantificationOrder {
+    /// Eliminate the variables from the last to the first one (the same order as `Bdd::project`).
+    BottomUp,
+    /// Eliminate the variables exactly in the given order.
+    AsGiven,
+    /// Always eliminate the variable with the fewest decision nodes in the current
+    /// intermediate result, re-counting the nodes after every step.
+    FewestNodes,
+}
+
+/// Configures the adaptive schedule of `Bdd::exists_clustered`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClusteringConfig {
+    cluster_size: usize,
+    growth_limit: f64,
+}
+
+/// The error returned by the cancellable operations (like `Bdd::cancellable_and`) when they
+/// are cancelled before the result is computed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Cancelled;
+
+/// Receives progress reports from long-running operations, like `Bdd::and_with_progress`.
+///
+/// An operation processes *tasks* (pairs of nodes of the operands). Each task is eventually
+/// resolved to a node of the result, but new tasks are discovered while the operation runs,
+/// so the total number of tasks is not known in advance. A stall can be recognized as
+/// a growing number of processed tasks which does not increase the size of the result.
+pub trait ApplyProgress {
+    /// The number of processed tasks between two consecutive reports.
+    fn interval(&self) -> usize {
+        1 << 12
+    }
+
+    /// Called after every `interval` processed tasks with the total number of processed
+    /// tasks, the number of tasks which are waiting to be processed, and the number of nodes
+    /// of the partial result.
+    fn report(&mut self, processed_tasks: usize, pending_tasks: usize, result_size: usize);
+}
+
+/// Describes how `Bdd::map_nodes` rewrites one decision node of a `Bdd`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NodeRewrite {
+    /// Keep the decision, using the rewritten children.
+    Keep,
+    /// Replace the node with its (rewritten) low child.
+    Low,
+    /// Replace the node with its (rewritten) high child.
+    High,
+    /// Keep the decision, but exchange the (rewritten) low and high child.
+    Swap,
+    /// Keep the decision and the (rewritten) children, but test a different variable.
+    Rename(BddVariable),
+    /// Replace the node with a constant.
+    Constant(bool),
+}
+
 /// Exhaustively iterates over all valuations with a certain number of variables.
 ///
+/// The valuations are produced by counting in binary, where the first variable is the least
+/// significant bit (i.e. the first variable changes in every step). This order is guaranteed.
+///
 /// Be aware of the exponential time complexity of such operation!
 pub struct BddValuationIterator(Option<BddValuation>);
 
 /// An iterator over all satisfying valuations of a specific BDD.
 ///
+/// The valuations are produced in lexicographic order with respect to the variable ordering
+/// (i.e. in increasing order of `BddValuation`). This order is guaranteed.
+///
 /// Be aware of the potential exponential number of iterations!
 pub struct BddSatisfyingValuations<'a> {
     bdd: &'a Bdd,
-    continuation: Option<(Vec<BddPointer>, BddValuation, BddValuation)>,
+    /// The current valuation and the nodes reached by following it (the node at position `k` is
+    /// reached after fixing the first `k` variables).
+    continuation: Option<(Vec<BddPointer>, BddValuation)>,
 }
 
+/// A `Bdd` tagged at compile time with a marker type `U` identifying its variable set.
+///
+/// This is a zero-cost wrapper around a `Bdd` which only allows logical operations between
+/// `Bdd`s with the same marker. As a result, mixing `Bdd`s created in different
+/// `BddVariableSet`s is caught by the compiler instead of producing a runtime panic (or worse,
+/// a silently wrong result when the two sets have the same number of variables).
+///
+/// ```rust
+/// use biodivine_lib_bdd::*;
+///
+/// struct States;
+///
+/// let vars = BddVariableSet::new(vec!["a", "b"]);
+/// let a: TypedBdd<States> = TypedBdd::from_bdd(vars.mk_var_by_name("a"));
+/// let b: TypedBdd<States> = TypedBdd::from_bdd(vars.mk_var_by_name("b"));
+/// assert_eq!(a.and(&b).into_bdd(), vars.eval_expression_string("a & b"));
+/// ```
+///
+/// ```compile_fail
+/// use biodivine_lib_bdd::*;
+///
+/// struct States;
+/// struct Parameters;
+///
+/// let vars = BddVariableSet::new(vec!["a", "b"]);
+/// let a: TypedBdd<States> = TypedBdd::from_bdd(vars.mk_var_by_name("a"));
+/// let b: TypedBdd<Parameters> = TypedBdd::from_bdd(vars.mk_var_by_name("b"));
+/// a.and(&b); // Error: `Parameters` and `States` are different universes.
+/// ```
+#[repr(transparent)]
+pub struct TypedBdd<U> {
+    bdd: Bdd,
+    universe: PhantomData<fn() -> U>,
+}
+
+/// A three-valued (Kleene) Boolean function, where each valuation evaluates to `true`, `false`
+/// or *unknown*.
+///
+/// Internally, the function is represented by two `Bdd`s: an under-approximation (valuations
+/// that are definitely `true`) and an over-approximation (valuations that are `true` or
+/// unknown). The under-approximation is always a subset of the over-approximation.
+/// The logical operators follow the Kleene strong three-valued semantics, i.e. unknown values
+/// only propagate if they can influence the result:
+///
+/// ```rust
+/// use biodivine_lib_bdd::*;
+///
+/// let vars = BddVariableSet::new(vec!["a", "b"]);
+/// let a = Bdd3::from(vars.mk_var_by_name("a"));
+/// let unknown = Bdd3::from_approximations(vars.mk_false(), vars.mk_true());
+/// let result = a.and(&unknown);
+///
+/// let a_false = BddValuation::new(vec![false, false]);
+/// let a_true = BddValuation::new(vec![true, false]);
+/// assert_eq!(Some(false), result.eval_in(&a_false));
+/// assert_eq!(None, result.eval_in(&a_true));
+/// assert_eq!(vars.mk_var_by_name("a"), result.unknown_set());
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Bdd3 {
+    under: Bdd,
+    over: Bdd,
+}
+
+/// An interval of `Bdd`s, i.e. an abstraction of an unknown set $X$ such that
+/// $\texttt{lower} \subseteq X \subseteq \texttt{upper}$.
+///
+/// All operations on intervals are sound: the result of an operation always contains the
+/// results of the same operation applied to all sets from the operand intervals. This makes
+/// intervals useful for abstraction-based analyses that need to track an under- and
+/// over-approximation of the same set at the same time.
+///
+/// ```rust
+/// use biodivine_lib_bdd::*;
+///
+/// let vars = BddVariableSet::new(vec!["a", "b"]);
+/// let x = BddInterval::new(vars.eval_expression_string("a & b"), vars.mk_var_by_name("a"));
+/// let y = BddInterval::from(vars.mk_var_by_name("b"));
+/// let union = x.or(&y);
+/// assert_eq!(&vars.mk_var_by_name("b"), union.lower());
+/// assert_eq!(&vars.eval_expression_string("a | b"), union.upper());
+/// assert!(union.contains(&vars.eval_expression_string("(a & !b) | b")));
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BddInterval {
+    lower: Bdd,
+    upper: Bdd,
+}
+
+/// A lazily evaluated expression over `Bdd`s.
+///
+/// Logical operators on `Bdd` references (`&`, `|`, `^`, `!`) produce a `BddExpr` which only
+/// records the operation tree. The result is computed by `BddExpr::build`, which picks
+/// the evaluation order automatically: chains of the same operator are evaluated starting
+/// with the smallest operands and quantified variables are eliminated as early as possible.
+///
+/// ```rust
+/// use biodivine_lib_bdd::*;
+///
+/// let vars = BddVariableSet::new(vec!["a", "b", "c"]);
+/// let a = vars.mk_var_by_name("a");
+/// let b = vars.mk_var_by_name("b");
+/// let c = vars.mk_var_by_name("c");
+/// let b_var = vars.var_by_name("b").unwrap();
+/// let expr = (&a & &b & !&c).exists(&[b_var]);
+/// assert_eq!(vars.eval_expression_string("a & !c"), expr.build());
+/// ```
+#[derive(Clone, Debug)]
+pub struct BddExpr(_impl_bdd_expr::ExprNode);
+
+/// A collection of `Bdd` constraints which represents their conjunction without computing it.
+///
+/// Adding a constraint only merges it with an existing constraint that shares variables with it,
+/// and only if the merged `Bdd` stays small. Queries then work with the (mostly small)
+/// constraints and stop as soon as the answer is known, so the full conjunction typically
+/// never has to be built.
+///
+/// ```rust
+/// use biodivine_lib_bdd::*;
+///
+/// let vars = BddVariableSet::new(vec!["a", "b", "c"]);
+/// let mut pool = ConstraintPool::new();
+/// pool.add(vars.eval_expression_string("a => b"));
+/// pool.add(vars.eval_expression_string("b => c"));
+/// assert!(pool.implied(&vars.eval_expression_string("a => c")));
+/// let a = vars.var_by_name("a").unwrap();
+/// let c = vars.var_by_name("c").unwrap();
+/// assert!(!pool.is_sat_under(&BddPartialValuation::from_values(&[(a, true), (c, false)])));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConstraintPool {
+    constraints: Vec<Bdd>,
+    merge_limit: usize,
+}
+
+/// A read-only `Bdd` with precomputed data which speeds up repeated queries: evaluation,
+/// counting, indexing and (with the `sampling` feature) uniform sampling of satisfying
+/// valuations (see `Bdd::freeze`).
+///
+/// ```rust
+/// use biodivine_lib_bdd::*;
+///
+/// let vars = BddVariableSet::new(vec!["a", "b", "c"]);
+/// let frozen = vars.eval_expression_string("a ^ c").freeze();
+/// assert_eq!(4.0, frozen.cardinality());
+/// let second = frozen.sat_valuation_at(1).unwrap();
+/// assert_eq!(BddValuation::new(vec![false, true, true]), second);
+/// assert!(frozen.eval_in(&second));
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrozenBdd {
+    bdd: Bdd,
+    /// Decision variable of every node.
+    vars: Vec<u16>,
+    /// Low and high link of every node, indexed by the value of the decision variable.
+    links: Vec<[u32; 2]>,
+    /// Fraction of all valuations which satisfy each node (independent of the node level).
+    densities: Vec<f64>,
+    /// Number of satisfying valuations of the variables from the node variable (inclusive)
+    /// below, if the cardinality of the `Bdd` fits into `u128`.
+    counts: Option<Vec<u128>>,
+}
+
+/// A graph of `Bdd` operations over changeable inputs, which caches all intermediate results.
+///
+/// When an input changes, only the operations which (transitively) depend on it are
+/// invalidated, and they are only recomputed once their value is requested again.
+///
+/// ```rust
+/// use biodivine_lib_bdd::*;
+/// use biodivine_lib_bdd::op_function::BooleanOp;
+///
+/// let vars = BddVariableSet::new(vec!["a", "b", "c"]);
+/// let mut computation = BddComputation::new();
+/// let a = computation.add_input(vars.eval_expression_string("a"));
+/// let b = computation.add_input(vars.eval_expression_string("b"));
+/// let c = computation.add_input(vars.eval_expression_string("c"));
+/// let a_and_b = computation.add_op(BooleanOp::And, a, b);
+/// let result = computation.add_op(BooleanOp::Or, a_and_b, c);
+/// assert_eq!(&vars.eval_expression_string("(a & b) | c"), computation.value(result));
+/// computation.set_input(c, vars.eval_expression_string("!c"));
+/// // The conjunction is not affected by the change and stays cached.
+/// assert!(computation.is_computed(a_and_b));
+/// assert_eq!(&vars.eval_expression_string("(a & b) | !c"), computation.value(result));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BddComputation {
+    nodes: Vec<_impl_bdd_computation::ComputationNode>,
+}
+
+/// Identifies an input or an operation of a `BddComputation`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ComputationId(usize);
+
 /// Maintains the set of variables that can appear in a `Bdd`.
 /// Used to create new `Bdd`s for basic formulas.
 #[derive(Clone)]
@@ -117,6 +441,30 @@ pub struct BddVariableSet {
     var_index_mapping: HashMap<String, u16>,
 }
 
+/// A view of a `BddVariableSet` restricted to a subset of its variables.
+///
+/// The sub-universe creates `Bdd`s which only depend on the variables in its scope, but are
+/// valid in the parent `BddVariableSet` (variables outside of the scope are unconstrained).
+/// This is useful for modular encodings where each component only constrains its own
+/// variables:
+///
+/// ```rust
+/// use biodivine_lib_bdd::*;
+///
+/// let vars = BddVariableSet::new(vec!["a", "b", "c", "d"]);
+/// let a = vars.var_by_name("a").unwrap();
+/// let b = vars.var_by_name("b").unwrap();
+/// let component = vars.scoped(&[a, b]);
+/// let constraint = component.eval_expression_string("a ^ b");
+/// assert_eq!(vars.eval_expression_string("a ^ b"), constraint);
+/// assert_eq!(2, component.valuations(&constraint).len());
+/// ```
+#[derive(Clone)]
+pub struct BddSubUniverse<'a> {
+    parent: &'a BddVariableSet,
+    variables: Vec<BddVariable>,
+}
+
 /// Used to safely initialize `BddVariableSet`.
 ///
 /// Note that some characters are not allowed in variable names (to allow safe serialisation,
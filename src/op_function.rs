@@ -1,5 +1,8 @@
 //! Contains simple functions that can be used with `apply` and `fused_flip_apply` to
 //! implement basic logical operations.
+//!
+//! There is one function for each of the 16 binary Boolean operators. If the operator needs
+//! to be selected at runtime, use the `BooleanOp` enum, which maps to these functions.
 
 /// Partial operator function corresponding to $x \land y$.
 pub fn and(l: Option<bool>, r: Option<bool>) -> Option<bool> {
@@ -21,7 +24,7 @@ pub fn or(l: Option<bool>, r: Option<bool>) -> Option<bool> {
     }
 }
 
-/// Partial operator function corresponding to $x \Leftarrow y$.
+/// Partial operator function corresponding to $x \Rightarrow y$.
 pub fn imp(l: Option<bool>, r: Option<bool>) -> Option<bool> {
     match (l, r) {
         (Some(true), Some(false)) => Some(false),
@@ -56,3 +59,360 @@ pub fn and_not(l: Option<bool>, r: Option<bool>) -> Option<bool> {
         _ => None,
     }
 }
+
+/// Partial operator function corresponding to the constant $0$ (`false`).
+pub fn always_false(_l: Option<bool>, _r: Option<bool>) -> Option<bool> {
+    Some(false)
+}
+
+/// Partial operator function corresponding to the constant $1$ (`true`).
+pub fn always_true(_l: Option<bool>, _r: Option<bool>) -> Option<bool> {
+    Some(true)
+}
+
+/// Partial operator function corresponding to $x$ (projection to the left operand).
+pub fn left(l: Option<bool>, _r: Option<bool>) -> Option<bool> {
+    l
+}
+
+/// Partial operator function corresponding to $y$ (projection to the right operand).
+pub fn right(_l: Option<bool>, r: Option<bool>) -> Option<bool> {
+    r
+}
+
+/// Partial operator function corresponding to $\neg x$.
+pub fn not_left(l: Option<bool>, _r: Option<bool>) -> Option<bool> {
+    l.map(|l| !l)
+}
+
+/// Partial operator function corresponding to $\neg y$.
+pub fn not_right(_l: Option<bool>, r: Option<bool>) -> Option<bool> {
+    r.map(|r| !r)
+}
+
+/// Partial operator function corresponding to $\neg (x \land y)$.
+pub fn nand(l: Option<bool>, r: Option<bool>) -> Option<bool> {
+    and(l, r).map(|value| !value)
+}
+
+/// Partial operator function corresponding to $\neg (x \lor y)$.
+pub fn nor(l: Option<bool>, r: Option<bool>) -> Option<bool> {
+    or(l, r).map(|value| !value)
+}
+
+/// Partial operator function corresponding to $x \Leftarrow y$ (converse implication).
+pub fn rev_imp(l: Option<bool>, r: Option<bool>) -> Option<bool> {
+    imp(r, l)
+}
+
+/// Partial operator function corresponding to $\neg x \land y$ (converse non-implication).
+pub fn rev_and_not(l: Option<bool>, r: Option<bool>) -> Option<bool> {
+    and_not(r, l)
+}
+
+/// Enumerates all 16 binary Boolean operators, so that an operator can be selected
+/// dynamically at runtime:
+///
+/// ```rust
+/// use biodivine_lib_bdd::*;
+/// use biodivine_lib_bdd::op_function::BooleanOp;
+///
+/// let vars = BddVariableSet::new_anonymous(2);
+/// let a = vars.mk_var_by_name("x_0");
+/// let b = vars.mk_var_by_name("x_1");
+/// for op in BooleanOp::ALL.iter() {
+///     let result = Bdd::binary_op(&a, &b, op.function());
+///     assert_eq!(result, Bdd::binary_op(&b, &a, op.swap().function()));
+/// }
+/// assert_eq!(a.and(&b).not(), Bdd::binary_op(&a, &b, BooleanOp::Nand.function()));
+/// ```
+///
+/// Each operator is uniquely identified by its truth table (see `BooleanOp::truth_table`).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum BooleanOp {
+    /// $0$, see `always_false`.
+    False,
+    /// $\neg (x \lor y)$, see `nor`.
+    Nor,
+    /// $\neg x \land y$, see `rev_and_not`.
+    RevAndNot,
+    /// $\neg x$, see `not_left`.
+    NotLeft,
+    /// $x \land \neg y$, see `and_not`.
+    AndNot,
+    /// $\neg y$, see `not_right`.
+    NotRight,
+    /// $x \not\Leftrightarrow y$, see `xor`.
+    Xor,
+    /// $\neg (x \land y)$, see `nand`.
+    Nand,
+    /// $x \land y$, see `and`.
+    And,
+    /// $x \Leftrightarrow y$, see `iff`.
+    Iff,
+    /// $y$, see `right`.
+    Right,
+    /// $x \Rightarrow y$, see `imp`.
+    Imp,
+    /// $x$, see `left`.
+    Left,
+    /// $x \Leftarrow y$, see `rev_imp`.
+    RevImp,
+    /// $x \lor y$, see `or`.
+    Or,
+    /// $1$, see `always_true`.
+    True,
+}
+
+impl BooleanOp {
+    /// All binary Boolean operators, ordered by their truth table.
+    pub const ALL: [BooleanOp; 16] = [
+        BooleanOp::False,
+        BooleanOp::Nor,
+        BooleanOp::RevAndNot,
+        BooleanOp::NotLeft,
+        BooleanOp::AndNot,
+        BooleanOp::NotRight,
+        BooleanOp::Xor,
+        BooleanOp::Nand,
+        BooleanOp::And,
+        BooleanOp::Iff,
+        BooleanOp::Right,
+        BooleanOp::Imp,
+        BooleanOp::Left,
+        BooleanOp::RevImp,
+        BooleanOp::Or,
+        BooleanOp::True,
+    ];
+
+    /// The partial operator function implementing this operator, usable with `Bdd::binary_op`.
+    pub fn function(self) -> fn(Option<bool>, Option<bool>) -> Option<bool> {
+        match self {
+            BooleanOp::False => always_false,
+            BooleanOp::Nor => nor,
+            BooleanOp::RevAndNot => rev_and_not,
+            BooleanOp::NotLeft => not_left,
+            BooleanOp::AndNot => and_not,
+            BooleanOp::NotRight => not_right,
+            BooleanOp::Xor => xor,
+            BooleanOp::Nand => nand,
+            BooleanOp::And => and,
+            BooleanOp::Iff => iff,
+            BooleanOp::Right => right,
+            BooleanOp::Imp => imp,
+            BooleanOp::Left => left,
+            BooleanOp::RevImp => rev_imp,
+            BooleanOp::Or => or,
+            BooleanOp::True => always_true,
+        }
+    }
+
+    /// Evaluate this operator for the given (partially known) operands.
+    pub fn apply(self, l: Option<bool>, r: Option<bool>) -> Option<bool> {
+        self.function()(l, r)
+    }
+
+    /// Evaluate this operator for two known operands.
+    pub fn eval(self, l: bool, r: bool) -> bool {
+        self.truth_table() & (1 << Self::table_index(l, r)) != 0
+    }
+
+    /// The truth table of this operator, encoded as four bits: bit $2x + y$ is the
+    /// value of $x \circ y$.
+    ///
+    /// This is also the index of the operator in `BooleanOp::ALL`.
+    pub fn truth_table(self) -> u8 {
+        self as u8
+    }
+
+    /// Find the operator with the given truth table (see `BooleanOp::truth_table`).
+    /// Returns `None` if the table has more than four bits.
+    pub fn from_truth_table(table: u8) -> Option<BooleanOp> {
+        Self::ALL.get(usize::from(table)).cloned()
+    }
+
+    /// The operator with swapped operands, i.e. $y \circ x$.
+    pub fn swap(self) -> BooleanOp {
+        let mut table: u8 = 0;
+        for (l, r) in TERMINAL_PAIRS.iter() {
+            if self.eval(*r, *l) {
+                table |= 1 << Self::table_index(*l, *r);
+            }
+        }
+        BooleanOp::ALL[usize::from(table)]
+    }
+
+    /// The operator with negated result, i.e. $\neg (x \circ y)$.
+    pub fn negate(self) -> BooleanOp {
+        BooleanOp::ALL[usize::from(!self.truth_table() & 0b1111)]
+    }
+
+    /// True if the operator gives the same result regardless of the order of its operands.
+    pub fn is_commutative(self) -> bool {
+        self.swap() == self
+    }
+
+    /// True if the operator is monotone in both operands, i.e. changing any operand from
+    /// `false` to `true` never changes the result from `true` to `false`.
+    pub fn is_monotone(self) -> bool {
+        TERMINAL_PAIRS
+            .iter()
+            .all(|(l, r)| !self.eval(*l, *r) || (self.eval(true, *r) && self.eval(*l, true)))
+    }
+
+    /// Identify the operator implemented by a custom partial operator function, checking
+    /// that the function is valid for use with `Bdd::binary_op`.
+    ///
+    /// The function is evaluated in all nine combinations of (partially known) operands. It
+    /// must return a value whenever both operands are known, and when it returns a value for
+    /// a partially known combination, that value must agree with all of its completions
+    /// (otherwise, `apply` would silently produce wrong results). Returning `None` for
+    /// a partially known combination is always valid, it only prevents `apply` from
+    /// stopping early. On success, the corresponding `BooleanOp` is returned, which can be
+    /// used to examine the properties of the operator (e.g. `is_commutative`).
+    pub fn from_function<F>(function: F) -> Result<BooleanOp, String>
+    where
+        F: Fn(Option<bool>, Option<bool>) -> Option<bool>,
+    {
+        let mut table: u8 = 0;
+        for (l, r) in TERMINAL_PAIRS.iter() {
+            match function(Some(*l), Some(*r)) {
+                Some(true) => table |= 1 << Self::table_index(*l, *r),
+                Some(false) => {}
+                None => return Err(format!("No result for ({}, {}).", l, r)),
+            }
+        }
+        let op = BooleanOp::ALL[usize::from(table)];
+        let values = [None, Some(false), Some(true)];
+        for l in values.iter() {
+            for r in values.iter() {
+                if let Some(value) = function(*l, *r) {
+                    let wrong = TERMINAL_PAIRS
+                        .iter()
+                        .filter(|(a, b)| l.unwrap_or(*a) == *a && r.unwrap_or(*b) == *b)
+                        .find(|(a, b)| op.eval(*a, *b) != value);
+                    if let Some((a, b)) = wrong {
+                        return Err(format!(
+                            "Result {} for ({:?}, {:?}) contradicts the result for ({}, {}).",
+                            value, l, r, a, b
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(op)
+    }
+
+    /// **(internal)** Position of the $x \circ y$ value in the truth table.
+    fn table_index(l: bool, r: bool) -> u8 {
+        2 * (l as u8) + (r as u8)
+    }
+}
+
+/// **(internal)** All possible combinations of operand values.
+const TERMINAL_PAIRS: [(bool, bool); 4] =
+    [(false, false), (false, true), (true, false), (true, true)];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boolean_op_functions_match_truth_tables() {
+        let values = [None, Some(false), Some(true)];
+        for op in BooleanOp::ALL.iter() {
+            for l in values.iter() {
+                for r in values.iter() {
+                    // Expected result: if all completions of the unknown operands agree,
+                    // the function has to return this value.
+                    let completions: Vec<bool> = TERMINAL_PAIRS
+                        .iter()
+                        .filter(|(a, b)| l.unwrap_or(*a) == *a && r.unwrap_or(*b) == *b)
+                        .map(|(a, b)| op.eval(*a, *b))
+                        .collect();
+                    let expected = if completions.iter().all(|v| *v == completions[0]) {
+                        Some(completions[0])
+                    } else {
+                        None
+                    };
+                    assert_eq!(expected, op.apply(*l, *r), "{:?}({:?}, {:?})", op, l, r);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn boolean_op_algebra() {
+        assert_eq!(BooleanOp::Imp, BooleanOp::RevImp.swap());
+        assert_eq!(BooleanOp::AndNot, BooleanOp::RevAndNot.swap());
+        assert_eq!(BooleanOp::Left, BooleanOp::Right.swap());
+        assert_eq!(BooleanOp::Nand, BooleanOp::And.negate());
+        assert_eq!(BooleanOp::Iff, BooleanOp::Xor.negate());
+        assert_eq!(BooleanOp::True, BooleanOp::False.negate());
+        assert_eq!(None, BooleanOp::from_truth_table(16));
+        let commutative: Vec<BooleanOp> = BooleanOp::ALL
+            .iter()
+            .cloned()
+            .filter(|op| op.is_commutative())
+            .collect();
+        assert_eq!(
+            vec![
+                BooleanOp::False,
+                BooleanOp::Nor,
+                BooleanOp::Xor,
+                BooleanOp::Nand,
+                BooleanOp::And,
+                BooleanOp::Iff,
+                BooleanOp::Or,
+                BooleanOp::True
+            ],
+            commutative
+        );
+        for op in BooleanOp::ALL.iter() {
+            assert_eq!(Some(*op), BooleanOp::from_truth_table(op.truth_table()));
+            assert_eq!(*op, op.swap().swap());
+        }
+        let monotone: Vec<BooleanOp> = BooleanOp::ALL
+            .iter()
+            .cloned()
+            .filter(|op| op.is_monotone())
+            .collect();
+        assert_eq!(
+            vec![
+                BooleanOp::False,
+                BooleanOp::And,
+                BooleanOp::Right,
+                BooleanOp::Left,
+                BooleanOp::Or,
+                BooleanOp::True
+            ],
+            monotone
+        );
+    }
+
+    #[test]
+    fn boolean_op_from_function() {
+        for op in BooleanOp::ALL.iter() {
+            assert_eq!(Ok(*op), BooleanOp::from_function(op.function()));
+        }
+        // A total function without early termination is valid.
+        let and_total = |l: Option<bool>, r: Option<bool>| match (l, r) {
+            (Some(l), Some(r)) => Some(l && r),
+            _ => None,
+        };
+        assert_eq!(Ok(BooleanOp::And), BooleanOp::from_function(and_total));
+        // Missing result for known operands.
+        let incomplete = |l: Option<bool>, r: Option<bool>| match (l, r) {
+            (Some(true), Some(true)) => None,
+            (Some(l), Some(r)) => Some(l || r),
+            _ => None,
+        };
+        assert!(BooleanOp::from_function(incomplete).is_err());
+        // Wrong early termination: `true | x` is not always `false`.
+        let wrong = |l: Option<bool>, r: Option<bool>| match (l, r) {
+            (Some(true), None) => Some(false),
+            _ => or(l, r),
+        };
+        assert!(BooleanOp::from_function(wrong).is_err());
+    }
+}
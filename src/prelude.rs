@@ -0,0 +1,27 @@
+//! Re-exports the types needed by most users of this crate, so that they can be imported
+//! using a single `use` statement:
+//!
+//! ```rust
+//! use biodivine_lib_bdd::prelude::*;
+//!
+//! let vars = BddVariableSet::new(vec!["a", "b", "c"]);
+//! let a = vars.var_by_name("a").unwrap();
+//! let c = vars.var_by_name("c").unwrap();
+//!
+//! let formula = vars.eval_expression_string("(a | b) & !c");
+//! let clause = vars.mk_disjunctive_clause(&BddPartialValuation::from_values(&[(a, true), (c, false)]));
+//! assert_eq!(formula, formula.and(&clause));
+//! assert_eq!(vars.eval_expression_string("a | b"), formula.project(&[c]));
+//! ```
+//!
+//! Most of the functionality is accessible through the `BddVariableSet`, which creates
+//! the atomic `Bdd`s (variables, literals, cubes and clauses) and parses Boolean expressions,
+//! and through the methods of `Bdd` itself (logical operations, quantification, serialisation).
+
+pub use crate::bdd;
+pub use crate::boolean_expression::BooleanExpression;
+pub use crate::op_function::BooleanOp;
+pub use crate::{
+    Bdd, BddExpr, BddPartialValuation, BddValuation, BddVariable, BddVariableSet,
+    BddVariableSetBuilder,
+};
@@ -0,0 +1,280 @@
+//! Tracking of `Bdd` sizes across runs of a symbolic algorithm (requires the `regression`
+//! feature).
+//!
+//! A `BddRegressionTracker` records the node count and a structural hash of named intermediate
+//! `Bdd`s. The records can be saved into a corpus file and compared with the corpus of
+//! a previous run, so that changes in the size of intermediate results (which typically
+//! dominate the performance of symbolic algorithms) become visible:
+//!
+//! ```rust
+//! use biodivine_lib_bdd::BddVariableSet;
+//! use biodivine_lib_bdd::regression::BddRegressionTracker;
+//!
+//! let vars = BddVariableSet::new(vec!["a", "b", "c"]);
+//! let mut previous = BddRegressionTracker::new();
+//! previous.record("reachable", &vars.eval_expression_string("a & b"));
+//! let mut corpus: Vec<u8> = Vec::new();
+//! previous.write_corpus(&mut corpus).unwrap();
+//!
+//! let previous = BddRegressionTracker::read_corpus(&mut corpus.as_slice()).unwrap();
+//! let mut current = BddRegressionTracker::new();
+//! current.record("reachable", &vars.eval_expression_string("a | (b & c)"));
+//! let changes = current.diff(&previous);
+//! assert_eq!(1, changes.len());
+//! assert_eq!(Some(4), changes[0].previous_size());
+//! assert_eq!(Some(5), changes[0].current_size());
+//! ```
+
+use crate::Bdd;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{Read, Write};
+
+/// Records the sizes and hashes of named `Bdd`s, in the order in which they were recorded.
+///
+/// The same name can be recorded repeatedly (for example, once per iteration of a fixpoint
+/// loop). Records with the same name are distinguished by their occurrence number.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BddRegressionTracker {
+    records: Vec<BddRecord>,
+    occurrences: HashMap<String, usize>,
+}
+
+/// **(internal)** One recorded `Bdd`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct BddRecord {
+    name: String,
+    occurrence: usize,
+    size: usize,
+    hash: u64,
+}
+
+/// A difference between two corpora of a `BddRegressionTracker`: a recorded `Bdd` which
+/// changed its size or structure, or which is only present in one of the corpora.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BddRegressionChange {
+    name: String,
+    occurrence: usize,
+    previous: Option<(usize, u64)>,
+    current: Option<(usize, u64)>,
+}
+
+impl BddRegressionTracker {
+    /// Create a new tracker with no records.
+    pub fn new() -> BddRegressionTracker {
+        BddRegressionTracker::default()
+    }
+
+    /// Record the size and structural hash of the given `Bdd` under the given name.
+    ///
+    /// *Panics:* The name must not be empty or contain tabs and newlines.
+    pub fn record(&mut self, name: &str, bdd: &Bdd) {
+        if name.is_empty() || name.contains(&['\t', '\n', '\r'][..]) {
+            panic!("Invalid record name: {:?}.", name);
+        }
+        let occurrence = self.occurrences.entry(name.to_string()).or_insert(0);
+        self.records.push(BddRecord {
+            name: name.to_string(),
+            occurrence: *occurrence,
+            size: bdd.size(),
+            hash: fxhash::hash64(&bdd.to_bytes()),
+        });
+        *occurrence += 1;
+    }
+
+    /// The number of recorded `Bdd`s.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// True if no `Bdd` was recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Write the records into a corpus file. Each record is written on one line as
+    /// `name<TAB>occurrence<TAB>size<TAB>hash`.
+    pub fn write_corpus(&self, output: &mut dyn Write) -> Result<(), std::io::Error> {
+        for record in &self.records {
+            writeln!(
+                output,
+                "{}\t{}\t{}\t{:016x}",
+                record.name, record.occurrence, record.size, record.hash
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Read the records from a corpus file created by `write_corpus`.
+    pub fn read_corpus(input: &mut dyn Read) -> Result<BddRegressionTracker, String> {
+        let mut data = String::new();
+        input
+            .read_to_string(&mut data)
+            .map_err(|e| format!("Cannot read corpus: {}", e))?;
+        let mut tracker = BddRegressionTracker::new();
+        for (line_number, line) in data.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let error = || format!("Invalid corpus record on line {}.", line_number + 1);
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() != 4 || fields[0].is_empty() {
+                return Err(error());
+            }
+            let occurrence: usize = fields[1].parse().map_err(|_| error())?;
+            let size: usize = fields[2].parse().map_err(|_| error())?;
+            let hash = u64::from_str_radix(fields[3], 16).map_err(|_| error())?;
+            tracker
+                .occurrences
+                .insert(fields[0].to_string(), occurrence + 1);
+            tracker.records.push(BddRecord {
+                name: fields[0].to_string(),
+                occurrence,
+                size,
+                hash,
+            });
+        }
+        Ok(tracker)
+    }
+
+    /// Compare the records of this tracker with the `previous` tracker (typically read
+    /// from the corpus of an earlier run).
+    ///
+    /// The result contains every record (identified by its name and occurrence) whose size
+    /// or hash differs, as well as the records which only appear in one of the trackers.
+    /// The changes are ordered by the records of this tracker, followed by the records which
+    /// only appear in `previous`.
+    pub fn diff(&self, previous: &BddRegressionTracker) -> Vec<BddRegressionChange> {
+        let previous_records: HashMap<(&str, usize), &BddRecord> = previous
+            .records
+            .iter()
+            .map(|r| ((r.name.as_str(), r.occurrence), r))
+            .collect();
+        let current_records: HashMap<(&str, usize), &BddRecord> = self
+            .records
+            .iter()
+            .map(|r| ((r.name.as_str(), r.occurrence), r))
+            .collect();
+        let mut changes = Vec::new();
+        for record in &self.records {
+            let old = previous_records.get(&(record.name.as_str(), record.occurrence));
+            let unchanged =
+                matches!(old, Some(old) if old.size == record.size && old.hash == record.hash);
+            if !unchanged {
+                changes.push(BddRegressionChange {
+                    name: record.name.clone(),
+                    occurrence: record.occurrence,
+                    previous: old.map(|r| (r.size, r.hash)),
+                    current: Some((record.size, record.hash)),
+                });
+            }
+        }
+        for record in &previous.records {
+            if !current_records.contains_key(&(record.name.as_str(), record.occurrence)) {
+                changes.push(BddRegressionChange {
+                    name: record.name.clone(),
+                    occurrence: record.occurrence,
+                    previous: Some((record.size, record.hash)),
+                    current: None,
+                });
+            }
+        }
+        changes
+    }
+}
+
+impl BddRegressionChange {
+    /// The name of the changed record.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The occurrence of the changed record (how many records with the same name
+    /// preceded it).
+    pub fn occurrence(&self) -> usize {
+        self.occurrence
+    }
+
+    /// The node count in the previous corpus, if the record is present there.
+    pub fn previous_size(&self) -> Option<usize> {
+        self.previous.map(|(size, _)| size)
+    }
+
+    /// The node count in the current corpus, if the record is present there.
+    pub fn current_size(&self) -> Option<usize> {
+        self.current.map(|(size, _)| size)
+    }
+
+    /// True if the record is present in both corpora with the same size, i.e. only
+    /// the structure of the `Bdd` changed.
+    pub fn is_structural_only(&self) -> bool {
+        matches!((self.previous, self.current), (Some(p), Some(c)) if p.0 == c.0)
+    }
+}
+
+impl Display for BddRegressionChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}#{}: ", self.name, self.occurrence)?;
+        match (self.previous_size(), self.current_size()) {
+            (Some(p), Some(c)) if p == c => write!(f, "{} nodes (structure changed)", c),
+            (Some(p), Some(c)) => write!(f, "{} -> {} nodes", p, c),
+            (Some(p), None) => write!(f, "{} nodes (removed)", p),
+            (None, Some(c)) => write!(f, "{} nodes (new)", c),
+            (None, None) => write!(f, "no data"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BddRegressionTracker;
+    use crate::_test_util::mk_5_variable_set;
+
+    #[test]
+    fn regression_corpus_round_trip() {
+        let variables = mk_5_variable_set();
+        let mut tracker = BddRegressionTracker::new();
+        assert!(tracker.is_empty());
+        tracker.record("a", &variables.eval_expression_string("v1 & v2"));
+        tracker.record("b", &variables.eval_expression_string("v3 | v4"));
+        tracker.record("a", &variables.eval_expression_string("v1 ^ v5"));
+        assert_eq!(3, tracker.len());
+
+        let mut corpus: Vec<u8> = Vec::new();
+        tracker.write_corpus(&mut corpus).unwrap();
+        let read = BddRegressionTracker::read_corpus(&mut corpus.as_slice()).unwrap();
+        assert_eq!(tracker, read);
+        assert!(tracker.diff(&read).is_empty());
+
+        assert!(BddRegressionTracker::read_corpus(&mut "a\t0\t3".as_bytes()).is_err());
+        assert!(BddRegressionTracker::read_corpus(&mut "a\tx\t3\t0".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn regression_diff() {
+        let variables = mk_5_variable_set();
+        let mut previous = BddRegressionTracker::new();
+        previous.record("a", &variables.eval_expression_string("v1 & v2"));
+        previous.record("b", &variables.eval_expression_string("v3 | v4"));
+        previous.record("c", &variables.eval_expression_string("v5"));
+        let mut current = BddRegressionTracker::new();
+        current.record("a", &variables.eval_expression_string("v1 & v2"));
+        current.record("b", &variables.eval_expression_string("v3 & v4"));
+        current.record("a", &variables.eval_expression_string("v1"));
+
+        let changes = current.diff(&previous);
+        let printed: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            vec![
+                "b#0: 4 nodes (structure changed)",
+                "a#1: 3 nodes (new)",
+                "c#0: 3 nodes (removed)"
+            ],
+            printed
+        );
+        assert!(changes[0].is_structural_only());
+        assert!(!changes[1].is_structural_only());
+        assert_eq!("a", changes[1].name());
+        assert_eq!(1, changes[1].occurrence());
+    }
+}